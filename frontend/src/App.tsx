@@ -27,13 +27,17 @@ function App() {
         const message = JSON.parse(event.data) as WebSocketMessage
         
         switch (message.type) {
-          case 'targets':
-            setTargets(message.targets)
+          case 'tracks':
+            // Track estimates carry the same polar fields as targets
+            setTargets(message.tracks)
             // Clear selection if selected drone is no longer in the list
-            if (selectedDroneId !== null && !message.targets.some(t => t.id === selectedDroneId)) {
+            if (selectedDroneId !== null && !message.tracks.some(t => t.id === selectedDroneId)) {
               setSelectedDroneId(null)
             }
             break
+          case 'detections':
+            // Only sent for clutter scenarios; not displayed yet
+            break
           case 'error':
             setError(message.message)
             setStatus(null)
@@ -251,7 +255,7 @@ function App() {
                     <div className="trajectory-details">
                       <div className="trajectory-item">
                         <span className="trajectory-label">Heading:</span>
-                        <span className="trajectory-value">{analysisResult.trajectory_analysis.heading_deg.toFixed(1)}°</span>
+                        <span className="trajectory-value">{analysisResult.trajectory_analysis.heading_deg === null ? '—' : `${analysisResult.trajectory_analysis.heading_deg.toFixed(1)}°`}</span>
                       </div>
                       <div className="trajectory-item">
                         <span className="trajectory-label">Speed:</span>
@@ -261,6 +265,12 @@ function App() {
                         <span className="trajectory-label">Altitude Estimate:</span>
                         <span className="trajectory-value">{analysisResult.trajectory_analysis.altitude_estimate_m.toFixed(0)} m</span>
                       </div>
+                      {analysisResult.trajectory_analysis.behavior && (
+                        <div className="trajectory-item">
+                          <span className="trajectory-label">Behavior:</span>
+                          <span className="trajectory-value">{analysisResult.trajectory_analysis.behavior}</span>
+                        </div>
+                      )}
                     </div>
                   </div>
 
@@ -1,10 +1,65 @@
 
+export type SwerlingModel = 'swerling_0' | 'swerling_1' | 'swerling_2' | 'swerling_3' | 'swerling_4'
+
+export interface RotorModel {
+  rotor_count: number
+  blade_count: number
+  blade_length_m: number
+  rpm: number
+  blade_rcs?: number
+}
+
+export interface Velocity3 {
+  vx_m_s: number
+  vy_m_s: number
+  vz_m_s: number
+}
+
+export interface GeodeticPosition {
+  latitude_deg: number
+  longitude_deg: number
+  height_m?: number
+}
+
+export interface RadarSite {
+  position: GeodeticPosition
+  heading_deg?: number
+}
+
 export interface TargetPosition {
   id: number
   range_m: number
   azimuth_deg: number
+  elevation_deg?: number
+  altitude_m?: number
   vel_m_s: number
+  velocity?: Velocity3 | null
+  geodetic?: GeodeticPosition | null
   rcs: number
+  swerling?: SwerlingModel
+  rotor?: RotorModel | null
+  mode_probabilities?: ModeProbability[]
+}
+
+export type ImmMode = 'constant_velocity' | 'coordinated_turn' | 'near_stationary'
+
+export interface ModeProbability {
+  mode: ImmMode
+  probability: number
+}
+
+export type FlightBehavior = 'cruising' | 'maneuvering' | 'hovering'
+
+export type TargetClass = 'drone' | 'bird' | 'fixed_wing' | 'clutter' | 'manned_aircraft'
+
+export interface FeatureVector {
+  mean_rcs_dbsm: number
+  rcs_normalized_variance: number
+  mean_speed_m_s: number
+  speed_std_m_s: number
+  micro_doppler_bandwidth_hz: number
+  blade_flash_rate_hz: number
+  mean_abs_acceleration_m_s2: number
 }
 
 export interface DroneAnalysis {
@@ -12,10 +67,13 @@ export interface DroneAnalysis {
   threat_level: 'low' | 'medium' | 'high'
   estimated_type: string
   confidence: number
+  class_probabilities: { class: TargetClass; probability: number }[]
+  features: FeatureVector
   trajectory_analysis: {
-    heading_deg: number
+    heading_deg: number | null
     speed_m_s: number
     altitude_estimate_m: number
+    behavior: FlightBehavior | null
   }
   risk_assessment: {
     proximity_risk: number
@@ -23,11 +81,53 @@ export interface DroneAnalysis {
     overall_risk: number
   }
   recommendations: string[]
+  geodetic: GeodeticPosition | null
 }
 
 
+export interface AngleEstimate {
+  azimuth_deg: number
+  elevation_deg: number | null
+  azimuth_std_deg: number
+  elevation_std_deg: number | null
+  snr_db: number
+}
+
+export interface Detection {
+  range_bin: number
+  doppler_bin: number
+  range_m: number
+  velocity_m_s: number
+  snr_db: number
+  angle: AngleEstimate | null
+}
+
+export type MotionModel = 'constant_velocity' | 'constant_acceleration'
+
+export type TrackStatus = 'tentative' | 'confirmed' | 'coasting' | 'deleted'
+
+export interface TrackEstimate {
+  id: number
+  range_m: number
+  azimuth_deg: number
+  elevation_deg: number
+  altitude_m: number
+  vel_m_s: number
+  velocity: Velocity3
+  geodetic: GeodeticPosition | null
+  rcs: number
+  model: MotionModel | null
+  mode_probabilities: ModeProbability[]
+  state: number[]
+  covariance: number[][]
+  hits: number
+  misses: number
+  status: TrackStatus
+}
+
 export type WebSocketMessage =
-  | { type: 'start_tracking' }
-  | { type: 'targets'; targets: TargetPosition[] }
+  | { type: 'start_tracking'; scenario?: Record<string, unknown>; mht?: Record<string, unknown>; tracker?: Record<string, unknown>; site?: RadarSite; geodetic?: boolean }
+  | { type: 'tracks'; tracks: TrackEstimate[] }
+  | { type: 'detections'; detections: Detection[] }
   | { type: 'error'; message: string }
   | { type: 'status'; message: string }
@@ -1,41 +1,43 @@
+use crate::classifier::{default_classifier, most_likely, Classifier, FeatureVector};
+use crate::imm::FlightBehavior;
 use crate::types::{DroneAnalysis, RiskAssessment, TargetPosition, TrajectoryAnalysis};
 
 pub fn analyze_drone(target: &TargetPosition) -> DroneAnalysis {
+    analyze_drone_with(target, default_classifier())
+}
+
+/// Analyze a target using a specific classifier
+pub fn analyze_drone_with(target: &TargetPosition, classifier: &dyn Classifier) -> DroneAnalysis {
     // Simulate analysis computation (this would be more complex in reality)
     use std::time::Duration;
     std::thread::sleep(Duration::from_millis(500)); // Simulate processing time
 
-    let speed = target.vel_m_s.abs();
+    let speed = target.speed_m_s();
     let range_km = target.range_m / 1000.0;
+    let behavior = FlightBehavior::from_mode_probabilities(&target.mode_probabilities);
+    let hovering = behavior == Some(FlightBehavior::Hovering);
+    let maneuvering = behavior == Some(FlightBehavior::Maneuvering);
 
-    // Determine threat level
-    let threat_level = if range_km < 5.0 && speed > 40.0 {
+    // Determine threat level; a drone hovering close in may be surveilling the site
+    let threat_level = if range_km < 5.0 && (speed > 40.0 || hovering) {
         "high"
-    } else if range_km < 10.0 || speed > 30.0 {
+    } else if range_km < 10.0 || speed > 30.0 || maneuvering {
         "medium"
     } else {
         "low"
     };
 
-    // Estimate drone type based on characteristics
-    let estimated_type = if speed > 50.0 {
-        "Racing/High-Speed"
-    } else if target.rcs > 0.8 {
-        "Commercial/Large"
-    } else {
-        "Consumer/Small"
+    // Classify from RCS, kinematic and micro-Doppler features
+    let features = FeatureVector::from_target(target);
+    let class_probabilities = classifier.classify(&features);
+    let (estimated_type, confidence) = match most_likely(&class_probabilities) {
+        Some(best) => (best.class.label(), best.probability),
+        None => ("Unknown", 0.0),
     };
 
-    // Calculate confidence based on RCS and consistency
-    let confidence = (target.rcs * 0.6 + 0.4).min(1.0);
-
-    // Trajectory analysis
-    let heading_deg = target.azimuth_deg;
-    let altitude_estimate_m = if range_km < 2.0 {
-        50.0 + (range_km * 25.0)
-    } else {
-        100.0 + (range_km * 20.0)
-    };
+    // Trajectory analysis from the target's geometry; a radial velocity alone gives no heading
+    let heading_deg = target.velocity.and_then(|velocity| velocity.heading_deg());
+    let altitude_estimate_m = target.altitude_m();
 
     // Risk assessment
     let proximity_risk = (1.0 - (range_km / 50.0).min(1.0)) * 100.0;
@@ -53,6 +55,14 @@ pub fn analyze_drone(target: &TargetPosition) -> DroneAnalysis {
     if range_km < 3.0 {
         recommendations.push("Drone in close range - alert security personnel".to_string());
     }
+    if hovering {
+        recommendations
+            .push("Target hovering - possible surveillance, check for payload".to_string());
+    }
+    if maneuvering {
+        recommendations
+            .push("Target maneuvering - expect evasive flight, keep track custody".to_string());
+    }
     if recommendations.is_empty() {
         recommendations.push("Continue monitoring - no immediate action required".to_string());
     }
@@ -62,10 +72,13 @@ pub fn analyze_drone(target: &TargetPosition) -> DroneAnalysis {
         threat_level: threat_level.to_string(),
         estimated_type: estimated_type.to_string(),
         confidence,
+        class_probabilities,
+        features,
         trajectory_analysis: TrajectoryAnalysis {
             heading_deg,
             speed_m_s: speed,
             altitude_estimate_m,
+            behavior,
         },
         risk_assessment: RiskAssessment {
             proximity_risk,
@@ -73,13 +86,17 @@ pub fn analyze_drone(target: &TargetPosition) -> DroneAnalysis {
             overall_risk,
         },
         recommendations,
+        geodetic: None,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::TargetPosition;
+    use crate::imm::{ImmMode, ModeProbability};
+    use crate::micro_doppler::RotorModel;
+    use crate::swerling::SwerlingModel;
+    use crate::types::{TargetPosition, Velocity3};
 
     #[test]
     fn test_analyze_drone_high_threat() {
@@ -89,6 +106,7 @@ mod tests {
             azimuth_deg: 45.0,
             vel_m_s: 50.0, // High speed
             rcs: 0.9,
+            ..TargetPosition::default()
         };
 
         let analysis = analyze_drone(&target);
@@ -97,7 +115,8 @@ mod tests {
         assert_eq!(analysis.threat_level, "high");
         assert!(analysis.confidence > 0.0 && analysis.confidence <= 1.0);
         assert_eq!(analysis.trajectory_analysis.speed_m_s, 50.0);
-        assert_eq!(analysis.trajectory_analysis.heading_deg, 45.0);
+        // A radial velocity alone gives no heading
+        assert_eq!(analysis.trajectory_analysis.heading_deg, None);
         assert!(analysis.risk_assessment.overall_risk > 0.0);
         assert!(!analysis.recommendations.is_empty());
     }
@@ -110,6 +129,7 @@ mod tests {
             azimuth_deg: 90.0,
             vel_m_s: 35.0, // Medium-high speed
             rcs: 0.7,
+            ..TargetPosition::default()
         };
 
         let analysis = analyze_drone(&target);
@@ -126,6 +146,7 @@ mod tests {
             azimuth_deg: 180.0,
             vel_m_s: 15.0, // Low speed
             rcs: 0.5,
+            ..TargetPosition::default()
         };
 
         let analysis = analyze_drone(&target);
@@ -135,75 +156,80 @@ mod tests {
     }
 
     #[test]
-    fn test_analyze_drone_racing_type() {
+    fn test_analyze_drone_rotor_classified_as_drone() {
         let target = TargetPosition {
             id: 4,
             range_m: 10_000.0,
-            azimuth_deg: 0.0,
-            vel_m_s: 60.0, // Very high speed
-            rcs: 0.6,
+            vel_m_s: 20.0,
+            rcs: 0.9,
+            swerling: SwerlingModel::One,
+            rotor: Some(RotorModel::quadcopter()),
+            ..TargetPosition::default()
         };
 
         let analysis = analyze_drone(&target);
 
-        assert_eq!(analysis.estimated_type, "Racing/High-Speed");
+        assert_eq!(analysis.estimated_type, "Drone");
+        assert!(analysis.features.blade_flash_rate_hz > 0.0);
     }
 
     #[test]
-    fn test_analyze_drone_commercial_type() {
+    fn test_analyze_drone_bird_type() {
         let target = TargetPosition {
             id: 5,
             range_m: 10_000.0,
-            azimuth_deg: 0.0,
-            vel_m_s: 20.0,
-            rcs: 0.9, // Large RCS
+            vel_m_s: 12.0,
+            rcs: 0.01, // Bird-sized RCS, no rotor
+            swerling: SwerlingModel::Two,
+            ..TargetPosition::default()
         };
 
         let analysis = analyze_drone(&target);
 
-        assert_eq!(analysis.estimated_type, "Commercial/Large");
+        assert_eq!(analysis.estimated_type, "Bird");
     }
 
     #[test]
-    fn test_analyze_drone_consumer_type() {
+    fn test_analyze_drone_manned_aircraft_type() {
         let target = TargetPosition {
             id: 6,
             range_m: 10_000.0,
-            azimuth_deg: 0.0,
-            vel_m_s: 25.0,
-            rcs: 0.5, // Small RCS
+            vel_m_s: 150.0,
+            rcs: 15.0, // Large RCS at airliner speed
+            swerling: SwerlingModel::One,
+            ..TargetPosition::default()
         };
 
         let analysis = analyze_drone(&target);
 
-        assert_eq!(analysis.estimated_type, "Consumer/Small");
+        assert_eq!(analysis.estimated_type, "Manned Aircraft");
     }
 
     #[test]
-    fn test_analyze_drone_confidence_calculation() {
-        let target_high_rcs = TargetPosition {
+    fn test_analyze_drone_confidence_is_top_probability() {
+        let target = TargetPosition {
             id: 7,
             range_m: 10_000.0,
-            azimuth_deg: 0.0,
-            vel_m_s: 20.0,
-            rcs: 1.0,
-        };
-
-        let target_low_rcs = TargetPosition {
-            id: 8,
-            range_m: 10_000.0,
-            azimuth_deg: 0.0,
             vel_m_s: 20.0,
             rcs: 0.1,
+            ..TargetPosition::default()
         };
 
-        let analysis_high = analyze_drone(&target_high_rcs);
-        let analysis_low = analyze_drone(&target_low_rcs);
+        let analysis = analyze_drone(&target);
 
-        // Higher RCS should generally lead to higher confidence
-        assert!(analysis_high.confidence >= analysis_low.confidence);
-        assert!(analysis_high.confidence > 0.0 && analysis_high.confidence <= 1.0);
-        assert!(analysis_low.confidence > 0.0 && analysis_low.confidence <= 1.0);
+        let total: f64 = analysis
+            .class_probabilities
+            .iter()
+            .map(|p| p.probability)
+            .sum();
+        let top = analysis
+            .class_probabilities
+            .iter()
+            .map(|p| p.probability)
+            .fold(0.0, f64::max);
+        assert!((total - 1.0).abs() < 1e-9);
+        assert_eq!(analysis.confidence, top);
+        assert!(analysis.confidence > 0.0 && analysis.confidence <= 1.0);
     }
 
     #[test]
@@ -214,6 +240,7 @@ mod tests {
             azimuth_deg: 0.0,
             vel_m_s: 30.0,
             rcs: 0.8,
+            ..TargetPosition::default()
         };
 
         let target_far = TargetPosition {
@@ -222,6 +249,7 @@ mod tests {
             azimuth_deg: 0.0,
             vel_m_s: 30.0,
             rcs: 0.8,
+            ..TargetPosition::default()
         };
 
         let analysis_close = analyze_drone(&target_close);
@@ -245,6 +273,7 @@ mod tests {
             azimuth_deg: 0.0,
             vel_m_s: 80.0, // Very high speed
             rcs: 0.9,
+            ..TargetPosition::default()
         };
 
         let analysis = analyze_drone(&target_high_risk);
@@ -261,6 +290,7 @@ mod tests {
             azimuth_deg: 0.0,
             vel_m_s: -30.0, // Negative velocity (moving away)
             rcs: 0.7,
+            ..TargetPosition::default()
         };
 
         let analysis = analyze_drone(&target);
@@ -270,33 +300,89 @@ mod tests {
     }
 
     #[test]
-    fn test_analyze_drone_altitude_estimate() {
-        let target_close = TargetPosition {
+    fn test_analyze_drone_geometry() {
+        // Due north at 150 m, crossing eastbound while climbing
+        let velocity = Velocity3 {
+            vx_m_s: 12.0,
+            vy_m_s: 0.0,
+            vz_m_s: 5.0,
+        };
+        let elevation_deg = 150.0f64.atan2(4_000.0).to_degrees();
+        let mut target = TargetPosition {
             id: 13,
-            range_m: 1_000.0, // 1 km
-            azimuth_deg: 0.0,
-            vel_m_s: 20.0,
+            range_m: 4_000.0f64.hypot(150.0),
+            elevation_deg,
+            altitude_m: Some(150.0),
+            velocity: Some(velocity),
             rcs: 0.7,
+            ..TargetPosition::default()
         };
+        target.vel_m_s = velocity.radial_m_s(target.position_m());
+
+        let [x, y, z] = target.position_m();
+        assert!(x.abs() < 1e-9 && (y - 4_000.0).abs() < 1e-6 && (z - 150.0).abs() < 1e-6);
+        // Only the climb is along the line of sight
+        assert!((target.vel_m_s - 5.0 * elevation_deg.to_radians().sin()).abs() < 1e-9);
+
+        let trajectory = analyze_drone(&target).trajectory_analysis;
+        assert_eq!(trajectory.altitude_estimate_m, 150.0);
+        assert!((trajectory.heading_deg.unwrap() - 90.0).abs() < 1e-9);
+        assert!((trajectory.speed_m_s - 13.0).abs() < 1e-9);
+
+        // Without an altitude it follows from the range and elevation
+        let from_elevation = TargetPosition {
+            altitude_m: None,
+            ..target.clone()
+        };
+        let trajectory = analyze_drone(&from_elevation).trajectory_analysis;
+        assert!((trajectory.altitude_estimate_m - 150.0).abs() < 1e-6);
 
-        let target_far = TargetPosition {
-            id: 14,
-            range_m: 10_000.0, // 10 km
-            azimuth_deg: 0.0,
-            vel_m_s: 20.0,
+        // Hovering: no heading
+        target.velocity = Some(Velocity3::default());
+        assert_eq!(analyze_drone(&target).trajectory_analysis.heading_deg, None);
+    }
+
+    #[test]
+    fn test_analyze_drone_uses_track_behavior() {
+        let hovering = TargetPosition {
+            id: 15,
+            range_m: 4_000.0,
+            vel_m_s: 0.2,
             rcs: 0.7,
+            mode_probabilities: vec![
+                ModeProbability {
+                    mode: ImmMode::ConstantVelocity,
+                    probability: 0.1,
+                },
+                ModeProbability {
+                    mode: ImmMode::CoordinatedTurn,
+                    probability: 0.1,
+                },
+                ModeProbability {
+                    mode: ImmMode::NearStationary,
+                    probability: 0.8,
+                },
+            ],
+            ..TargetPosition::default()
+        };
+        let untracked = TargetPosition {
+            mode_probabilities: Vec::new(),
+            ..hovering.clone()
         };
 
-        let analysis_close = analyze_drone(&target_close);
-        let analysis_far = analyze_drone(&target_far);
-
-        // Altitude estimate should be reasonable
-        assert!(analysis_close.trajectory_analysis.altitude_estimate_m > 0.0);
-        assert!(analysis_far.trajectory_analysis.altitude_estimate_m > 0.0);
-        // Far target should have higher altitude estimate
-        assert!(
-            analysis_far.trajectory_analysis.altitude_estimate_m
-                > analysis_close.trajectory_analysis.altitude_estimate_m
+        let analysis = analyze_drone(&hovering);
+        assert_eq!(
+            analysis.trajectory_analysis.behavior,
+            Some(FlightBehavior::Hovering)
         );
+        assert_eq!(analysis.threat_level, "high");
+        assert!(analysis
+            .recommendations
+            .iter()
+            .any(|r| r.contains("hovering")));
+
+        let analysis = analyze_drone(&untracked);
+        assert_eq!(analysis.trajectory_analysis.behavior, None);
+        assert_eq!(analysis.threat_level, "medium");
     }
 }
@@ -0,0 +1,660 @@
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Cost standing in for a forbidden assignment, large enough never to be chosen
+pub const FORBIDDEN: f64 = 1e12;
+
+/// Gating statistics of one track-measurement pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairScore {
+    /// Normalised innovation squared
+    pub d2: f64,
+    /// Log of the Gaussian measurement likelihood `N(nu; 0, S)`
+    pub log_likelihood: f64,
+}
+
+/// Outcome of associating one scan of measurements with the tracks
+#[derive(Debug, Clone, PartialEq)]
+pub struct Association {
+    /// For every track, the measurements it is updated with and their
+    /// weights; a single weight of one is a hard assignment and an empty
+    /// list a miss
+    pub weights: Vec<Vec<(usize, f64)>>,
+    /// Measurements explained by some track, which do not open new tracks
+    pub used: Vec<bool>,
+}
+
+/// Assigns measurements to tracks from their gated pair scores
+pub trait Associator: Send + Sync {
+    /// `scores[t][m]` is `None` when measurement `m` falls outside track `t`'s gate
+    fn associate(&self, scores: &[Vec<Option<PairScore>>], num_measurements: usize) -> Association;
+}
+
+/// Data association method of the tracker
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AssociationMethod {
+    /// Greedy nearest neighbour: closest gated pairs first
+    NearestNeighbor,
+    /// Global nearest neighbour: the one-to-one assignment with the smallest
+    /// total normalised distance, solved with the Hungarian algorithm
+    #[default]
+    GlobalNearestNeighbor,
+    /// Joint probabilistic data association: every track is updated with all
+    /// gated measurements, weighted by their marginal association probabilities
+    Jpda {
+        /// Probability that a target produces a measurement in a scan
+        detection_probability: f64,
+        /// Expected false measurements per unit of measurement space
+        /// (per meter · radian · meter per second)
+        clutter_density: f64,
+    },
+}
+
+impl AssociationMethod {
+    pub fn associator(&self) -> Box<dyn Associator> {
+        match *self {
+            AssociationMethod::NearestNeighbor => Box::new(NearestNeighbor),
+            AssociationMethod::GlobalNearestNeighbor => Box::new(GlobalNearestNeighbor),
+            AssociationMethod::Jpda {
+                detection_probability,
+                clutter_density,
+            } => Box::new(Jpda {
+                detection_probability,
+                clutter_density,
+            }),
+        }
+    }
+}
+
+/// Hard assignments of `pairs` (track, measurement) as an [`Association`]
+fn hard(pairs: &[(usize, usize)], num_tracks: usize, num_measurements: usize) -> Association {
+    let mut weights = vec![Vec::new(); num_tracks];
+    let mut used = vec![false; num_measurements];
+    for &(t, m) in pairs {
+        weights[t] = vec![(m, 1.0)];
+        used[m] = true;
+    }
+    Association { weights, used }
+}
+
+/// Greedy nearest-neighbour association
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NearestNeighbor;
+
+impl Associator for NearestNeighbor {
+    fn associate(&self, scores: &[Vec<Option<PairScore>>], num_measurements: usize) -> Association {
+        let mut pairs: Vec<(f64, usize, usize)> = scores
+            .iter()
+            .enumerate()
+            .flat_map(|(t, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(m, score)| score.map(|s| (s.d2, t, m)))
+            })
+            .collect();
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut track_used = vec![false; scores.len()];
+        let mut measurement_used = vec![false; num_measurements];
+        let mut assigned = Vec::new();
+        for (_, t, m) in pairs {
+            if track_used[t] || measurement_used[m] {
+                continue;
+            }
+            track_used[t] = true;
+            measurement_used[m] = true;
+            assigned.push((t, m));
+        }
+        hard(&assigned, scores.len(), num_measurements)
+    }
+}
+
+/// Global nearest-neighbour association.
+///
+/// Leaving a track or a measurement unassigned costs the largest gated
+/// distance seen, so any gated pair is preferred over leaving both
+/// unassigned.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GlobalNearestNeighbor;
+
+impl Associator for GlobalNearestNeighbor {
+    fn associate(&self, scores: &[Vec<Option<PairScore>>], num_measurements: usize) -> Association {
+        let (num_tracks, n) = (scores.len(), scores.len() + num_measurements);
+        let unassigned = scores
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|s| s.d2)
+            .fold(1.0, f64::max);
+
+        // Rows: tracks, then one dummy per measurement. Columns: measurements,
+        // then one dummy per track.
+        let mut cost = Array2::from_elem((n, n), FORBIDDEN);
+        for (t, row) in scores.iter().enumerate() {
+            for (m, score) in row.iter().enumerate() {
+                if let Some(score) = score {
+                    cost[[t, m]] = score.d2;
+                }
+            }
+            cost[[t, num_measurements + t]] = unassigned;
+        }
+        for m in 0..num_measurements {
+            cost[[num_tracks + m, m]] = unassigned;
+            for t in 0..num_tracks {
+                cost[[num_tracks + m, num_measurements + t]] = 0.0;
+            }
+        }
+
+        let assigned: Vec<(usize, usize)> = hungarian(&cost)
+            .into_iter()
+            .enumerate()
+            .take(num_tracks)
+            .filter(|&(t, m)| m < num_measurements && scores[t][m].is_some())
+            .collect();
+        hard(&assigned, num_tracks, num_measurements)
+    }
+}
+
+/// Joint probabilistic data association by enumerating the feasible joint
+/// events (each track takes at most one gated measurement and each
+/// measurement at most one track)
+#[derive(Debug, Clone, Copy)]
+pub struct Jpda {
+    pub detection_probability: f64,
+    pub clutter_density: f64,
+}
+
+impl Default for Jpda {
+    fn default() -> Self {
+        Self {
+            detection_probability: 0.9,
+            clutter_density: 1e-4,
+        }
+    }
+}
+
+impl Jpda {
+    /// Add the weight of every joint event extending `assignment` (tracks
+    /// before `track` already decided) to `marginals[t][m]`, with the miss in
+    /// the last column; returns the total weight of those events
+    fn enumerate(
+        track: usize,
+        pair_weights: &[Vec<Option<f64>>],
+        miss_weight: f64,
+        taken: &mut Vec<bool>,
+        assignment: &mut Vec<Option<usize>>,
+        marginals: &mut [Vec<f64>],
+    ) -> f64 {
+        if track == pair_weights.len() {
+            let weight: f64 = assignment
+                .iter()
+                .zip(pair_weights)
+                .map(|(m, row)| m.and_then(|m| row[m]).unwrap_or(miss_weight))
+                .product();
+            for (t, m) in assignment.iter().enumerate() {
+                let column = m.unwrap_or(taken.len());
+                marginals[t][column] += weight;
+            }
+            return weight;
+        }
+
+        assignment.push(None);
+        let mut total = Self::enumerate(
+            track + 1,
+            pair_weights,
+            miss_weight,
+            taken,
+            assignment,
+            marginals,
+        );
+        for m in 0..taken.len() {
+            if taken[m] || pair_weights[track][m].is_none() {
+                continue;
+            }
+            taken[m] = true;
+            assignment[track] = Some(m);
+            total += Self::enumerate(
+                track + 1,
+                pair_weights,
+                miss_weight,
+                taken,
+                assignment,
+                marginals,
+            );
+            taken[m] = false;
+        }
+        assignment.pop();
+        total
+    }
+}
+
+impl Associator for Jpda {
+    fn associate(&self, scores: &[Vec<Option<PairScore>>], num_measurements: usize) -> Association {
+        let pd = self.detection_probability.clamp(1e-6, 1.0);
+        let pair_weights: Vec<Vec<Option<f64>>> = scores
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|score| {
+                        score
+                            .map(|s| pd * s.log_likelihood.exp() / self.clutter_density.max(1e-300))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut marginals = vec![vec![0.0; num_measurements + 1]; scores.len()];
+        let total = Self::enumerate(
+            0,
+            &pair_weights,
+            1.0 - pd,
+            &mut vec![false; num_measurements],
+            &mut Vec::with_capacity(scores.len()),
+            &mut marginals,
+        );
+
+        let mut used = vec![false; num_measurements];
+        let weights = marginals
+            .iter()
+            .map(|row| {
+                row[..num_measurements]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, w)| **w > 0.0)
+                    .map(|(m, w)| {
+                        used[m] = true;
+                        (m, w / total)
+                    })
+                    .collect()
+            })
+            .collect();
+        Association { weights, used }
+    }
+}
+
+/// Minimum-cost assignment of every row to a distinct column by the
+/// Hungarian algorithm with row and column potentials; the matrix must have
+/// no more rows than columns. Returns the column assigned to each row.
+pub fn hungarian(cost: &Array2<f64>) -> Vec<usize> {
+    let (n, m) = cost.dim();
+    assert!(n <= m, "hungarian needs at least as many columns as rows");
+    // 1-based: column 0 is a virtual start, row_of[j] is the row matched to column j
+    let mut u = vec![0.0; n + 1];
+    let mut v = vec![0.0; m + 1];
+    let mut row_of = vec![0usize; m + 1];
+    let mut way = vec![0usize; m + 1];
+
+    for i in 1..=n {
+        row_of[0] = i;
+        let mut j0 = 0;
+        let mut min_slack = vec![f64::INFINITY; m + 1];
+        let mut visited = vec![false; m + 1];
+        loop {
+            visited[j0] = true;
+            let i0 = row_of[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if visited[j] {
+                    continue;
+                }
+                let slack = cost[[i0 - 1, j - 1]] - u[i0] - v[j];
+                if slack < min_slack[j] {
+                    min_slack[j] = slack;
+                    way[j] = j0;
+                }
+                if min_slack[j] < delta {
+                    delta = min_slack[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=m {
+                if visited[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_slack[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if row_of[j0] == 0 {
+                break;
+            }
+        }
+        // Augment along the alternating path back to the virtual column
+        while j0 != 0 {
+            let j1 = way[j0];
+            row_of[j0] = row_of[j1];
+            j0 = j1;
+        }
+    }
+
+    let mut assignment = vec![0; n];
+    for (j, &i) in row_of.iter().enumerate().skip(1) {
+        if i > 0 {
+            assignment[i - 1] = j - 1;
+        }
+    }
+    assignment
+}
+
+/// Cheapest assignment with some (row, column) pairs forced and others
+/// forbidden, or `None` if every assignment uses a forbidden pair
+fn constrained_assignment(
+    cost: &Array2<f64>,
+    forced: &[(usize, usize)],
+    forbidden: &[(usize, usize)],
+) -> Option<(Vec<usize>, f64)> {
+    let mut cost = cost.clone();
+    for &(r, c) in forbidden {
+        cost[[r, c]] = FORBIDDEN;
+    }
+    for &(r, c) in forced {
+        let keep = cost[[r, c]];
+        cost.row_mut(r).fill(FORBIDDEN);
+        cost.column_mut(c).fill(FORBIDDEN);
+        cost[[r, c]] = keep;
+    }
+    let assignment = hungarian(&cost);
+    let pair_costs: Vec<f64> = assignment
+        .iter()
+        .enumerate()
+        .map(|(r, &c)| cost[[r, c]])
+        .collect();
+    if pair_costs.iter().any(|&c| c >= FORBIDDEN) {
+        return None;
+    }
+    Some((assignment, pair_costs.iter().sum()))
+}
+
+/// The `k` cheapest assignments of every row to a distinct column, cheapest
+/// first, by Murty's partitioning of the solution space. Costs at or above
+/// [`FORBIDDEN`] mark pairs that may not be assigned.
+pub fn k_best_assignments(cost: &Array2<f64>, k: usize) -> Vec<(Vec<usize>, f64)> {
+    struct Node {
+        assignment: Vec<usize>,
+        cost: f64,
+        forced: Vec<(usize, usize)>,
+        forbidden: Vec<(usize, usize)>,
+    }
+
+    let mut best = Vec::with_capacity(k);
+    let mut open: Vec<Node> = constrained_assignment(cost, &[], &[])
+        .map(|(assignment, cost)| Node {
+            assignment,
+            cost,
+            forced: Vec::new(),
+            forbidden: Vec::new(),
+        })
+        .into_iter()
+        .collect();
+    while best.len() < k && !open.is_empty() {
+        let cheapest = (0..open.len())
+            .min_by(|&a, &b| open[a].cost.total_cmp(&open[b].cost))
+            .unwrap_or(0);
+        let node = open.swap_remove(cheapest);
+
+        // Split the remaining solutions of this node: the t-th part keeps the
+        // first t free rows as they are and moves row t elsewhere
+        let mut forced = node.forced.clone();
+        for (row, &column) in node.assignment.iter().enumerate() {
+            if node.forced.iter().any(|&(r, _)| r == row) {
+                continue;
+            }
+            let mut forbidden = node.forbidden.clone();
+            forbidden.push((row, column));
+            if let Some((assignment, cost)) = constrained_assignment(cost, &forced, &forbidden) {
+                open.push(Node {
+                    assignment,
+                    cost,
+                    forced: forced.clone(),
+                    forbidden,
+                });
+            }
+            forced.push((row, column));
+        }
+        best.push((node.assignment, node.cost));
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracking::{measure, TrackEstimate, Tracker, TrackerConfig};
+    use crate::types::TargetPosition;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn score(d2: f64) -> Option<PairScore> {
+        Some(PairScore {
+            d2,
+            log_likelihood: -0.5 * d2,
+        })
+    }
+
+    /// Smallest total over all permutations
+    fn brute_force(cost: &Array2<f64>) -> f64 {
+        fn search(cost: &Array2<f64>, row: usize, taken: &mut Vec<bool>) -> f64 {
+            if row == cost.nrows() {
+                return 0.0;
+            }
+            let mut best = f64::INFINITY;
+            for col in 0..cost.ncols() {
+                if !taken[col] {
+                    taken[col] = true;
+                    best = best.min(cost[[row, col]] + search(cost, row + 1, taken));
+                    taken[col] = false;
+                }
+            }
+            best
+        }
+        search(cost, 0, &mut vec![false; cost.ncols()])
+    }
+
+    #[test]
+    fn test_hungarian_is_optimal() {
+        let mut rng = StdRng::seed_from_u64(16);
+        for n in 1..=6 {
+            let cost = Array2::from_shape_fn((n, n), |_| rng.gen_range(0.0..10.0));
+            let assignment = hungarian(&cost);
+            let mut columns = assignment.clone();
+            columns.sort_unstable();
+            assert_eq!(columns, (0..n).collect::<Vec<_>>());
+            let total: f64 = assignment
+                .iter()
+                .enumerate()
+                .map(|(i, &j)| cost[[i, j]])
+                .sum();
+            assert!((total - brute_force(&cost)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_k_best_assignments_match_enumeration() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let cost = Array2::from_shape_fn((3, 5), |_| {
+            if rng.gen_bool(0.2) {
+                FORBIDDEN
+            } else {
+                rng.gen_range(0.0..10.0)
+            }
+        });
+        // Every injective row-to-column map without forbidden pairs
+        let mut totals = Vec::new();
+        for a in 0..5 {
+            for b in 0..5 {
+                for c in 0..5 {
+                    let columns = [a, b, c];
+                    if a == b || b == c || a == c {
+                        continue;
+                    }
+                    let pairs = columns.iter().enumerate().map(|(r, &c)| cost[[r, c]]);
+                    if pairs.clone().all(|p| p < FORBIDDEN) {
+                        totals.push(pairs.sum::<f64>());
+                    }
+                }
+            }
+        }
+        totals.sort_by(f64::total_cmp);
+
+        let best = k_best_assignments(&cost, 8);
+        assert_eq!(best.len(), 8.min(totals.len()));
+        for ((assignment, total), expected) in best.iter().zip(&totals) {
+            assert!((total - expected).abs() < 1e-9);
+            let sum: f64 = assignment
+                .iter()
+                .enumerate()
+                .map(|(r, &c)| cost[[r, c]])
+                .sum();
+            assert!((sum - total).abs() < 1e-9);
+        }
+        // All distinct
+        for i in 0..best.len() {
+            for j in 0..i {
+                assert_ne!(best[i].0, best[j].0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_gnn_beats_greedy_on_shared_measurement() {
+        // Greedy takes the closest pair (0, 0) and leaves track 1 without a
+        // measurement; the global assignment serves both tracks
+        let scores = vec![vec![score(1.0), score(2.0)], vec![score(1.5), None]];
+        let greedy = NearestNeighbor.associate(&scores, 2);
+        assert_eq!(greedy.weights, vec![vec![(0, 1.0)], vec![]]);
+        let global = GlobalNearestNeighbor.associate(&scores, 2);
+        assert_eq!(global.weights, vec![vec![(1, 1.0)], vec![(0, 1.0)]]);
+        assert_eq!(global.used, vec![true, true]);
+
+        // Measurements outside every gate stay free to open tracks
+        let lonely = GlobalNearestNeighbor.associate(&[vec![None, score(3.0)]], 2);
+        assert_eq!(lonely.weights, vec![vec![(1, 1.0)]]);
+        assert_eq!(lonely.used, vec![false, true]);
+    }
+
+    #[test]
+    fn test_jpda_marginals() {
+        let jpda = Jpda::default();
+        let scores = vec![vec![score(1.0), score(4.0)], vec![score(1.0), None]];
+        let association = jpda.associate(&scores, 2);
+        for weights in &association.weights {
+            let total: f64 = weights.iter().map(|(_, w)| w).sum();
+            assert!(total > 0.0 && total <= 1.0 + 1e-12);
+        }
+        // Track 1 competes for measurement 0, so track 0 leans on measurement 1
+        // more than it would alone
+        let alone = jpda.associate(&scores[..1], 2);
+        let weight = |a: &Association, m: usize| {
+            a.weights[0]
+                .iter()
+                .find(|(j, _)| *j == m)
+                .map_or(0.0, |(_, w)| *w)
+        };
+        assert!(weight(&association, 1) > weight(&alone, 1));
+        assert!(weight(&association, 0) < weight(&alone, 0));
+        assert_eq!(association.used, vec![true, true]);
+    }
+
+    /// Targets on straight lines through a common point near the middle of the run
+    fn crossing_targets(t: f64) -> Vec<TargetPosition> {
+        let center = (1_500.0, 4_000.0);
+        // (heading deg clockwise from north, speed m/s, seconds until the crossing)
+        let legs: [(f64, f64, f64); 3] =
+            [(90.0, 20.0, 30.0), (0.0, 15.0, 31.0), (225.0, 18.0, 32.0)];
+        legs.iter()
+            .enumerate()
+            .map(|(id, &(heading, speed, crossing))| {
+                let heading = heading.to_radians();
+                let (vx, vy) = (speed * heading.sin(), speed * heading.cos());
+                let (x, y) = (
+                    center.0 + vx * (t - crossing),
+                    center.1 + vy * (t - crossing),
+                );
+                let range_m = x.hypot(y);
+                TargetPosition {
+                    id,
+                    range_m,
+                    azimuth_deg: x.atan2(y).to_degrees().rem_euclid(360.0),
+                    vel_m_s: (x * vx + y * vy) / range_m,
+                    rcs: 0.1,
+                    ..TargetPosition::default()
+                }
+            })
+            .collect()
+    }
+
+    /// Id of the established track nearest to each target at time `t`
+    fn nearest_tracks(tracker: &Tracker, t: f64) -> Vec<u32> {
+        let estimates = tracker.estimates();
+        crossing_targets(t)
+            .iter()
+            .map(|target| {
+                let distance = |e: &TrackEstimate| {
+                    let azimuth = (e.azimuth_deg - target.azimuth_deg).to_radians();
+                    (e.range_m - target.range_m).abs() + target.range_m * azimuth.abs()
+                };
+                estimates
+                    .iter()
+                    .filter(|e| e.hits > 10)
+                    .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+                    .map(|e| e.id)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    /// Run a tracker over the crossing and return the tracks nearest to the
+    /// targets well before and well after it
+    fn track_crossing(association: AssociationMethod, seed: u64) -> (Vec<u32>, Vec<u32>) {
+        let config = TrackerConfig {
+            association,
+            ..TrackerConfig::default()
+        };
+        let noise = config.measurement_noise;
+        let mut tracker = Tracker::new(config);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let dt = 0.5;
+        let mut before = Vec::new();
+        for step in 0..=120 {
+            let t = step as f64 * dt;
+            let scan: Vec<_> = crossing_targets(t)
+                .iter()
+                .map(|target| measure(target, &noise, &mut rng))
+                .collect();
+            tracker.step(&scan, dt);
+            if step == 40 {
+                before = nearest_tracks(&tracker, t);
+            }
+        }
+        (before, nearest_tracks(&tracker, 60.0))
+    }
+
+    /// Every target is still held by the track that followed it before the
+    /// crossing: no swaps, and no track dropped and restarted under a new id
+    fn assert_identities_kept(association: AssociationMethod, seed: u64) {
+        let (before, after) = track_crossing(association, seed);
+        assert_eq!(after, before, "seed {seed}");
+    }
+
+    #[test]
+    fn test_gnn_keeps_identities_through_crossing() {
+        for seed in 0..5 {
+            assert_identities_kept(AssociationMethod::GlobalNearestNeighbor, seed);
+        }
+    }
+
+    #[test]
+    fn test_jpda_keeps_tracks_through_crossing() {
+        let jpda = AssociationMethod::Jpda {
+            detection_probability: 0.9,
+            clutter_density: 1e-4,
+        };
+        for seed in 0..3 {
+            assert_identities_kept(jpda, seed);
+        }
+    }
+}
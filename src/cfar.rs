@@ -0,0 +1,485 @@
+use crate::aoa::AngleEstimate;
+use crate::error::{AppError, AppResult};
+use crate::processing::RangeDopplerMap;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Noise-level estimator used by the CFAR detector
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CfarKind {
+    /// Mean of all training cells
+    CellAveraging,
+    /// Larger of the leading and lagging training means (robust at clutter edges)
+    GreatestOf,
+    /// Smaller of the leading and lagging training means (resolves closely spaced targets)
+    SmallestOf,
+    /// `rank`-th smallest training cell, 1-based (robust to interfering targets)
+    OrderedStatistic { rank: usize },
+}
+
+/// CFAR window geometry and false-alarm target.
+///
+/// 1D detectors use the range fields only; 2D detectors use a rectangular
+/// training annulus extending along both range and Doppler.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CfarConfig {
+    pub kind: CfarKind,
+    /// Guard cells on each side of the cell under test along range
+    pub guard_range: usize,
+    /// Training cells on each side beyond the guard cells along range
+    pub training_range: usize,
+    /// Guard cells on each side of the cell under test along Doppler
+    pub guard_doppler: usize,
+    /// Training cells on each side beyond the guard cells along Doppler
+    pub training_doppler: usize,
+    /// Design probability of false alarm per cell
+    pub pfa: f64,
+}
+
+impl Default for CfarConfig {
+    fn default() -> Self {
+        Self {
+            kind: CfarKind::CellAveraging,
+            guard_range: 2,
+            training_range: 8,
+            guard_doppler: 2,
+            training_doppler: 4,
+            pfa: 1e-6,
+        }
+    }
+}
+
+/// Largest 2D CFAR window, in cells, a scenario may ask for: every cell of a
+/// map is compared with a window this size on every dwell
+pub const MAX_CFAR_WINDOW_CELLS: usize = 1_024;
+
+impl CfarConfig {
+    /// Cells spanned by the 2D window along range and Doppler; `None` on overflow
+    pub fn window_span(&self) -> Option<(usize, usize)> {
+        let span = |guard: usize, training: usize| {
+            guard.checked_add(training)?.checked_mul(2)?.checked_add(1)
+        };
+        Some((
+            span(self.guard_range, self.training_range)?,
+            span(self.guard_doppler, self.training_doppler)?,
+        ))
+    }
+
+    /// Reject a 2D detector that cannot reach its Pfa or whose window is too
+    /// large to slide over every dwell
+    pub fn validate(&self) -> AppResult<()> {
+        if !(self.pfa > 0.0 && self.pfa < 1.0) {
+            return Err(AppError::InvalidInput(format!(
+                "CFAR pfa must lie between 0 and 1: {}",
+                self.pfa
+            )));
+        }
+        let window_cells = self
+            .window_span()
+            .and_then(|(range, doppler)| range.checked_mul(doppler))
+            .filter(|&cells| cells <= MAX_CFAR_WINDOW_CELLS)
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!(
+                    "CFAR window larger than {} cells",
+                    MAX_CFAR_WINDOW_CELLS
+                ))
+            })?;
+        let guard_cells = (2 * self.guard_range + 1) * (2 * self.guard_doppler + 1);
+        let num_training = window_cells - guard_cells;
+        if num_training == 0 {
+            return Err(AppError::InvalidInput(
+                "CFAR window has no training cells".to_string(),
+            ));
+        }
+        if let CfarKind::OrderedStatistic { rank } = self.kind {
+            if rank == 0 || rank > num_training {
+                return Err(AppError::InvalidInput(format!(
+                    "CFAR rank {} outside 1..={}",
+                    rank, num_training
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A cell of a 1D power profile that exceeded its adaptive threshold
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CfarCrossing {
+    /// Index of the cell under test
+    pub index: usize,
+    /// Power of the cell under test
+    pub power: f64,
+    /// Noise power estimated from the training cells
+    pub noise_power: f64,
+}
+
+/// A target detection from a range-Doppler map
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Detection {
+    /// Range bin index
+    pub range_bin: usize,
+    /// Doppler bin index (zero velocity at `num_doppler_bins / 2`)
+    pub doppler_bin: usize,
+    /// Range in meters
+    pub range_m: f64,
+    /// Radial velocity in meters per second (positive = moving away)
+    pub velocity_m_s: f64,
+    /// Cell power relative to the CFAR noise estimate, in dB
+    pub snr_db: f64,
+    /// Direction of arrival, when the scene has a receive array
+    #[serde(default)]
+    pub angle: Option<AngleEstimate>,
+}
+
+/// Threshold multiplier (applied to the noise estimate) that yields `pfa` for
+/// `num_training` exponentially distributed training cells, as produced by a
+/// square-law detector on complex Gaussian noise.
+pub fn threshold_factor(kind: CfarKind, num_training: usize, pfa: f64) -> f64 {
+    let n = num_training as f64;
+    match kind {
+        CfarKind::CellAveraging => n * (pfa.powf(-1.0 / n) - 1.0),
+        _ => solve_decreasing(
+            |alpha| false_alarm_probability(kind, num_training, alpha),
+            pfa,
+        ),
+    }
+}
+
+/// Analytic false-alarm probability of a CFAR detector for threshold multiplier `alpha`
+pub fn false_alarm_probability(kind: CfarKind, num_training: usize, alpha: f64) -> f64 {
+    let n = num_training as f64;
+    match kind {
+        CfarKind::CellAveraging => (1.0 + alpha / n).powf(-n),
+        CfarKind::GreatestOf | CfarKind::SmallestOf => {
+            let half = num_training / 2;
+            let h = half as f64;
+            let x = 2.0 + alpha / h;
+            // sum_{k<half} C(half-1+k, k) x^-(half+k)
+            let mut term = x.powf(-h);
+            let mut sum = term;
+            for k in 1..half {
+                term *= (h - 1.0 + k as f64) / k as f64 / x;
+                sum += term;
+            }
+            let smallest_of = 2.0 * sum;
+            if kind == CfarKind::SmallestOf {
+                smallest_of
+            } else {
+                2.0 * (1.0 + alpha / h).powf(-h) - smallest_of
+            }
+        }
+        CfarKind::OrderedStatistic { rank } => (0..rank.min(num_training))
+            .map(|i| {
+                let remaining = n - i as f64;
+                remaining / (remaining + alpha)
+            })
+            .product(),
+    }
+}
+
+/// Find `alpha` with `f(alpha) = target` for a function decreasing from 1 at zero
+fn solve_decreasing(f: impl Fn(f64) -> f64, target: f64) -> f64 {
+    let mut lo = 0.0;
+    let mut hi = 1.0;
+    while f(hi) > target && hi < 1e12 {
+        hi *= 2.0;
+    }
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if f(mid) > target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+/// Combine leading/lagging training cells into a noise estimate
+fn noise_estimate(kind: CfarKind, leading: &[f64], lagging: &[f64], scratch: &mut Vec<f64>) -> f64 {
+    let mean = |cells: &[f64]| cells.iter().sum::<f64>() / cells.len() as f64;
+    match kind {
+        CfarKind::CellAveraging => {
+            (leading.iter().sum::<f64>() + lagging.iter().sum::<f64>())
+                / (leading.len() + lagging.len()) as f64
+        }
+        CfarKind::GreatestOf => mean(leading).max(mean(lagging)),
+        CfarKind::SmallestOf => mean(leading).min(mean(lagging)),
+        CfarKind::OrderedStatistic { rank } => {
+            scratch.clear();
+            scratch.extend_from_slice(leading);
+            scratch.extend_from_slice(lagging);
+            let k = rank.clamp(1, scratch.len()) - 1;
+            *scratch.select_nth_unstable_by(k, |a, b| a.total_cmp(b)).1
+        }
+    }
+}
+
+/// Run a CFAR detector over a 1D power profile.
+///
+/// Cells too close to either end to have a full training window are not tested.
+pub fn cfar_1d(power: &[f64], config: &CfarConfig) -> Vec<CfarCrossing> {
+    let guard = config.guard_range;
+    let train = config.training_range;
+    let reach = guard + train;
+    if train == 0 || power.len() <= 2 * reach {
+        return Vec::new();
+    }
+
+    let alpha = threshold_factor(config.kind, 2 * train, config.pfa);
+    let mut scratch = Vec::with_capacity(2 * train);
+    let mut crossings = Vec::new();
+
+    for cut in reach..power.len() - reach {
+        let leading = &power[cut - reach..cut - guard];
+        let lagging = &power[cut + guard + 1..=cut + reach];
+        let noise_power = noise_estimate(config.kind, leading, lagging, &mut scratch);
+        if power[cut] > alpha * noise_power {
+            crossings.push(CfarCrossing {
+                index: cut,
+                power: power[cut],
+                noise_power,
+            });
+        }
+    }
+
+    crossings
+}
+
+/// Run a 2D CFAR detector over a range-Doppler map.
+///
+/// The training region is the rectangular annulus around the guard window.
+/// Leading/lagging halves (for GO/SO) are split along range. The Doppler axis
+/// wraps around, matching the periodicity of the Doppler FFT; range bins
+/// without a full training window are not tested.
+pub fn cfar_2d(map: &RangeDopplerMap, config: &CfarConfig) -> Vec<Detection> {
+    let power = map.power();
+    let (num_range, num_doppler) = power.dim();
+    let (gr, tr) = (config.guard_range, config.training_range);
+    let (gd, td) = (config.guard_doppler, config.training_doppler);
+    let reach_r = gr + tr;
+    let reach_d = (gd + td) as isize;
+    if num_range <= 2 * reach_r || num_doppler == 0 {
+        return Vec::new();
+    }
+
+    let window_cells = (2 * reach_r + 1) * (2 * (gd + td) + 1);
+    let guard_cells = (2 * gr + 1) * (2 * gd + 1);
+    let num_training = window_cells - guard_cells;
+    if num_training == 0 {
+        return Vec::new();
+    }
+    let alpha = threshold_factor(config.kind, num_training, config.pfa);
+
+    let mut leading = Vec::with_capacity(num_training / 2 + 1);
+    let mut lagging = Vec::with_capacity(num_training / 2 + 1);
+    let mut scratch = Vec::with_capacity(num_training);
+    let mut detections = Vec::new();
+
+    for r in reach_r..num_range - reach_r {
+        for d in 0..num_doppler {
+            leading.clear();
+            lagging.clear();
+            for dr in -(reach_r as isize)..=reach_r as isize {
+                for dd in -reach_d..=reach_d {
+                    if dr.unsigned_abs() <= gr && dd.unsigned_abs() <= gd {
+                        continue;
+                    }
+                    let rr = (r as isize + dr) as usize;
+                    let dc = (d as isize + dd).rem_euclid(num_doppler as isize) as usize;
+                    let value = power[[rr, dc]];
+                    if dr < 0 || (dr == 0 && dd < 0) {
+                        leading.push(value);
+                    } else {
+                        lagging.push(value);
+                    }
+                }
+            }
+
+            let noise_power = noise_estimate(config.kind, &leading, &lagging, &mut scratch);
+            let cut = power[[r, d]];
+            if cut > alpha * noise_power {
+                let cell = map.cell(r, d);
+                detections.push(Detection {
+                    range_bin: r,
+                    doppler_bin: d,
+                    range_m: cell.range_m,
+                    velocity_m_s: cell.velocity_m_s,
+                    snr_db: 10.0 * (cut / noise_power).log10(),
+                    angle: None,
+                });
+            }
+        }
+    }
+
+    detections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::{range_doppler_map, ProcessingConfig};
+    use crate::signal::{simulate_iq_cube, FmcwParams};
+    use crate::types::TargetPosition;
+    use ndarray::Array2;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, Exp1};
+
+    const KINDS: [CfarKind; 4] = [
+        CfarKind::CellAveraging,
+        CfarKind::GreatestOf,
+        CfarKind::SmallestOf,
+        CfarKind::OrderedStatistic { rank: 24 },
+    ];
+
+    fn exponential_noise(len: usize, seed: u64) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..len).map(|_| Exp1.sample(&mut rng)).collect()
+    }
+
+    fn config(kind: CfarKind, pfa: f64) -> CfarConfig {
+        CfarConfig {
+            kind,
+            guard_range: 2,
+            training_range: 16,
+            guard_doppler: 1,
+            training_doppler: 2,
+            pfa,
+        }
+    }
+
+    fn assert_rate_close(kind: CfarKind, alarms: usize, trials: usize, pfa: f64) {
+        let expected = trials as f64 * pfa;
+        // Allow four standard deviations of the binomial count
+        let tolerance = 4.0 * expected.sqrt();
+        assert!(
+            (alarms as f64 - expected).abs() < tolerance,
+            "{kind:?}: {alarms} false alarms, expected {expected:.0} +/- {tolerance:.0}"
+        );
+    }
+
+    #[test]
+    fn test_threshold_factor_inverts_pfa() {
+        for kind in KINDS {
+            for pfa in [1e-3, 1e-6] {
+                let alpha = threshold_factor(kind, 32, pfa);
+                let achieved = false_alarm_probability(kind, 32, alpha);
+                assert!(((achieved - pfa) / pfa).abs() < 1e-6, "{kind:?} at {pfa}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_threshold_ordering() {
+        // For the same Pfa, GO needs a lower multiplier than CA on the larger half-mean,
+        // while SO needs a higher one on the smaller half-mean.
+        let ca = threshold_factor(CfarKind::CellAveraging, 32, 1e-6);
+        let go = threshold_factor(CfarKind::GreatestOf, 32, 1e-6);
+        let so = threshold_factor(CfarKind::SmallestOf, 32, 1e-6);
+        assert!(go < ca && ca < so);
+        // Known CA value: N=32, Pfa=1e-6 -> alpha ~ 17.28 (12.4 dB)
+        assert!((ca - 17.28).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_empirical_pfa_1d_matches_design() {
+        let pfa = 1e-3;
+        let noise = exponential_noise(400_000, 7);
+        for kind in KINDS {
+            let cfg = config(kind, pfa);
+            let alarms = cfar_1d(&noise, &cfg).len();
+            let trials = noise.len() - 2 * (cfg.guard_range + cfg.training_range);
+            assert_rate_close(kind, alarms, trials, pfa);
+        }
+    }
+
+    #[test]
+    fn test_empirical_pfa_2d_matches_design() {
+        let pfa = 1e-3;
+        let (rows, cols) = (512, 128);
+        let noise = exponential_noise(rows * cols, 11);
+        let map = RangeDopplerMap {
+            magnitude: Array2::from_shape_vec((rows, cols), noise)
+                .unwrap()
+                .mapv(f64::sqrt),
+            range_bin_m: 30.0,
+            velocity_bin_m_s: 1.0,
+        };
+        for kind in [
+            CfarKind::CellAveraging,
+            CfarKind::GreatestOf,
+            CfarKind::SmallestOf,
+            CfarKind::OrderedStatistic { rank: 100 },
+        ] {
+            let cfg = config(kind, pfa);
+            let alarms = cfar_2d(&map, &cfg).len();
+            let trials = (rows - 2 * (cfg.guard_range + cfg.training_range)) * cols;
+            assert_rate_close(kind, alarms, trials, pfa);
+        }
+    }
+
+    #[test]
+    fn test_detects_strong_target_in_noise() {
+        let mut profile = exponential_noise(256, 3);
+        profile[128] = 1_000.0;
+        for kind in KINDS {
+            let crossings = cfar_1d(&profile, &config(kind, 1e-6));
+            assert!(
+                crossings.iter().any(|c| c.index == 128),
+                "{kind:?} missed target"
+            );
+            assert!(crossings.len() <= 2);
+        }
+    }
+
+    #[test]
+    fn test_smallest_of_resolves_adjacent_target() {
+        // A second strong target inside the training window masks CA/GO but not SO/OS
+        let mut profile = vec![1.0; 128];
+        profile[60] = 200.0;
+        profile[68] = 400.0;
+        let cfg = |kind| CfarConfig {
+            training_range: 8,
+            ..config(kind, 1e-4)
+        };
+        let hit = |kind| cfar_1d(&profile, &cfg(kind)).iter().any(|c| c.index == 60);
+        assert!(hit(CfarKind::SmallestOf));
+        assert!(hit(CfarKind::OrderedStatistic { rank: 12 }));
+        assert!(!hit(CfarKind::GreatestOf));
+    }
+
+    #[test]
+    fn test_detections_from_simulated_scene() {
+        let params = FmcwParams {
+            chirps_per_frame: 32,
+            sample_rate_hz: 2.56e6,
+            num_rx: 2,
+            ..FmcwParams::default()
+        };
+        let target = TargetPosition {
+            range_m: 3_000.0,
+            vel_m_s: 14.79,
+            rcs: 1.0,
+            ..TargetPosition::default()
+        };
+        let mut cube = simulate_iq_cube(&params, &[target]);
+        let mut rng = StdRng::seed_from_u64(5);
+        let normal = rand_distr::Normal::new(0.0, 0.5).unwrap();
+        for sample in cube.iter_mut() {
+            *sample += num_complex::Complex::new(normal.sample(&mut rng), normal.sample(&mut rng));
+        }
+        let map = range_doppler_map(&cube, &params, &ProcessingConfig::default());
+        let detections = cfar_2d(&map, &CfarConfig::default());
+
+        let best = detections
+            .iter()
+            .max_by(|a, b| a.snr_db.total_cmp(&b.snr_db))
+            .expect("target should be detected");
+        assert!((best.range_m - 3_000.0).abs() <= 2.0 * map.range_bin_m);
+        assert!((best.velocity_m_s - 14.79).abs() <= map.velocity_bin_m_s);
+        assert!(best.snr_db > 20.0);
+    }
+}
@@ -0,0 +1,539 @@
+use crate::micro_doppler::{extract_features, target_spectrogram, StftConfig};
+use crate::signal::FmcwParams;
+use crate::types::TargetPosition;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+use std::sync::OnceLock;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// Slow-time sampling used to measure micro-Doppler features
+const ANALYSIS_PRF_HZ: f64 = 40_000.0;
+const ANALYSIS_PULSES: usize = 2_048;
+/// Spectrogram cells within this many dB of the peak count as occupied
+const MICRO_DOPPLER_DYNAMIC_RANGE_DB: f64 = 30.0;
+
+/// Target categories distinguished by the classifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetClass {
+    Drone,
+    Bird,
+    FixedWing,
+    Clutter,
+    MannedAircraft,
+}
+
+impl TargetClass {
+    pub const ALL: [TargetClass; 5] = [
+        TargetClass::Drone,
+        TargetClass::Bird,
+        TargetClass::FixedWing,
+        TargetClass::Clutter,
+        TargetClass::MannedAircraft,
+    ];
+
+    /// Human-readable name
+    pub fn label(&self) -> &'static str {
+        match self {
+            TargetClass::Drone => "Drone",
+            TargetClass::Bird => "Bird",
+            TargetClass::FixedWing => "Fixed-Wing",
+            TargetClass::Clutter => "Clutter",
+            TargetClass::MannedAircraft => "Manned Aircraft",
+        }
+    }
+}
+
+/// Features describing a track, as consumed by a [`Classifier`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FeatureVector {
+    /// Mean RCS in dBsm
+    pub mean_rcs_dbsm: f64,
+    /// RCS variance divided by the squared mean RCS
+    pub rcs_normalized_variance: f64,
+    /// Mean speed in meters per second
+    pub mean_speed_m_s: f64,
+    /// Standard deviation of speed in meters per second
+    pub speed_std_m_s: f64,
+    /// Micro-Doppler spread in Hz
+    pub micro_doppler_bandwidth_hz: f64,
+    /// Blade-flash rate in Hz
+    pub blade_flash_rate_hz: f64,
+    /// Mean absolute acceleration in meters per second squared
+    pub mean_abs_acceleration_m_s2: f64,
+}
+
+impl FeatureVector {
+    pub const LEN: usize = 7;
+
+    pub fn to_array(&self) -> [f64; Self::LEN] {
+        [
+            self.mean_rcs_dbsm,
+            self.rcs_normalized_variance,
+            self.mean_speed_m_s,
+            self.speed_std_m_s,
+            self.micro_doppler_bandwidth_hz,
+            self.blade_flash_rate_hz,
+            self.mean_abs_acceleration_m_s2,
+        ]
+    }
+
+    /// Features of a single target report.
+    ///
+    /// RCS spread comes from the target's Swerling model and micro-Doppler from
+    /// a spectrogram of its simulated slow-time return. Speed is taken from the
+    /// velocity vector when known; there is no history, so speed spread and
+    /// acceleration are zero.
+    pub fn from_target(target: &TargetPosition) -> Self {
+        let (bandwidth, flash_rate) = micro_doppler(target);
+        Self {
+            mean_rcs_dbsm: rcs_dbsm(target.rcs),
+            rcs_normalized_variance: target.swerling.normalized_variance(),
+            mean_speed_m_s: target.speed_m_s(),
+            speed_std_m_s: 0.0,
+            micro_doppler_bandwidth_hz: bandwidth,
+            blade_flash_rate_hz: flash_rate,
+            mean_abs_acceleration_m_s2: 0.0,
+        }
+    }
+
+    /// Features of a track history sampled every `interval_s` seconds.
+    ///
+    /// Speeds and accelerations come from the velocity vectors, falling back
+    /// to the radial velocity for reports without one. Micro-Doppler is
+    /// measured on the latest report. Returns `None` for an empty history.
+    pub fn from_history(history: &[TargetPosition], interval_s: f64) -> Option<Self> {
+        let latest = history.last()?;
+        let rcs: Vec<f64> = history.iter().map(|t| t.rcs).collect();
+        let speeds: Vec<f64> = history.iter().map(TargetPosition::speed_m_s).collect();
+        let (rcs_mean, rcs_var) = mean_variance(&rcs);
+        let (speed_mean, speed_var) = mean_variance(&speeds);
+        let accelerations: Vec<f64> = history
+            .windows(2)
+            .map(|w| velocity_change_m_s(&w[0], &w[1]) / interval_s)
+            .collect();
+        let (bandwidth, flash_rate) = micro_doppler(latest);
+
+        Some(Self {
+            mean_rcs_dbsm: rcs_dbsm(rcs_mean),
+            rcs_normalized_variance: if rcs_mean > 0.0 {
+                rcs_var / (rcs_mean * rcs_mean)
+            } else {
+                0.0
+            },
+            mean_speed_m_s: speed_mean,
+            speed_std_m_s: speed_var.sqrt(),
+            micro_doppler_bandwidth_hz: bandwidth,
+            blade_flash_rate_hz: flash_rate,
+            mean_abs_acceleration_m_s2: mean_variance(&accelerations).0,
+        })
+    }
+}
+
+fn rcs_dbsm(rcs: f64) -> f64 {
+    10.0 * rcs.max(1e-6).log10()
+}
+
+fn mean_variance(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    (mean, var)
+}
+
+/// Magnitude of the change in velocity between two reports
+fn velocity_change_m_s(from: &TargetPosition, to: &TargetPosition) -> f64 {
+    match (from.velocity, to.velocity) {
+        (Some(a), Some(b)) => (b.vx_m_s - a.vx_m_s)
+            .hypot(b.vy_m_s - a.vy_m_s)
+            .hypot(b.vz_m_s - a.vz_m_s),
+        _ => (to.vel_m_s - from.vel_m_s).abs(),
+    }
+}
+
+fn micro_doppler(target: &TargetPosition) -> (f64, f64) {
+    let spectrogram = target_spectrogram(
+        target,
+        FmcwParams::default().wavelength_m(),
+        ANALYSIS_PRF_HZ,
+        ANALYSIS_PULSES,
+        &StftConfig::default(),
+    );
+    let features = extract_features(&spectrogram, MICRO_DOPPLER_DYNAMIC_RANGE_DB);
+    (features.doppler_bandwidth_hz, features.blade_flash_rate_hz)
+}
+
+/// Posterior probability of one class
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ClassProbability {
+    /// Target class
+    pub class: TargetClass,
+    /// Probability (0.0 to 1.0)
+    pub probability: f64,
+}
+
+/// Maps a feature vector to class probabilities summing to one
+pub trait Classifier: Send + Sync {
+    fn classify(&self, features: &FeatureVector) -> Vec<ClassProbability>;
+}
+
+/// Highest-probability entry
+pub fn most_likely(probabilities: &[ClassProbability]) -> Option<ClassProbability> {
+    probabilities
+        .iter()
+        .copied()
+        .max_by(|a, b| a.probability.total_cmp(&b.probability))
+}
+
+#[derive(Error, Debug)]
+pub enum ClassifierError {
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("no labeled samples to train on")]
+    NoSamples,
+}
+
+#[derive(Debug, Clone)]
+struct ClassModel {
+    class: TargetClass,
+    log_prior: f64,
+    mean: [f64; FeatureVector::LEN],
+    variance: [f64; FeatureVector::LEN],
+}
+
+impl ClassModel {
+    fn log_likelihood(&self, x: &[f64; FeatureVector::LEN]) -> f64 {
+        self.log_prior
+            + x.iter()
+                .zip(self.mean.iter().zip(&self.variance))
+                .map(|(x, (m, v))| {
+                    -0.5 * ((2.0 * std::f64::consts::PI * v).ln() + (x - m).powi(2) / v)
+                })
+                .sum::<f64>()
+    }
+}
+
+/// Gaussian naive Bayes over the [`FeatureVector`] components
+#[derive(Debug, Clone)]
+pub struct GaussianNaiveBayes {
+    classes: Vec<ClassModel>,
+}
+
+/// One row of a training CSV: a `label` column followed by the feature columns
+#[derive(Debug, Deserialize)]
+struct TrainingRow {
+    label: TargetClass,
+    mean_rcs_dbsm: f64,
+    rcs_normalized_variance: f64,
+    mean_speed_m_s: f64,
+    speed_std_m_s: f64,
+    micro_doppler_bandwidth_hz: f64,
+    blade_flash_rate_hz: f64,
+    mean_abs_acceleration_m_s2: f64,
+}
+
+impl GaussianNaiveBayes {
+    /// Fraction of each feature's overall variance added to every class variance
+    const VARIANCE_SMOOTHING: f64 = 1e-2;
+
+    /// Hand-tuned model covering typical X-band observations of each class
+    pub fn pretrained() -> Self {
+        // (mean, standard deviation) per feature, in FeatureVector order
+        #[rustfmt::skip]
+        let table: [(TargetClass, [(f64, f64); FeatureVector::LEN]); 5] = [
+            (TargetClass::Drone, [(-13.0, 6.0), (0.6, 0.4), (15.0, 10.0), (2.0, 2.0), (8_000.0, 3_000.0), (200.0, 120.0), (2.0, 2.0)]),
+            (TargetClass::Bird, [(-20.0, 6.0), (0.8, 0.4), (12.0, 6.0), (3.0, 2.0), (2_500.0, 600.0), (0.0, 30.0), (3.0, 2.0)]),
+            (TargetClass::FixedWing, [(-3.0, 6.0), (0.6, 0.4), (35.0, 12.0), (2.0, 2.0), (2_500.0, 800.0), (0.0, 30.0), (1.5, 1.5)]),
+            (TargetClass::Clutter, [(0.0, 10.0), (0.2, 0.3), (0.5, 1.0), (0.5, 0.5), (2_200.0, 600.0), (0.0, 30.0), (0.2, 0.3)]),
+            (TargetClass::MannedAircraft, [(12.0, 6.0), (0.6, 0.4), (120.0, 60.0), (3.0, 3.0), (2_500.0, 800.0), (0.0, 30.0), (2.0, 2.0)]),
+        ];
+        let log_prior = -(table.len() as f64).ln();
+        let classes = table
+            .iter()
+            .map(|(class, stats)| ClassModel {
+                class: *class,
+                log_prior,
+                mean: stats.map(|(m, _)| m),
+                variance: stats.map(|(_, s)| s * s),
+            })
+            .collect();
+        Self { classes }
+    }
+
+    /// Estimate priors, means and variances from labeled samples.
+    ///
+    /// Classes without samples are left out and always score zero.
+    pub fn fit(samples: &[(TargetClass, FeatureVector)]) -> Result<Self, ClassifierError> {
+        if samples.is_empty() {
+            return Err(ClassifierError::NoSamples);
+        }
+        let all: Vec<[f64; FeatureVector::LEN]> =
+            samples.iter().map(|(_, f)| f.to_array()).collect();
+        let smoothing: [f64; FeatureVector::LEN] = std::array::from_fn(|i| {
+            let column: Vec<f64> = all.iter().map(|x| x[i]).collect();
+            Self::VARIANCE_SMOOTHING * mean_variance(&column).1 + 1e-9
+        });
+
+        let classes = TargetClass::ALL
+            .iter()
+            .filter_map(|&class| {
+                let rows: Vec<&[f64; FeatureVector::LEN]> = samples
+                    .iter()
+                    .zip(&all)
+                    .filter(|((label, _), _)| *label == class)
+                    .map(|(_, x)| x)
+                    .collect();
+                if rows.is_empty() {
+                    return None;
+                }
+                let mut mean = [0.0; FeatureVector::LEN];
+                let mut variance = [0.0; FeatureVector::LEN];
+                for i in 0..FeatureVector::LEN {
+                    let column: Vec<f64> = rows.iter().map(|x| x[i]).collect();
+                    let (m, v) = mean_variance(&column);
+                    mean[i] = m;
+                    variance[i] = v + smoothing[i];
+                }
+                Some(ClassModel {
+                    class,
+                    log_prior: (rows.len() as f64 / samples.len() as f64).ln(),
+                    mean,
+                    variance,
+                })
+            })
+            .collect();
+        Ok(Self { classes })
+    }
+
+    /// Train from CSV with a `label` column (`drone`, `bird`, `fixed_wing`,
+    /// `clutter`, `manned_aircraft`) and one column per feature, headed by the
+    /// [`FeatureVector`] field names
+    pub fn from_csv_reader<R: Read>(reader: R) -> Result<Self, ClassifierError> {
+        let mut csv = csv::Reader::from_reader(reader);
+        let samples = csv
+            .deserialize::<TrainingRow>()
+            .map(|row| {
+                row.map(|r| {
+                    (
+                        r.label,
+                        FeatureVector {
+                            mean_rcs_dbsm: r.mean_rcs_dbsm,
+                            rcs_normalized_variance: r.rcs_normalized_variance,
+                            mean_speed_m_s: r.mean_speed_m_s,
+                            speed_std_m_s: r.speed_std_m_s,
+                            micro_doppler_bandwidth_hz: r.micro_doppler_bandwidth_hz,
+                            blade_flash_rate_hz: r.blade_flash_rate_hz,
+                            mean_abs_acceleration_m_s2: r.mean_abs_acceleration_m_s2,
+                        },
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::fit(&samples)
+    }
+
+    /// Train from a CSV file; see [`GaussianNaiveBayes::from_csv_reader`]
+    pub fn from_csv_path(path: impl AsRef<Path>) -> Result<Self, ClassifierError> {
+        let file = std::fs::File::open(path).map_err(csv::Error::from)?;
+        Self::from_csv_reader(file)
+    }
+}
+
+impl Classifier for GaussianNaiveBayes {
+    fn classify(&self, features: &FeatureVector) -> Vec<ClassProbability> {
+        let x = features.to_array();
+        let scores: Vec<f64> = self.classes.iter().map(|c| c.log_likelihood(&x)).collect();
+        let max = scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = scores.iter().map(|s| (s - max).exp()).collect();
+        let total: f64 = weights.iter().sum();
+
+        TargetClass::ALL
+            .iter()
+            .map(|&class| {
+                let probability = self
+                    .classes
+                    .iter()
+                    .position(|c| c.class == class)
+                    .map_or(0.0, |i| weights[i] / total);
+                ClassProbability { class, probability }
+            })
+            .collect()
+    }
+}
+
+static DEFAULT_CLASSIFIER: OnceLock<Box<dyn Classifier>> = OnceLock::new();
+
+/// Install the classifier used by `analyze_drone`. Fails if one is already in use.
+pub fn set_default_classifier(classifier: Box<dyn Classifier>) -> Result<(), Box<dyn Classifier>> {
+    DEFAULT_CLASSIFIER.set(classifier)
+}
+
+/// Classifier used by `analyze_drone`; the pretrained naive Bayes model unless
+/// another was installed first
+pub fn default_classifier() -> &'static dyn Classifier {
+    DEFAULT_CLASSIFIER
+        .get_or_init(|| Box::new(GaussianNaiveBayes::pretrained()))
+        .as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::micro_doppler::RotorModel;
+    use crate::swerling::SwerlingModel;
+
+    fn target(rcs: f64, vel_m_s: f64, rotor: Option<RotorModel>) -> TargetPosition {
+        TargetPosition {
+            range_m: 5_000.0,
+            vel_m_s,
+            rcs,
+            swerling: SwerlingModel::One,
+            rotor,
+            ..TargetPosition::default()
+        }
+    }
+
+    fn classify(target: &TargetPosition) -> TargetClass {
+        let probabilities =
+            GaussianNaiveBayes::pretrained().classify(&FeatureVector::from_target(target));
+        most_likely(&probabilities).unwrap().class
+    }
+
+    #[test]
+    fn test_probabilities_sum_to_one() {
+        let features = FeatureVector::from_target(&target(0.5, 20.0, None));
+        let probabilities = GaussianNaiveBayes::pretrained().classify(&features);
+        assert_eq!(probabilities.len(), TargetClass::ALL.len());
+        let total: f64 = probabilities.iter().map(|p| p.probability).sum();
+        assert!((total - 1.0).abs() < 1e-12);
+        assert!(probabilities
+            .iter()
+            .all(|p| (0.0..=1.0).contains(&p.probability)));
+    }
+
+    #[test]
+    fn test_pretrained_separates_classes() {
+        assert_eq!(
+            classify(&target(1.0, 25.0, Some(RotorModel::quadcopter()))),
+            TargetClass::Drone
+        );
+        assert_eq!(classify(&target(0.01, 10.0, None)), TargetClass::Bird);
+        assert_eq!(classify(&target(0.6, 45.0, None)), TargetClass::FixedWing);
+        assert_eq!(
+            classify(&target(20.0, 180.0, None)),
+            TargetClass::MannedAircraft
+        );
+        assert_eq!(classify(&target(3.0, 0.0, None)), TargetClass::Clutter);
+    }
+
+    #[test]
+    fn test_rotor_raises_drone_probability() {
+        let classifier = GaussianNaiveBayes::pretrained();
+        let drone_probability = |rotor| {
+            classifier
+                .classify(&FeatureVector::from_target(&target(0.05, 10.0, rotor)))
+                .into_iter()
+                .find(|p| p.class == TargetClass::Drone)
+                .unwrap()
+                .probability
+        };
+        assert!(drone_probability(Some(RotorModel::quadcopter())) > 0.9);
+        assert!(drone_probability(None) < 0.5);
+    }
+
+    #[test]
+    fn test_history_features() {
+        let history: Vec<TargetPosition> = [10.0, 12.0, 14.0]
+            .iter()
+            .map(|&v| TargetPosition {
+                rcs: 2.0,
+                ..target(2.0, v, None)
+            })
+            .collect();
+        let features = FeatureVector::from_history(&history, 0.5).unwrap();
+        assert!((features.mean_rcs_dbsm - 10.0 * 2f64.log10()).abs() < 1e-12);
+        assert_eq!(features.rcs_normalized_variance, 0.0);
+        assert!((features.mean_speed_m_s - 12.0).abs() < 1e-12);
+        assert!((features.speed_std_m_s - (8.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert!((features.mean_abs_acceleration_m_s2 - 4.0).abs() < 1e-12);
+        assert!(FeatureVector::from_history(&[], 0.5).is_none());
+    }
+
+    #[test]
+    fn test_speed_features_use_velocity_vector() {
+        use crate::types::Velocity3;
+
+        // Due north of the radar and flying east: no radial velocity at all
+        let crossing = |vx_m_s: f64, vy_m_s: f64| TargetPosition {
+            velocity: Some(Velocity3 {
+                vx_m_s,
+                vy_m_s,
+                vz_m_s: 0.0,
+            }),
+            ..target(20.0, 0.0, None)
+        };
+        let features = FeatureVector::from_target(&crossing(180.0, 0.0));
+        assert_eq!(features.mean_speed_m_s, 180.0);
+        assert_eq!(classify(&crossing(180.0, 0.0)), TargetClass::MannedAircraft);
+
+        // A constant-speed turn still accelerates
+        let history = [crossing(30.0, 0.0), crossing(0.0, 30.0)];
+        let features = FeatureVector::from_history(&history, 0.5).unwrap();
+        assert!((features.mean_speed_m_s - 30.0).abs() < 1e-12);
+        assert_eq!(features.speed_std_m_s, 0.0);
+        assert!((features.mean_abs_acceleration_m_s2 - 30.0 * 2f64.sqrt() / 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_train_from_csv() {
+        let csv = "\
+label,mean_rcs_dbsm,rcs_normalized_variance,mean_speed_m_s,speed_std_m_s,micro_doppler_bandwidth_hz,blade_flash_rate_hz,mean_abs_acceleration_m_s2
+drone,-12,0.5,10,1,9000,200,1
+drone,-14,0.7,14,2,8000,180,2
+drone,-10,0.6,8,1,9500,220,1
+bird,-22,0.9,11,3,2400,0,3
+bird,-19,0.8,13,2,2600,0,4
+bird,-21,0.7,9,3,2300,0,2
+";
+        let model = GaussianNaiveBayes::from_csv_reader(csv.as_bytes()).unwrap();
+        let probabilities = model.classify(&FeatureVector {
+            mean_rcs_dbsm: -13.0,
+            rcs_normalized_variance: 0.6,
+            mean_speed_m_s: 11.0,
+            speed_std_m_s: 1.5,
+            micro_doppler_bandwidth_hz: 8_500.0,
+            blade_flash_rate_hz: 190.0,
+            mean_abs_acceleration_m_s2: 1.5,
+        });
+        assert_eq!(
+            most_likely(&probabilities).unwrap().class,
+            TargetClass::Drone
+        );
+        // Classes absent from the training data never win
+        let clutter = probabilities
+            .iter()
+            .find(|p| p.class == TargetClass::Clutter)
+            .unwrap();
+        assert_eq!(clutter.probability, 0.0);
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        let header = "label,mean_rcs_dbsm,rcs_normalized_variance,mean_speed_m_s,speed_std_m_s,micro_doppler_bandwidth_hz,blade_flash_rate_hz,mean_abs_acceleration_m_s2\n";
+        assert!(matches!(
+            GaussianNaiveBayes::from_csv_reader(header.as_bytes()),
+            Err(ClassifierError::NoSamples)
+        ));
+        let bad_label = format!("{header}helicopter,0,0,0,0,0,0,0\n");
+        assert!(matches!(
+            GaussianNaiveBayes::from_csv_reader(bad_label.as_bytes()),
+            Err(ClassifierError::Csv(_))
+        ));
+    }
+}
@@ -0,0 +1,594 @@
+use crate::constants::C;
+use crate::error::{AppError, AppResult};
+use crate::signal::FmcwParams;
+use ndarray::{Array2, Array3};
+use num_complex::Complex;
+use rand::Rng;
+use rand_distr::{Distribution, Gamma, Normal, StandardNormal};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use utoipa::ToSchema;
+
+/// Scatterers summed per range cell when synthesizing clutter IQ
+const SCATTERERS_PER_CELL: usize = 16;
+
+/// Clutter sources present in a scenario; all absent by default (clean scene)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ClutterConfig {
+    /// Land clutter
+    #[serde(default)]
+    pub ground: Option<GroundClutter>,
+    /// Volumetric precipitation clutter
+    #[serde(default)]
+    pub rain: Option<RainClutter>,
+    /// Sea surface clutter
+    #[serde(default)]
+    pub sea: Option<SeaClutter>,
+    /// Two-way azimuth beamwidth in degrees
+    #[serde(default = "default_azimuth_beamwidth")]
+    pub azimuth_beamwidth_deg: f64,
+    /// Two-way elevation beamwidth in degrees
+    #[serde(default = "default_elevation_beamwidth")]
+    pub elevation_beamwidth_deg: f64,
+}
+
+fn default_azimuth_beamwidth() -> f64 {
+    2.0
+}
+
+fn default_elevation_beamwidth() -> f64 {
+    5.0
+}
+
+impl Default for ClutterConfig {
+    fn default() -> Self {
+        Self {
+            ground: None,
+            rain: None,
+            sea: None,
+            azimuth_beamwidth_deg: default_azimuth_beamwidth(),
+            elevation_beamwidth_deg: default_elevation_beamwidth(),
+        }
+    }
+}
+
+/// Land clutter with reflectivity falling off with range (grazing angle)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GroundClutter {
+    /// Surface reflectivity sigma0 at the reference range, in dB (m²/m²)
+    pub reflectivity_db: f64,
+    /// Range at which `reflectivity_db` applies
+    pub reference_range_m: f64,
+    /// sigma0 scales as (reference range / range) ^ exponent
+    pub range_exponent: f64,
+    /// Radar horizon; no ground clutter beyond this range
+    pub max_range_m: f64,
+    /// Standard deviation of the clutter velocity spectrum (wind-blown vegetation)
+    pub spectral_spread_m_s: f64,
+}
+
+impl Default for GroundClutter {
+    fn default() -> Self {
+        Self {
+            reflectivity_db: -20.0,
+            reference_range_m: 1_000.0,
+            range_exponent: 1.0,
+            max_range_m: 15_000.0,
+            spectral_spread_m_s: 0.1,
+        }
+    }
+}
+
+impl GroundClutter {
+    /// Linear sigma0 at a given range
+    pub fn reflectivity(&self, range_m: f64) -> f64 {
+        if range_m <= 0.0 || range_m > self.max_range_m {
+            return 0.0;
+        }
+        10f64.powf(self.reflectivity_db / 10.0)
+            * (self.reference_range_m / range_m).powf(self.range_exponent)
+    }
+}
+
+/// Rain filling the beam between two ranges
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RainClutter {
+    /// Rain rate in mm/h
+    pub rain_rate_mm_h: f64,
+    /// Near edge of the rain cell
+    pub min_range_m: f64,
+    /// Far edge of the rain cell
+    pub max_range_m: f64,
+    /// Mean radial velocity of the drops (wind component along the beam)
+    pub wind_velocity_m_s: f64,
+    /// Standard deviation of the velocity spectrum (turbulence and shear)
+    pub spectral_spread_m_s: f64,
+}
+
+impl Default for RainClutter {
+    fn default() -> Self {
+        Self {
+            rain_rate_mm_h: 4.0,
+            min_range_m: 2_000.0,
+            max_range_m: 12_000.0,
+            wind_velocity_m_s: 5.0,
+            spectral_spread_m_s: 1.5,
+        }
+    }
+}
+
+impl RainClutter {
+    /// Volume reflectivity eta in m²/m³: 7e-12 f^4 r^1.6 with f in GHz
+    pub fn volume_reflectivity(&self, wavelength_m: f64) -> f64 {
+        let f_ghz = C / wavelength_m / 1e9;
+        7e-12 * f_ghz.powi(4) * self.rain_rate_mm_h.max(0.0).powf(1.6)
+    }
+}
+
+/// Amplitude statistics of sea clutter
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SeaAmplitude {
+    /// Compound Gaussian with gamma texture; smaller `shape` means spikier clutter
+    KDistribution { shape: f64 },
+    /// Weibull amplitude; `shape` 2 is Rayleigh, smaller is spikier
+    Weibull { shape: f64 },
+}
+
+/// Sea surface clutter
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SeaClutter {
+    /// Mean surface reflectivity sigma0 in dB (m²/m²)
+    pub reflectivity_db: f64,
+    /// No sea clutter beyond this range
+    pub max_range_m: f64,
+    pub amplitude: SeaAmplitude,
+    /// Mean radial velocity of the surface (waves and currents)
+    pub mean_velocity_m_s: f64,
+    /// Standard deviation of the velocity spectrum
+    pub spectral_spread_m_s: f64,
+}
+
+impl Default for SeaClutter {
+    fn default() -> Self {
+        Self {
+            reflectivity_db: -35.0,
+            max_range_m: 20_000.0,
+            amplitude: SeaAmplitude::KDistribution { shape: 1.5 },
+            mean_velocity_m_s: 1.0,
+            spectral_spread_m_s: 1.0,
+        }
+    }
+}
+
+/// How the complex Gaussian speckle of a clutter component is reshaped
+#[derive(Debug, Clone, Copy)]
+enum Statistics {
+    Rayleigh,
+    K { shape: f64 },
+    Weibull { shape: f64 },
+}
+
+/// One clutter source's contribution to a resolution cell
+#[derive(Debug, Clone, Copy)]
+struct Component {
+    rcs: f64,
+    mean_velocity_m_s: f64,
+    spread_m_s: f64,
+    statistics: Statistics,
+}
+
+impl ClutterConfig {
+    /// Reject clutter sources with negative extents, rates or spreads, or a
+    /// beam that is not a proper angle
+    pub fn validate(&self) -> AppResult<()> {
+        let invalid = |what: &str| Err(AppError::InvalidInput(format!("invalid {what}")));
+        let beam = |width: f64| width > 0.0 && width <= 180.0;
+        if !beam(self.azimuth_beamwidth_deg) || !beam(self.elevation_beamwidth_deg) {
+            return invalid("clutter beamwidth");
+        }
+        if let Some(ground) = &self.ground {
+            if ground.reference_range_m <= 0.0
+                || ground.max_range_m < 0.0
+                || ground.spectral_spread_m_s < 0.0
+            {
+                return invalid("ground clutter");
+            }
+        }
+        if let Some(rain) = &self.rain {
+            if rain.rain_rate_mm_h < 0.0
+                || rain.min_range_m < 0.0
+                || rain.max_range_m < rain.min_range_m
+                || rain.spectral_spread_m_s < 0.0
+            {
+                return invalid("rain clutter");
+            }
+        }
+        if let Some(sea) = &self.sea {
+            let (SeaAmplitude::KDistribution { shape } | SeaAmplitude::Weibull { shape }) =
+                sea.amplitude;
+            if shape <= 0.0 || sea.max_range_m < 0.0 || sea.spectral_spread_m_s < 0.0 {
+                return invalid("sea clutter");
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether any clutter source is enabled
+    pub fn is_empty(&self) -> bool {
+        self.ground.is_none() && self.rain.is_none() && self.sea.is_none()
+    }
+
+    /// Mean clutter RCS of the resolution cell at `range_m` with depth `cell_m`
+    pub fn cell_rcs(&self, range_m: f64, cell_m: f64, wavelength_m: f64) -> f64 {
+        self.components(range_m, cell_m, wavelength_m)
+            .iter()
+            .map(|c| c.rcs)
+            .sum()
+    }
+
+    fn components(&self, range_m: f64, cell_m: f64, wavelength_m: f64) -> Vec<Component> {
+        let az = self.azimuth_beamwidth_deg.to_radians();
+        let el = self.elevation_beamwidth_deg.to_radians();
+        let surface_area = range_m * az * cell_m;
+        let mut components = Vec::new();
+
+        if let Some(ground) = &self.ground {
+            let rcs = ground.reflectivity(range_m) * surface_area;
+            if rcs > 0.0 {
+                components.push(Component {
+                    rcs,
+                    mean_velocity_m_s: 0.0,
+                    spread_m_s: ground.spectral_spread_m_s,
+                    statistics: Statistics::Rayleigh,
+                });
+            }
+        }
+        if let Some(rain) = &self.rain {
+            if (rain.min_range_m..=rain.max_range_m).contains(&range_m) {
+                let volume = PI / 4.0 * (range_m * az) * (range_m * el) * cell_m;
+                components.push(Component {
+                    rcs: rain.volume_reflectivity(wavelength_m) * volume,
+                    mean_velocity_m_s: rain.wind_velocity_m_s,
+                    spread_m_s: rain.spectral_spread_m_s,
+                    statistics: Statistics::Rayleigh,
+                });
+            }
+        }
+        if let Some(sea) = &self.sea {
+            if range_m > 0.0 && range_m <= sea.max_range_m {
+                components.push(Component {
+                    rcs: 10f64.powf(sea.reflectivity_db / 10.0) * surface_area,
+                    mean_velocity_m_s: sea.mean_velocity_m_s,
+                    spread_m_s: sea.spectral_spread_m_s,
+                    statistics: match sea.amplitude {
+                        SeaAmplitude::KDistribution { shape } => Statistics::K { shape },
+                        SeaAmplitude::Weibull { shape } => Statistics::Weibull { shape },
+                    },
+                });
+            }
+        }
+        components
+    }
+}
+
+fn complex_gaussian<R: Rng + ?Sized>(rng: &mut R) -> Complex<f64> {
+    let re: f64 = StandardNormal.sample(rng);
+    let im: f64 = StandardNormal.sample(rng);
+    Complex::new(re, im) * 0.5f64.sqrt()
+}
+
+/// Unit-mean gamma texture of a K-distributed cell
+fn k_texture<R: Rng + ?Sized>(shape: f64, rng: &mut R) -> f64 {
+    match Gamma::new(shape.max(1e-3), 1.0 / shape.max(1e-3)) {
+        Ok(gamma) => gamma.sample(rng),
+        Err(_) => 1.0,
+    }
+}
+
+/// Memoryless transform of a complex Gaussian sample with mean power `power`
+/// into one with Weibull amplitude of the same mean power, keeping its phase
+fn weibull_transform(sample: Complex<f64>, power: f64, shape: f64) -> Complex<f64> {
+    if power <= 0.0 || sample.norm() == 0.0 {
+        return sample;
+    }
+    let shape = shape.max(0.1);
+    // |z|²/P is unit exponential; map it through the Weibull inverse CDF
+    let exponential = sample.norm_sqr() / power;
+    let scale = (power / crate::special::ln_gamma(1.0 + 2.0 / shape).exp()).sqrt();
+    let amplitude = scale * exponential.powf(1.0 / shape);
+    Complex::from_polar(amplitude, sample.arg())
+}
+
+/// Doppler power spectrum of a clutter component over the (shifted) bins of a
+/// map, Gaussian in velocity and folded at the ambiguity limits; sums to one
+fn doppler_weights(component: &Component, num_bins: usize, velocity_bin_m_s: f64) -> Vec<f64> {
+    let span = num_bins as f64 * velocity_bin_m_s;
+    let sigma = component.spread_m_s.max(0.25 * velocity_bin_m_s);
+    let weights: Vec<f64> = (0..num_bins)
+        .map(|d| {
+            let v = (d as f64 - (num_bins / 2) as f64) * velocity_bin_m_s;
+            (-1..=1)
+                .map(|k| {
+                    let offset = v - component.mean_velocity_m_s + k as f64 * span;
+                    (-0.5 * (offset / sigma).powi(2)).exp()
+                })
+                .sum()
+        })
+        .collect();
+    let total: f64 = weights.iter().sum();
+    weights
+        .iter()
+        .map(|w| w / total.max(f64::MIN_POSITIVE))
+        .collect()
+}
+
+/// Detection-level clutter: complex cell amplitudes of a range-Doppler grid.
+///
+/// Each range cell's clutter power is spread over Doppler bins following its
+/// velocity spectrum, with Rayleigh, K or Weibull amplitude statistics. Mean
+/// `|x|²` summed over a range row equals the cell's clutter RCS; scale by the
+/// radar equation to compare with noise.
+pub fn clutter_map<R: Rng + ?Sized>(
+    config: &ClutterConfig,
+    num_range_bins: usize,
+    num_doppler_bins: usize,
+    range_bin_m: f64,
+    velocity_bin_m_s: f64,
+    wavelength_m: f64,
+    rng: &mut R,
+) -> Array2<Complex<f64>> {
+    let mut map = Array2::<Complex<f64>>::zeros((num_range_bins, num_doppler_bins));
+    for r in 1..num_range_bins {
+        let range_m = r as f64 * range_bin_m;
+        for component in config.components(range_m, range_bin_m, wavelength_m) {
+            let weights = doppler_weights(&component, num_doppler_bins, velocity_bin_m_s);
+            let texture = match component.statistics {
+                Statistics::K { shape } => k_texture(shape, rng),
+                _ => 1.0,
+            };
+            for (d, weight) in weights.iter().enumerate() {
+                let power = component.rcs * weight;
+                let speckle = complex_gaussian(rng) * (power * texture).sqrt();
+                map[[r, d]] += match component.statistics {
+                    Statistics::Weibull { shape } => weibull_transform(speckle, power, shape),
+                    _ => speckle,
+                };
+            }
+        }
+    }
+    map
+}
+
+/// Signal-level clutter: add clutter returns to a beat-signal IQ cube.
+///
+/// Every range resolution cell out to the unambiguous range holds a patch of
+/// scatterers at one azimuth inside the beam, with velocities drawn from its
+/// clutter spectrum. Amplitudes are `sqrt(rcs)` like [`crate::signal::simulate_iq_cube`].
+pub fn add_clutter_to_cube<R: Rng + ?Sized>(
+    cube: &mut Array3<Complex<f64>>,
+    params: &FmcwParams,
+    config: &ClutterConfig,
+    rng: &mut R,
+) {
+    let (num_samples, num_chirps, num_rx) = cube.dim();
+    let wavelength = params.wavelength_m();
+    let cell_m = params.range_resolution_m();
+    let dt = 1.0 / params.sample_rate_hz;
+    let num_cells = (params.max_range_m() / cell_m) as usize;
+    let half_beam = 0.5 * config.azimuth_beamwidth_deg.to_radians();
+
+    let mut slow_time = vec![Complex::new(0.0, 0.0); num_chirps];
+    for cell in 1..num_cells {
+        let range_m = cell as f64 * cell_m;
+        let components = config.components(range_m, cell_m, wavelength);
+        if components.is_empty() {
+            continue;
+        }
+
+        slow_time
+            .iter_mut()
+            .for_each(|s| *s = Complex::new(0.0, 0.0));
+        for component in &components {
+            let texture = match component.statistics {
+                Statistics::K { shape } => k_texture(shape, rng),
+                _ => 1.0,
+            };
+            let amplitude = (component.rcs * texture / SCATTERERS_PER_CELL as f64).sqrt();
+            let velocity = Normal::new(component.mean_velocity_m_s, component.spread_m_s.max(0.0))
+                .unwrap_or_else(|_| Normal::new(component.mean_velocity_m_s, 0.0).unwrap());
+            let mut patch = vec![Complex::new(0.0, 0.0); num_chirps];
+            for _ in 0..SCATTERERS_PER_CELL {
+                let v = velocity.sample(rng);
+                let phase0 = rng.gen_range(0.0..2.0 * PI);
+                let step = 4.0 * PI * v * params.chirp_duration_s / wavelength;
+                for (m, p) in patch.iter_mut().enumerate() {
+                    *p += Complex::from_polar(amplitude, phase0 + step * m as f64);
+                }
+            }
+            if let Statistics::Weibull { shape } = component.statistics {
+                for p in &mut patch {
+                    *p = weibull_transform(*p, component.rcs, shape);
+                }
+            }
+            for (s, p) in slow_time.iter_mut().zip(&patch) {
+                *s += p;
+            }
+        }
+
+        let azimuth: f64 = rng.gen_range(-half_beam..=half_beam);
+        let spatial_step = PI * azimuth.sin();
+        let beat_step = 2.0 * PI * params.chirp_slope() * 2.0 * range_m / C * dt;
+        let rotation = Complex::from_polar(1.0, beat_step);
+        for k in 0..num_rx {
+            let steer = Complex::from_polar(1.0, spatial_step * k as f64);
+            for (m, s) in slow_time.iter().enumerate() {
+                let mut sample = s * steer;
+                for n in 0..num_samples {
+                    cube[[n, m, k]] += sample;
+                    sample *= rotation;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::{range_doppler_map, ProcessingConfig};
+    use crate::signal::simulate_iq_cube;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const WAVELENGTH: f64 = 0.0316;
+
+    fn small_params() -> FmcwParams {
+        FmcwParams {
+            carrier_freq_hz: 9.5e9,
+            bandwidth_hz: 5e6,
+            chirp_duration_s: 100e-6,
+            chirps_per_frame: 32,
+            sample_rate_hz: 2.56e6,
+            num_rx: 2,
+        }
+    }
+
+    /// Mean Doppler row power over range bins
+    fn doppler_profile(map: &Array2<Complex<f64>>) -> Vec<f64> {
+        (0..map.ncols())
+            .map(|d| map.column(d).iter().map(|c| c.norm_sqr()).sum::<f64>())
+            .collect()
+    }
+
+    #[test]
+    fn test_ground_reflectivity_falls_with_range() {
+        let ground = GroundClutter::default();
+        assert!((ground.reflectivity(1_000.0) - 0.01).abs() < 1e-12);
+        assert!((ground.reflectivity(2_000.0) - 0.005).abs() < 1e-12);
+        assert_eq!(ground.reflectivity(20_000.0), 0.0);
+    }
+
+    #[test]
+    fn test_rain_reflectivity_grows_with_rate() {
+        let light = RainClutter::default();
+        let heavy = RainClutter {
+            rain_rate_mm_h: 16.0,
+            ..RainClutter::default()
+        };
+        let ratio = heavy.volume_reflectivity(WAVELENGTH) / light.volume_reflectivity(WAVELENGTH);
+        assert!((ratio - 4f64.powf(1.6)).abs() < 1e-9);
+        // X-band, 4 mm/h: a few 1e-7 m²/m³
+        let eta = light.volume_reflectivity(WAVELENGTH);
+        assert!(eta > 1e-7 && eta < 1e-6, "eta {eta}");
+    }
+
+    #[test]
+    fn test_clean_scene_has_no_clutter() {
+        let config = ClutterConfig::default();
+        assert!(config.is_empty());
+        assert_eq!(config.cell_rcs(5_000.0, 30.0, WAVELENGTH), 0.0);
+        let mut rng = StdRng::seed_from_u64(1);
+        let map = clutter_map(&config, 64, 16, 30.0, 1.0, WAVELENGTH, &mut rng);
+        assert!(map.iter().all(|c| c.norm() == 0.0));
+    }
+
+    #[test]
+    fn test_ground_clutter_concentrates_at_zero_doppler() {
+        let config = ClutterConfig {
+            ground: Some(GroundClutter::default()),
+            ..ClutterConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(2);
+        let map = clutter_map(&config, 256, 32, 30.0, 0.5, WAVELENGTH, &mut rng);
+        let profile = doppler_profile(&map);
+        let total: f64 = profile.iter().sum();
+        assert!(profile[16] + profile[15] + profile[17] > 0.95 * total);
+    }
+
+    #[test]
+    fn test_rain_doppler_follows_wind() {
+        let config = ClutterConfig {
+            rain: Some(RainClutter {
+                wind_velocity_m_s: 6.0,
+                spectral_spread_m_s: 1.0,
+                ..RainClutter::default()
+            }),
+            ..ClutterConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(3);
+        let map = clutter_map(&config, 512, 32, 30.0, 0.5, WAVELENGTH, &mut rng);
+        let profile = doppler_profile(&map);
+        let peak = (0..profile.len())
+            .max_by(|&a, &b| profile[a].total_cmp(&profile[b]))
+            .unwrap();
+        assert!(((peak as f64 - 16.0) * 0.5 - 6.0).abs() <= 1.0);
+        // Rain is confined to its cell
+        assert!(map.row(20).iter().all(|c| c.norm() == 0.0));
+        assert!(map.row(100).iter().any(|c| c.norm() > 0.0));
+    }
+
+    #[test]
+    fn test_sea_clutter_is_spikier_than_rayleigh() {
+        // Normalised fourth moment E|x|^4 / E|x|^2^2 is 2 for Rayleigh and larger for K/Weibull
+        let kurtosis = |amplitude: SeaAmplitude| {
+            let config = ClutterConfig {
+                sea: Some(SeaClutter {
+                    amplitude,
+                    max_range_m: 1e9,
+                    ..SeaClutter::default()
+                }),
+                ..ClutterConfig::default()
+            };
+            let mut rng = StdRng::seed_from_u64(4);
+            let map = clutter_map(&config, 4_096, 8, 30.0, 0.5, WAVELENGTH, &mut rng);
+            // Normalise each range row so the range dependence of sigma drops out
+            let (mut m2, mut m4, mut n) = (0.0, 0.0, 0.0);
+            for r in 1..map.nrows() {
+                let rcs = config.cell_rcs(r as f64 * 30.0, 30.0, WAVELENGTH);
+                let d = map.ncols() / 2;
+                let p = map[[r, d]].norm_sqr() / rcs;
+                m2 += p;
+                m4 += p * p;
+                n += 1.0;
+            }
+            (m4 / n) / (m2 / n).powi(2)
+        };
+        let k = kurtosis(SeaAmplitude::KDistribution { shape: 0.5 });
+        let weibull = kurtosis(SeaAmplitude::Weibull { shape: 1.0 });
+        let rayleigh_like = kurtosis(SeaAmplitude::Weibull { shape: 2.0 });
+        assert!(
+            (rayleigh_like - 2.0).abs() < 0.3,
+            "Rayleigh {rayleigh_like}"
+        );
+        // K: 2 (1 + 1/shape) = 6; Weibull shape 1: Gamma(5)/Gamma(3)^2 = 6
+        assert!(k > 4.0, "K {k}");
+        assert!(weibull > 4.0, "Weibull {weibull}");
+    }
+
+    #[test]
+    fn test_signal_level_ground_clutter_at_zero_doppler() {
+        let params = small_params();
+        let config = ClutterConfig {
+            ground: Some(GroundClutter {
+                max_range_m: 3_000.0,
+                ..GroundClutter::default()
+            }),
+            ..ClutterConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut cube = simulate_iq_cube(&params, &[]);
+        add_clutter_to_cube(&mut cube, &params, &config, &mut rng);
+        assert!(cube.iter().any(|s| s.norm() > 0.0));
+
+        let map = range_doppler_map(&cube, &params, &ProcessingConfig::default());
+        let power = map.power();
+        let zero = map.num_doppler_bins() / 2;
+        let near_zero: f64 = (zero - 1..=zero + 1).map(|d| power.column(d).sum()).sum();
+        assert!(near_zero > 0.95 * power.sum());
+        // Nothing beyond the clutter horizon but window sidelobes
+        let near: f64 = power.row(50).sum();
+        let far: f64 = power.row(200).sum();
+        assert!(far < 1e-4 * near);
+    }
+}
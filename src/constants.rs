@@ -1,2 +1,5 @@
 /// Physical constants used in radar simulation
 pub const C: f64 = 299_792_458.0; // Speed of light in m/s
+pub const BOLTZMANN: f64 = 1.380_649e-23; // Boltzmann constant in J/K
+pub const T0: f64 = 290.0; // Standard noise reference temperature in K
+pub const EARTH_RADIUS_M: f64 = 6_371_000.0; // Mean earth radius in m
@@ -0,0 +1,604 @@
+use crate::association::hungarian;
+use crate::tracking::{TrackEstimate, TrackStatus};
+use crate::trajectory::ManeuveringTarget;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use utoipa::ToSchema;
+
+/// Per-scan scores kept for a live session: ten minutes of the 10 Hz WebSocket loop
+const MAX_SCAN_SCORES: usize = 6_000;
+
+/// True state of a simulated target at one scan
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TruthState {
+    /// Id of the simulated target
+    pub id: usize,
+    /// East position in meters
+    pub x_m: f64,
+    /// North position in meters
+    pub y_m: f64,
+    pub vx_m_s: f64,
+    pub vy_m_s: f64,
+}
+
+impl From<&ManeuveringTarget> for TruthState {
+    fn from(target: &ManeuveringTarget) -> Self {
+        let (x_m, y_m) = target.position();
+        let (vx_m_s, vy_m_s) = target.velocity();
+        Self {
+            id: target.template.id,
+            x_m,
+            y_m,
+            vx_m_s,
+            vy_m_s,
+        }
+    }
+}
+
+/// Ground truth and published tracks at one scan
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EvaluationScan {
+    /// Seconds since the run started
+    pub time_s: f64,
+    pub truth: Vec<TruthState>,
+    /// Tentative and deleted tracks are not scored
+    pub tracks: Vec<TrackEstimate>,
+}
+
+/// Scoring settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct EvaluationConfig {
+    /// OSPA/GOSPA cut-off `c` in meters: the cost of a missed target or a
+    /// false track, and the distance within which a track counts as following
+    /// the target it is assigned to
+    pub cutoff_m: f64,
+    /// OSPA/GOSPA order `p` (at least 1)
+    pub order: f64,
+}
+
+impl Default for EvaluationConfig {
+    fn default() -> Self {
+        Self {
+            cutoff_m: 100.0,
+            order: 2.0,
+        }
+    }
+}
+
+/// Set distances and counts at one scan
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ScanScore {
+    pub time_s: f64,
+    /// Optimal sub-pattern assignment distance, normalised by the larger set
+    pub ospa_m: f64,
+    /// Generalised OSPA (alpha = 2): localisation error plus half the
+    /// cut-off cost for every missed target and false track, unnormalised
+    pub gospa_m: f64,
+    pub targets: usize,
+    pub tracks: usize,
+    /// Targets no track follows
+    pub missed_targets: usize,
+    /// Tracks following no target
+    pub false_tracks: usize,
+}
+
+/// Accuracy of one track over the scans it followed a target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TrackAccuracy {
+    pub track_id: u32,
+    /// Target the track followed most often
+    pub target_id: usize,
+    /// Scans on which it followed a target
+    pub scans: u32,
+    pub position_rmse_m: f64,
+    pub velocity_rmse_m_s: f64,
+}
+
+/// How continuously one target was tracked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TargetContinuity {
+    pub target_id: usize,
+    /// Scans the target was present
+    pub scans: u32,
+    /// Fraction of those on which a track followed it
+    pub coverage: f64,
+    /// Times tracking resumed after a gap
+    pub fragmentations: u32,
+    /// Times the following track changed identity
+    pub id_switches: u32,
+}
+
+/// Scores of a tracking run against ground truth
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EvaluationReport {
+    pub config: EvaluationConfig,
+    pub scans: u64,
+    pub mean_ospa_m: f64,
+    pub mean_gospa_m: f64,
+    /// Over every scan on which a track followed a target
+    pub position_rmse_m: f64,
+    pub velocity_rmse_m_s: f64,
+    pub fragmentations: u32,
+    pub id_switches: u32,
+    /// Mean number of published tracks following no target
+    pub false_tracks_per_scan: f64,
+    /// Mean number of targets no track follows
+    pub missed_targets_per_scan: f64,
+    /// By track id
+    pub tracks: Vec<TrackAccuracy>,
+    /// By target id
+    pub targets: Vec<TargetContinuity>,
+    /// Most recent scans, oldest first
+    pub per_scan: Vec<ScanScore>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TrackTally {
+    scans: u32,
+    position_sq: f64,
+    velocity_sq: f64,
+    targets: HashMap<usize, u32>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TargetTally {
+    scans: u32,
+    covered: u32,
+    fragmentations: u32,
+    id_switches: u32,
+    /// Track that last followed the target
+    last_track: Option<u32>,
+    /// Whether a track followed it on its previous scan
+    covered_last_scan: bool,
+}
+
+/// Optimal assignment of `rows` to `cols` by cost, returning `(row, col)`
+/// pairs; the smaller side is fully assigned
+fn assign(cost: &Array2<f64>) -> Vec<(usize, usize)> {
+    let (rows, cols) = cost.dim();
+    if rows == 0 || cols == 0 {
+        return Vec::new();
+    }
+    if rows <= cols {
+        hungarian(cost).into_iter().enumerate().collect()
+    } else {
+        let transposed = cost.t().to_owned();
+        hungarian(&transposed)
+            .into_iter()
+            .enumerate()
+            .map(|(col, row)| (row, col))
+            .collect()
+    }
+}
+
+/// Scores tracker output against ground truth scan by scan.
+///
+/// Every scan, targets and scored tracks are matched by the assignment that
+/// minimises the sum of `min(d, c)^p` over position distances `d`; this one
+/// assignment gives both OSPA and GOSPA. A matched track within the cut-off
+/// follows its target: its position and velocity errors feed the RMSE, and
+/// changes in which track follows a target count as ID switches, or as
+/// fragmentations when tracking resumes after a gap. Unmatched tracks, and
+/// tracks matched beyond the cut-off, are false tracks.
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    pub config: EvaluationConfig,
+    scans: u64,
+    ospa_sum: f64,
+    gospa_sum: f64,
+    false_tracks: u64,
+    missed_targets: u64,
+    tracks: BTreeMap<u32, TrackTally>,
+    targets: BTreeMap<usize, TargetTally>,
+    per_scan: VecDeque<ScanScore>,
+}
+
+impl Evaluator {
+    pub fn new(config: EvaluationConfig) -> Self {
+        Self {
+            config,
+            scans: 0,
+            ospa_sum: 0.0,
+            gospa_sum: 0.0,
+            false_tracks: 0,
+            missed_targets: 0,
+            tracks: BTreeMap::new(),
+            targets: BTreeMap::new(),
+            per_scan: VecDeque::new(),
+        }
+    }
+
+    /// Score one scan
+    pub fn add_scan(&mut self, scan: &EvaluationScan) -> ScanScore {
+        let (c, p) = (self.config.cutoff_m, self.config.order);
+        let tracks: Vec<&TrackEstimate> = scan
+            .tracks
+            .iter()
+            .filter(|t| !matches!(t.status, TrackStatus::Tentative | TrackStatus::Deleted))
+            .filter(|t| t.state.len() >= 4)
+            .collect();
+        let (m, n) = (scan.truth.len(), tracks.len());
+        let distance = Array2::from_shape_fn((m, n), |(i, j)| {
+            let (truth, track) = (&scan.truth[i], tracks[j]);
+            (track.state[0] - truth.x_m).hypot(track.state[1] - truth.y_m)
+        });
+        let cost = distance.mapv(|d| d.min(c).powf(p));
+        let pairs = assign(&cost);
+
+        let assigned_cost: f64 = pairs.iter().map(|&(i, j)| cost[[i, j]]).sum();
+        let unassigned_cost = c.powf(p) * m.abs_diff(n) as f64;
+        let ospa_m = match m.max(n) {
+            0 => 0.0,
+            larger => ((assigned_cost + unassigned_cost) / larger as f64).powf(1.0 / p),
+        };
+        let gospa_m = (assigned_cost + 0.5 * unassigned_cost).powf(1.0 / p);
+
+        let following: HashMap<usize, usize> = pairs
+            .iter()
+            .filter(|&&(i, j)| distance[[i, j]] < c)
+            .copied()
+            .collect();
+        for (i, truth) in scan.truth.iter().enumerate() {
+            let tally = self.targets.entry(truth.id).or_default();
+            tally.scans += 1;
+            let Some(&j) = following.get(&i) else {
+                tally.covered_last_scan = false;
+                continue;
+            };
+            let track = tracks[j];
+            tally.covered += 1;
+            match tally.last_track {
+                Some(last) if last != track.id => tally.id_switches += 1,
+                Some(_) if !tally.covered_last_scan => tally.fragmentations += 1,
+                _ => {}
+            }
+            tally.last_track = Some(track.id);
+            tally.covered_last_scan = true;
+
+            let accuracy = self.tracks.entry(track.id).or_default();
+            accuracy.scans += 1;
+            accuracy.position_sq += distance[[i, j]].powi(2);
+            accuracy.velocity_sq +=
+                (track.state[2] - truth.vx_m_s).powi(2) + (track.state[3] - truth.vy_m_s).powi(2);
+            *accuracy.targets.entry(truth.id).or_default() += 1;
+        }
+
+        let score = ScanScore {
+            time_s: scan.time_s,
+            ospa_m,
+            gospa_m,
+            targets: m,
+            tracks: n,
+            missed_targets: m - following.len(),
+            false_tracks: n - following.len(),
+        };
+        self.scans += 1;
+        self.ospa_sum += ospa_m;
+        self.gospa_sum += gospa_m;
+        self.false_tracks += score.false_tracks as u64;
+        self.missed_targets += score.missed_targets as u64;
+        self.per_scan.push_back(score);
+        if self.per_scan.len() > MAX_SCAN_SCORES {
+            self.per_scan.pop_front();
+        }
+        score
+    }
+
+    pub fn report(&self) -> EvaluationReport {
+        let per_scan_mean = |sum: f64| {
+            if self.scans == 0 {
+                0.0
+            } else {
+                sum / self.scans as f64
+            }
+        };
+        let rmse = |sq: f64, count: u32| {
+            if count == 0 {
+                0.0
+            } else {
+                (sq / count as f64).sqrt()
+            }
+        };
+        let tracks: Vec<TrackAccuracy> = self
+            .tracks
+            .iter()
+            .map(|(&track_id, tally)| TrackAccuracy {
+                track_id,
+                target_id: tally
+                    .targets
+                    .iter()
+                    .max_by_key(|&(&id, &count)| (count, std::cmp::Reverse(id)))
+                    .map_or(0, |(&id, _)| id),
+                scans: tally.scans,
+                position_rmse_m: rmse(tally.position_sq, tally.scans),
+                velocity_rmse_m_s: rmse(tally.velocity_sq, tally.scans),
+            })
+            .collect();
+        let targets: Vec<TargetContinuity> = self
+            .targets
+            .iter()
+            .map(|(&target_id, tally)| TargetContinuity {
+                target_id,
+                scans: tally.scans,
+                coverage: tally.covered as f64 / tally.scans.max(1) as f64,
+                fragmentations: tally.fragmentations,
+                id_switches: tally.id_switches,
+            })
+            .collect();
+        let followed: u32 = self.tracks.values().map(|t| t.scans).sum();
+        let position_sq: f64 = self.tracks.values().map(|t| t.position_sq).sum();
+        let velocity_sq: f64 = self.tracks.values().map(|t| t.velocity_sq).sum();
+
+        EvaluationReport {
+            config: self.config,
+            scans: self.scans,
+            mean_ospa_m: per_scan_mean(self.ospa_sum),
+            mean_gospa_m: per_scan_mean(self.gospa_sum),
+            position_rmse_m: rmse(position_sq, followed),
+            velocity_rmse_m_s: rmse(velocity_sq, followed),
+            fragmentations: targets.iter().map(|t| t.fragmentations).sum(),
+            id_switches: targets.iter().map(|t| t.id_switches).sum(),
+            false_tracks_per_scan: per_scan_mean(self.false_tracks as f64),
+            missed_targets_per_scan: per_scan_mean(self.missed_targets as f64),
+            tracks,
+            targets,
+            per_scan: self.per_scan.iter().copied().collect(),
+        }
+    }
+}
+
+/// Largest total assignment cost, in [`assignment_work`] units, a recorded
+/// run may ask for
+pub const MAX_ASSIGNMENT_WORK: u64 = 1_000_000_000;
+
+/// Work of scoring a recorded run: the cubic assignment cost of each scan,
+/// `max(targets, tracks)^3`, summed over the scans
+pub fn assignment_work(scans: &[EvaluationScan]) -> u64 {
+    scans
+        .iter()
+        .map(|scan| (scan.truth.len().max(scan.tracks.len()) as u64).saturating_pow(3))
+        .fold(0, u64::saturating_add)
+}
+
+/// Score a recorded run
+pub fn evaluate(config: EvaluationConfig, scans: &[EvaluationScan]) -> EvaluationReport {
+    let mut evaluator = Evaluator::new(config);
+    for scan in scans {
+        evaluator.add_scan(scan);
+    }
+    evaluator.report()
+}
+
+/// Live evaluation of the latest tracking session.
+///
+/// As with the track history, starting a session discards the previous
+/// one's scores and a superseded session's late scans are ignored.
+#[derive(Debug, Default)]
+pub struct EvaluationStore {
+    session: u64,
+    evaluator: Option<Evaluator>,
+}
+
+impl EvaluationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start scoring a new session; returns its number
+    pub fn start_session(&mut self, config: EvaluationConfig) -> u64 {
+        self.session += 1;
+        self.evaluator = Some(Evaluator::new(config));
+        self.session
+    }
+
+    pub fn add_scan(&mut self, session: u64, scan: &EvaluationScan) {
+        if session != self.session {
+            return;
+        }
+        if let Some(evaluator) = &mut self.evaluator {
+            evaluator.add_scan(scan);
+        }
+    }
+
+    /// Report of the latest session; `None` before any session
+    pub fn report(&self) -> Option<EvaluationReport> {
+        self.evaluator.as_ref().map(Evaluator::report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::imm::ImmConfig;
+    use crate::tracking::{DetectionModel, Tracker, TrackerConfig};
+    use crate::trajectory::Maneuver;
+    use crate::types::{TargetPosition, Velocity3};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn truth(id: usize, x_m: f64, y_m: f64) -> TruthState {
+        TruthState {
+            id,
+            x_m,
+            y_m,
+            vx_m_s: 10.0,
+            vy_m_s: 0.0,
+        }
+    }
+
+    fn track(id: u32, x: f64, y: f64) -> TrackEstimate {
+        TrackEstimate {
+            id,
+            range_m: x.hypot(y),
+            azimuth_deg: x.atan2(y).to_degrees(),
+            elevation_deg: 0.0,
+            altitude_m: 0.0,
+            vel_m_s: 0.0,
+            velocity: Velocity3 {
+                vx_m_s: 10.0,
+                vy_m_s: 0.0,
+                vz_m_s: 0.0,
+            },
+            geodetic: None,
+            rcs: 0.1,
+            model: None,
+            mode_probabilities: Vec::new(),
+            state: vec![x, y, 10.0, 0.0],
+            covariance: Vec::new(),
+            hits: 5,
+            misses: 0,
+            status: TrackStatus::Confirmed,
+        }
+    }
+
+    #[test]
+    fn test_ospa_and_gospa_values() {
+        let mut evaluator = Evaluator::new(EvaluationConfig {
+            cutoff_m: 100.0,
+            order: 1.0,
+        });
+        // Two targets, one tracked 30 m off, plus a false track
+        let score = evaluator.add_scan(&EvaluationScan {
+            time_s: 0.0,
+            truth: vec![truth(0, 0.0, 1_000.0), truth(1, 5_000.0, 0.0)],
+            tracks: vec![
+                track(7, 30.0, 1_000.0),
+                track(8, -4_000.0, 0.0),
+                track(9, 0.0, 8_000.0),
+            ],
+        });
+        // OSPA: (30 + 100 + 100) / 3; GOSPA: 30 + 100/2 * (1 missed + 2 false)
+        assert!((score.ospa_m - 230.0 / 3.0).abs() < 1e-9, "{score:?}");
+        assert!((score.gospa_m - 180.0).abs() < 1e-9, "{score:?}");
+        assert_eq!((score.missed_targets, score.false_tracks), (1, 2));
+
+        // Nothing to score costs nothing; tracks alone cost the cut-off
+        let empty = evaluator.add_scan(&EvaluationScan {
+            time_s: 1.0,
+            truth: Vec::new(),
+            tracks: Vec::new(),
+        });
+        assert_eq!((empty.ospa_m, empty.gospa_m), (0.0, 0.0));
+        let only_tracks = evaluator.add_scan(&EvaluationScan {
+            time_s: 2.0,
+            truth: Vec::new(),
+            tracks: vec![track(7, 0.0, 0.0)],
+        });
+        assert_eq!(only_tracks.ospa_m, 100.0);
+        assert_eq!(only_tracks.gospa_m, 50.0);
+    }
+
+    #[test]
+    fn test_switches_fragmentation_and_rmse() {
+        let scans: Vec<EvaluationScan> = (0..6)
+            .map(|k| {
+                let tracks = match k {
+                    // Track 1 follows 10 m off, drops out, resumes, then track 2 takes over
+                    0 | 1 | 3 => vec![track(1, 10.0, 1_000.0)],
+                    2 => Vec::new(),
+                    _ => vec![track(2, 0.0, 1_000.0), track(3, 3_000.0, 3_000.0)],
+                };
+                EvaluationScan {
+                    time_s: k as f64,
+                    truth: vec![truth(0, 0.0, 1_000.0)],
+                    tracks,
+                }
+            })
+            .collect();
+        let report = evaluate(EvaluationConfig::default(), &scans);
+        assert_eq!(report.scans, 6);
+        assert_eq!(report.fragmentations, 1);
+        assert_eq!(report.id_switches, 1);
+        assert_eq!(report.targets[0].coverage, 5.0 / 6.0);
+        assert!((report.false_tracks_per_scan - 2.0 / 6.0).abs() < 1e-12);
+        assert!((report.missed_targets_per_scan - 1.0 / 6.0).abs() < 1e-12);
+        let ids: Vec<u32> = report.tracks.iter().map(|t| t.track_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert!((report.tracks[0].position_rmse_m - 10.0).abs() < 1e-9);
+        assert_eq!(report.tracks[1].position_rmse_m, 0.0);
+        assert!((report.position_rmse_m - (300.0f64 / 5.0).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_store_scores_latest_session() {
+        let mut store = EvaluationStore::new();
+        assert!(store.report().is_none());
+        let old = store.start_session(EvaluationConfig::default());
+        let scan = EvaluationScan {
+            time_s: 0.0,
+            truth: vec![truth(0, 0.0, 1_000.0)],
+            tracks: vec![track(0, 0.0, 1_000.0)],
+        };
+        store.add_scan(old, &scan);
+        let new = store.start_session(EvaluationConfig::default());
+        store.add_scan(old, &scan);
+        assert_eq!(store.report().unwrap().scans, 0);
+        store.add_scan(new, &scan);
+        assert_eq!(store.report().unwrap().scans, 1);
+    }
+
+    /// Regression scores of the default IMM tracker on four maneuvering
+    /// targets with missed detections and false alarms
+    #[test]
+    fn test_imm_tracker_scores() {
+        let template = |id, range_m, azimuth_deg| TargetPosition {
+            id,
+            range_m,
+            azimuth_deg,
+            rcs: 0.1,
+            ..TargetPosition::default()
+        };
+        let plan = vec![
+            Maneuver::Cruise { duration_s: 10.0 },
+            Maneuver::Turn {
+                duration_s: 10.0,
+                turn_rate_deg_s: 9.0,
+            },
+            Maneuver::Hover { duration_s: 5.0 },
+        ];
+        let mut targets = vec![
+            ManeuveringTarget::new(template(0, 2_000.0, 20.0), 90.0, 15.0, plan.clone()),
+            ManeuveringTarget::new(template(1, 3_000.0, 120.0), 300.0, 20.0, plan.clone()),
+            ManeuveringTarget::new(template(2, 4_000.0, 220.0), 45.0, 12.0, plan.clone()),
+            ManeuveringTarget::new(template(3, 5_000.0, 300.0), 180.0, 25.0, plan),
+        ];
+        let config = TrackerConfig {
+            imm: Some(ImmConfig::default()),
+            ..TrackerConfig::default()
+        };
+        let noise = config.measurement_noise;
+        let mut tracker = Tracker::new(config);
+        let detection = DetectionModel::default();
+        let mut rng = StdRng::seed_from_u64(22);
+        let mut evaluator = Evaluator::new(EvaluationConfig::default());
+        let dt = 0.5;
+        for step in 0..120 {
+            for target in &mut targets {
+                target.step(dt);
+            }
+            let reports: Vec<TargetPosition> = targets.iter().map(|t| t.report()).collect();
+            tracker.step(&detection.scan(&reports, &noise, &mut rng), dt);
+            evaluator.add_scan(&EvaluationScan {
+                time_s: step as f64 * dt,
+                truth: targets.iter().map(TruthState::from).collect(),
+                tracks: tracker.estimates(),
+            });
+        }
+
+        let report = evaluator.report();
+        // Baseline with some headroom: at the time of writing 29 m OSPA, 25 m and
+        // 14 m/s RMSE, and 12 ID switches as tracks lost in the turns restart
+        assert!(report.mean_ospa_m < 35.0, "{report:?}");
+        assert!(report.position_rmse_m < 30.0, "{report:?}");
+        assert!(report.velocity_rmse_m_s < 17.0, "{report:?}");
+        assert!(report.id_switches <= 15, "{:?}", report.targets);
+        assert!(
+            report.targets.iter().all(|t| t.coverage > 0.9),
+            "{:?}",
+            report.targets
+        );
+        assert!(report.false_tracks_per_scan < 0.2, "{report:?}");
+    }
+}
@@ -0,0 +1,116 @@
+use num_complex::Complex;
+use std::f64::consts::PI;
+
+/// In-place iterative radix-2 FFT. The buffer length must be a power of two.
+pub fn fft_in_place(buf: &mut [Complex<f64>]) {
+    transform(buf, false);
+}
+
+/// In-place inverse FFT, scaled by 1/N. The buffer length must be a power of two.
+pub fn ifft_in_place(buf: &mut [Complex<f64>]) {
+    transform(buf, true);
+    let scale = 1.0 / buf.len() as f64;
+    for x in buf.iter_mut() {
+        *x *= scale;
+    }
+}
+
+/// FFT of `input` zero-padded to `size` samples (must be a power of two, at least the input length)
+pub fn fft_padded(input: &[Complex<f64>], size: usize) -> Vec<Complex<f64>> {
+    let mut buf = vec![Complex::new(0.0, 0.0); size];
+    buf[..input.len()].copy_from_slice(input);
+    fft_in_place(&mut buf);
+    buf
+}
+
+/// Rotate a spectrum so that the zero-frequency bin sits at index `len / 2`
+pub fn fftshift<T: Clone>(data: &mut [T]) {
+    let half = data.len() / 2;
+    data.rotate_right(half);
+}
+
+fn transform(buf: &mut [Complex<f64>], inverse: bool) {
+    let n = buf.len();
+    if n <= 1 {
+        return;
+    }
+    assert!(n.is_power_of_two(), "FFT length must be a power of two");
+
+    // Bit-reversal permutation
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            buf.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let step = Complex::from_polar(1.0, sign * 2.0 * PI / len as f64);
+        for start in (0..n).step_by(len) {
+            let mut w = Complex::new(1.0, 0.0);
+            for k in 0..len / 2 {
+                let even = buf[start + k];
+                let odd = buf[start + k + len / 2] * w;
+                buf[start + k] = even + odd;
+                buf[start + k + len / 2] = even - odd;
+                w *= step;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_dft(input: &[Complex<f64>]) -> Vec<Complex<f64>> {
+        let n = input.len();
+        (0..n)
+            .map(|k| {
+                input
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| {
+                        x * Complex::from_polar(1.0, -2.0 * PI * (k * i) as f64 / n as f64)
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fft_matches_naive_dft() {
+        let input: Vec<Complex<f64>> = (0..16)
+            .map(|i| Complex::new((i as f64 * 0.7).sin(), (i as f64 * 1.3).cos()))
+            .collect();
+        let mut fast = input.clone();
+        fft_in_place(&mut fast);
+        for (a, b) in fast.iter().zip(naive_dft(&input)) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_inverse_round_trip() {
+        let input: Vec<Complex<f64>> = (0..32)
+            .map(|i| Complex::new(i as f64, -(i as f64)))
+            .collect();
+        let mut buf = input.clone();
+        fft_in_place(&mut buf);
+        ifft_in_place(&mut buf);
+        for (a, b) in buf.iter().zip(&input) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_fftshift_centres_zero_frequency() {
+        let mut data = vec![0, 1, 2, 3, -4, -3, -2, -1];
+        fftshift(&mut data);
+        assert_eq!(data, vec![-4, -3, -2, -1, 0, 1, 2, 3]);
+    }
+}
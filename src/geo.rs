@@ -0,0 +1,444 @@
+use crate::types::{TargetPosition, Velocity3};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// WGS84 semi-major axis in meters
+pub const WGS84_A_M: f64 = 6_378_137.0;
+/// WGS84 flattening
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// First eccentricity squared
+const WGS84_E2: f64 = WGS84_F * (2.0 - WGS84_F);
+
+/// Convergence of the iterative ECEF-to-geodetic latitude, in radians (~0.1 mm)
+const LATITUDE_TOLERANCE_RAD: f64 = 1e-11;
+const MAX_LATITUDE_ITERATIONS: usize = 10;
+
+/// Position on the WGS84 ellipsoid
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct GeodeticPosition {
+    /// Geodetic latitude in degrees, positive north
+    pub latitude_deg: f64,
+    /// Longitude in degrees, positive east
+    pub longitude_deg: f64,
+    /// Height above the WGS84 ellipsoid in meters
+    #[serde(default)]
+    pub height_m: f64,
+}
+
+/// Radar position and orientation.
+///
+/// The radar's local frame is level at the site: `z` is the ellipsoid
+/// normal, and `y` points along the radar's zero azimuth, which is
+/// `heading_deg` clockwise from true north. With a zero heading the local
+/// frame is the site's east-north-up frame.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RadarSite {
+    pub position: GeodeticPosition,
+    /// True bearing of the radar's zero azimuth in degrees
+    #[serde(default)]
+    pub heading_deg: f64,
+}
+
+/// Range, azimuth and elevation of a point in the radar's local frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolarPosition {
+    pub range_m: f64,
+    /// Clockwise from the radar's zero azimuth, in `[0, 360)`
+    pub azimuth_deg: f64,
+    pub elevation_deg: f64,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum GeoError {
+    #[error("expected latitude,longitude,height[,heading], got {0:?}")]
+    Format(String),
+    #[error("latitude {0} outside [-90, 90]")]
+    Latitude(f64),
+    #[error("longitude {0} outside [-180, 360)")]
+    Longitude(f64),
+}
+
+/// Earth-centered, earth-fixed coordinates `[x, y, z]` of a geodetic position
+pub fn geodetic_to_ecef(position: &GeodeticPosition) -> [f64; 3] {
+    let (lat, lon) = (
+        position.latitude_deg.to_radians(),
+        position.longitude_deg.to_radians(),
+    );
+    let n = prime_vertical_radius(lat);
+    [
+        (n + position.height_m) * lat.cos() * lon.cos(),
+        (n + position.height_m) * lat.cos() * lon.sin(),
+        (n * (1.0 - WGS84_E2) + position.height_m) * lat.sin(),
+    ]
+}
+
+/// Geodetic position of ECEF coordinates, by Bowring's iteration on latitude
+pub fn ecef_to_geodetic(ecef: [f64; 3]) -> GeodeticPosition {
+    let [x, y, z] = ecef;
+    let p = x.hypot(y);
+    let longitude = y.atan2(x);
+    let mut latitude = z.atan2(p * (1.0 - WGS84_E2));
+    for _ in 0..MAX_LATITUDE_ITERATIONS {
+        let n = prime_vertical_radius(latitude);
+        let next = (z + WGS84_E2 * n * latitude.sin()).atan2(p);
+        let converged = (next - latitude).abs() < LATITUDE_TOLERANCE_RAD;
+        latitude = next;
+        if converged {
+            break;
+        }
+    }
+    // Valid at the poles, unlike p / cos(latitude) - N
+    let height = p * latitude.cos() + z * latitude.sin()
+        - WGS84_A_M * (1.0 - WGS84_E2 * latitude.sin().powi(2)).sqrt();
+    GeodeticPosition {
+        latitude_deg: latitude.to_degrees(),
+        longitude_deg: longitude.to_degrees(),
+        height_m: height,
+    }
+}
+
+/// East-north-up coordinates of an ECEF point relative to `origin`
+pub fn ecef_to_enu(origin: &GeodeticPosition, ecef: [f64; 3]) -> [f64; 3] {
+    let reference = geodetic_to_ecef(origin);
+    let d = [
+        ecef[0] - reference[0],
+        ecef[1] - reference[1],
+        ecef[2] - reference[2],
+    ];
+    let r = enu_rotation(origin);
+    [0, 1, 2].map(|row| r[row][0] * d[0] + r[row][1] * d[1] + r[row][2] * d[2])
+}
+
+/// ECEF point at east-north-up coordinates relative to `origin`
+pub fn enu_to_ecef(origin: &GeodeticPosition, enu: [f64; 3]) -> [f64; 3] {
+    let reference = geodetic_to_ecef(origin);
+    let r = enu_rotation(origin);
+    // The rotation is orthonormal: its transpose maps ENU back to ECEF
+    [0, 1, 2]
+        .map(|col| reference[col] + r[0][col] * enu[0] + r[1][col] * enu[1] + r[2][col] * enu[2])
+}
+
+/// Range, azimuth and elevation of local `[x, y, z]` coordinates
+pub fn local_to_polar(local: [f64; 3]) -> PolarPosition {
+    let [x, y, z] = local;
+    let ground = x.hypot(y);
+    PolarPosition {
+        range_m: ground.hypot(z),
+        azimuth_deg: x.atan2(y).to_degrees().rem_euclid(360.0),
+        elevation_deg: z.atan2(ground).to_degrees(),
+    }
+}
+
+/// Local `[x, y, z]` coordinates of a range, azimuth and elevation
+pub fn polar_to_local(polar: &PolarPosition) -> [f64; 3] {
+    let (azimuth, elevation) = (
+        polar.azimuth_deg.to_radians(),
+        polar.elevation_deg.to_radians(),
+    );
+    let ground = polar.range_m * elevation.cos();
+    [
+        ground * azimuth.sin(),
+        ground * azimuth.cos(),
+        polar.range_m * elevation.sin(),
+    ]
+}
+
+impl RadarSite {
+    /// Check that the site's coordinates are finite and within the latitude
+    /// and longitude ranges
+    pub fn validate(&self) -> Result<(), GeoError> {
+        let GeodeticPosition {
+            latitude_deg,
+            longitude_deg,
+            height_m,
+        } = self.position;
+        if ![latitude_deg, longitude_deg, height_m, self.heading_deg]
+            .iter()
+            .all(|value| value.is_finite())
+        {
+            return Err(GeoError::Format(format!(
+                "{},{},{},{}",
+                latitude_deg, longitude_deg, height_m, self.heading_deg
+            )));
+        }
+        if !(-90.0..=90.0).contains(&latitude_deg) {
+            return Err(GeoError::Latitude(latitude_deg));
+        }
+        if !(-180.0..360.0).contains(&longitude_deg) {
+            return Err(GeoError::Longitude(longitude_deg));
+        }
+        Ok(())
+    }
+
+    /// Rotate a local position or velocity into the site's east-north-up frame
+    pub fn local_to_enu(&self, local: [f64; 3]) -> [f64; 3] {
+        let (sin, cos) = self.heading_deg.to_radians().sin_cos();
+        [
+            local[0] * cos + local[1] * sin,
+            -local[0] * sin + local[1] * cos,
+            local[2],
+        ]
+    }
+
+    /// Rotate an east-north-up position or velocity into the local frame
+    pub fn enu_to_local(&self, enu: [f64; 3]) -> [f64; 3] {
+        let (sin, cos) = self.heading_deg.to_radians().sin_cos();
+        [
+            enu[0] * cos - enu[1] * sin,
+            enu[0] * sin + enu[1] * cos,
+            enu[2],
+        ]
+    }
+
+    /// Geodetic position of local `[x, y, z]` coordinates
+    pub fn local_to_geodetic(&self, local: [f64; 3]) -> GeodeticPosition {
+        ecef_to_geodetic(enu_to_ecef(&self.position, self.local_to_enu(local)))
+    }
+
+    /// Local `[x, y, z]` coordinates of a geodetic position
+    pub fn geodetic_to_local(&self, position: &GeodeticPosition) -> [f64; 3] {
+        self.enu_to_local(ecef_to_enu(&self.position, geodetic_to_ecef(position)))
+    }
+
+    /// Geodetic position of a target reported in radar coordinates
+    pub fn locate(&self, target: &TargetPosition) -> GeodeticPosition {
+        self.local_to_geodetic(target.position_m())
+    }
+
+    /// Fill a target's radar coordinates from its `geodetic` position.
+    ///
+    /// The target's velocity, if any, is taken as east-north-up and rotated
+    /// into the radar frame, and its radial velocity recomputed from it.
+    /// Targets without a geodetic position are left unchanged.
+    pub fn resolve(&self, target: &mut TargetPosition) {
+        let Some(position) = target.geodetic else {
+            return;
+        };
+        let local = self.geodetic_to_local(&position);
+        let polar = local_to_polar(local);
+        target.range_m = polar.range_m;
+        target.azimuth_deg = polar.azimuth_deg;
+        target.elevation_deg = polar.elevation_deg;
+        target.altitude_m = Some(local[2]);
+        if let Some(velocity) = target.velocity {
+            let [vx_m_s, vy_m_s, vz_m_s] =
+                self.enu_to_local([velocity.vx_m_s, velocity.vy_m_s, velocity.vz_m_s]);
+            let velocity = Velocity3 {
+                vx_m_s,
+                vy_m_s,
+                vz_m_s,
+            };
+            target.vel_m_s = velocity.radial_m_s(local);
+            target.velocity = Some(velocity);
+        }
+    }
+}
+
+impl FromStr for RadarSite {
+    type Err = GeoError;
+
+    /// Parse `latitude,longitude,height[,heading]` in degrees and meters
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values: Vec<f64> = s
+            .split(',')
+            .map(|value| value.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| GeoError::Format(s.to_string()))?;
+        let (latitude_deg, longitude_deg, height_m, heading_deg) = match values[..] {
+            [lat, lon, height] => (lat, lon, height, 0.0),
+            [lat, lon, height, heading] => (lat, lon, height, heading),
+            _ => return Err(GeoError::Format(s.to_string())),
+        };
+        if !values.iter().all(|value| value.is_finite()) {
+            return Err(GeoError::Format(s.to_string()));
+        }
+        let site = Self {
+            position: GeodeticPosition {
+                latitude_deg,
+                longitude_deg,
+                height_m,
+            },
+            heading_deg,
+        };
+        site.validate()?;
+        Ok(site)
+    }
+}
+
+fn prime_vertical_radius(latitude: f64) -> f64 {
+    WGS84_A_M / (1.0 - WGS84_E2 * latitude.sin().powi(2)).sqrt()
+}
+
+/// Rows are the east, north and up unit vectors in ECEF
+fn enu_rotation(origin: &GeodeticPosition) -> [[f64; 3]; 3] {
+    let (sin_lat, cos_lat) = origin.latitude_deg.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = origin.longitude_deg.to_radians().sin_cos();
+    [
+        [-sin_lon, cos_lon, 0.0],
+        [-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat],
+        [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(heading_deg: f64) -> RadarSite {
+        RadarSite {
+            position: GeodeticPosition {
+                latitude_deg: 52.0,
+                longitude_deg: 4.5,
+                height_m: 30.0,
+            },
+            heading_deg,
+        }
+    }
+
+    #[test]
+    fn test_ecef_known_points() {
+        let equator = geodetic_to_ecef(&GeodeticPosition {
+            latitude_deg: 0.0,
+            longitude_deg: 0.0,
+            height_m: 0.0,
+        });
+        assert!((equator[0] - WGS84_A_M).abs() < 1e-6);
+        assert!(equator[1].abs() < 1e-6 && equator[2].abs() < 1e-6);
+
+        // Semi-minor axis b = a(1 - f) = 6356752.314 m
+        let pole = geodetic_to_ecef(&GeodeticPosition {
+            latitude_deg: 90.0,
+            longitude_deg: 0.0,
+            height_m: 100.0,
+        });
+        assert!((pole[2] - 6_356_852.314).abs() < 1e-3);
+        let back = ecef_to_geodetic(pole);
+        assert!((back.latitude_deg - 90.0).abs() < 1e-9);
+        assert!((back.height_m - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_geodetic_ecef_round_trip() {
+        for (latitude_deg, longitude_deg, height_m) in [
+            (52.0, 4.5, 30.0),
+            (-33.9, 151.2, 1_200.0),
+            (78.2, -15.6, -40.0),
+            (0.1, 179.9, 10_000.0),
+        ] {
+            let position = GeodeticPosition {
+                latitude_deg,
+                longitude_deg,
+                height_m,
+            };
+            let back = ecef_to_geodetic(geodetic_to_ecef(&position));
+            assert!((back.latitude_deg - latitude_deg).abs() < 1e-9);
+            assert!((back.longitude_deg - longitude_deg).abs() < 1e-9);
+            assert!((back.height_m - height_m).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_enu_follows_the_earth_curvature() {
+        let origin = site(0.0).position;
+        // A point 10 km due north on the site's tangent plane is about
+        // d²/2R = 7.9 m above the ellipsoid
+        let enu = [0.0, 10_000.0, 0.0];
+        let position = ecef_to_geodetic(enu_to_ecef(&origin, enu));
+        assert!(position.latitude_deg > origin.latitude_deg);
+        assert!((position.longitude_deg - origin.longitude_deg).abs() < 1e-9);
+        assert!(
+            (position.height_m - 37.8).abs() < 0.2,
+            "{}",
+            position.height_m
+        );
+
+        let back = ecef_to_enu(&origin, geodetic_to_ecef(&position));
+        for (a, b) in back.iter().zip(enu) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_heading_rotates_radar_azimuth() {
+        // A radar whose zero azimuth points east sees a target due north at 270°
+        let site = site(90.0);
+        let north = ecef_to_geodetic(enu_to_ecef(&site.position, [0.0, 5_000.0, 500.0]));
+        let polar = local_to_polar(site.geodetic_to_local(&north));
+        assert!((polar.azimuth_deg - 270.0).abs() < 1e-6);
+        assert!((polar.range_m - 5_000f64.hypot(500.0)).abs() < 1e-4);
+
+        let local = polar_to_local(&polar);
+        let back = site.local_to_geodetic(local);
+        assert!((back.latitude_deg - north.latitude_deg).abs() < 1e-10);
+        assert!((back.height_m - north.height_m).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_resolve_target_from_geodetic() {
+        let site = site(30.0);
+        let truth = TargetPosition {
+            id: 7,
+            range_m: 4_000.0,
+            azimuth_deg: 200.0,
+            elevation_deg: 3.0,
+            altitude_m: Some(4_000.0 * 3f64.to_radians().sin()),
+            rcs: 0.5,
+            ..TargetPosition::default()
+        };
+        // An inbound target flying toward the radar along the ground: east-north-up
+        // velocity opposite its true bearing of 230°
+        let bearing = 230f64.to_radians();
+        let mut target = TargetPosition {
+            range_m: 0.0,
+            azimuth_deg: 0.0,
+            elevation_deg: 0.0,
+            altitude_m: None,
+            geodetic: Some(site.locate(&truth)),
+            velocity: Some(Velocity3 {
+                vx_m_s: -10.0 * bearing.sin(),
+                vy_m_s: -10.0 * bearing.cos(),
+                vz_m_s: 0.0,
+            }),
+            ..truth.clone()
+        };
+        site.resolve(&mut target);
+        assert!((target.range_m - truth.range_m).abs() < 1e-4);
+        assert!((target.azimuth_deg - truth.azimuth_deg).abs() < 1e-6);
+        assert!((target.elevation_deg - truth.elevation_deg).abs() < 1e-6);
+        assert!((target.altitude_m() - truth.altitude_m()).abs() < 1e-4);
+        assert!((target.vel_m_s + 10.0 * 3f64.to_radians().cos()).abs() < 1e-6);
+        let heading = target.velocity.unwrap().heading_deg().unwrap();
+        assert!((heading - 20.0).abs() < 1e-6, "{heading}");
+    }
+
+    #[test]
+    fn test_parse_site() {
+        let site: RadarSite = "52.0, 4.5, 30".parse().unwrap();
+        assert_eq!(site.position.latitude_deg, 52.0);
+        assert_eq!(site.heading_deg, 0.0);
+        let turned: RadarSite = "52.0,4.5,30,45".parse().unwrap();
+        assert_eq!(turned.heading_deg, 45.0);
+
+        assert!(matches!(
+            "52.0,4.5".parse::<RadarSite>(),
+            Err(GeoError::Format(_))
+        ));
+        assert!(matches!(
+            "95,4.5,0".parse::<RadarSite>(),
+            Err(GeoError::Latitude(_))
+        ));
+        assert!(matches!(
+            "52,north,0".parse::<RadarSite>(),
+            Err(GeoError::Format(_))
+        ));
+
+        // Sites that arrive as JSON get the same checks
+        let site: RadarSite = serde_json::from_str(
+            r#"{"position": {"latitude_deg": 52.0, "longitude_deg": 400.0, "height_m": 30.0}}"#,
+        )
+        .unwrap();
+        assert_eq!(site.validate(), Err(GeoError::Longitude(400.0)));
+    }
+}
@@ -1,23 +1,49 @@
 use crate::analysis::analyze_drone;
+use crate::evaluation::{
+    assignment_work, evaluate, EvaluationConfig, EvaluationReport, EvaluationScan, EvaluationStore,
+    TruthState, MAX_ASSIGNMENT_WORK,
+};
+use crate::geo::RadarSite;
+use crate::horizon::{HorizonProfile, RadarHorizon};
+use crate::imm::ImmConfig;
+use crate::link_budget::compute_link_budget;
+use crate::mht::MhtTracker;
+use crate::micro_doppler::RotorModel;
 use crate::observability::AppMetrics;
-use crate::types::{DroneAnalysis, TargetPosition, WebSocketMessage};
+use crate::performance::{evaluate_roc, monte_carlo_work, sweep_ranges, MAX_MONTE_CARLO_WORK};
+use crate::scenario::SceneDetector;
+use crate::signal::FmcwParams;
+use crate::smoothing::{SmoothedTrack, SmootherModel, TrackHistoryStore};
+use crate::swerling::SwerlingModel;
+use crate::tracking::{DetectionModel, MultiTargetTracker, TrackStatus, Tracker, TrackerConfig};
+use crate::trajectory::{Maneuver, ManeuveringTarget};
+use crate::types::{
+    AmbiguityRequest, AmbiguityResponse, AnalyzeRequest, DroneAnalysis, EvaluationRequest,
+    HorizonQuery, LinkBudgetRequest, LinkBudgetResponse, RocRequest, RocResponse, TargetPosition,
+    WebSocketMessage,
+};
+use crate::waveform::{ambiguity_work, evaluate_ambiguity, MAX_AMBIGUITY_WORK};
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        Path, Query, State,
     },
     http::StatusCode,
     response::Json,
 };
+use futures_util::stream::SplitSink;
 use futures_util::{SinkExt, StreamExt};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Instant;
+use tokio::sync::{Mutex, RwLock};
 use tracing::{error, info, warn};
 
 #[utoipa::path(
     post,
     path = "/api/analyze",
-    request_body = TargetPosition,
+    request_body = AnalyzeRequest,
     responses(
         (status = 200, description = "Analysis result", body = DroneAnalysis),
         (status = 400, description = "Bad request")
@@ -26,10 +52,42 @@ use tracing::{error, info, warn};
 )]
 pub async fn analyze_handler(
     State(metrics): State<Arc<AppMetrics>>,
-    axum::extract::Json(target): axum::extract::Json<TargetPosition>,
+    State(site): State<Option<RadarSite>>,
+    axum::extract::Json(request): axum::extract::Json<AnalyzeRequest>,
 ) -> Result<Json<DroneAnalysis>, StatusCode> {
     metrics.increment_requests().await;
 
+    // A target needs a range and azimuth or a geodetic position to be placed at all
+    let Some(mut target) = request.into_target() else {
+        metrics.increment_failure().await;
+        warn!("Target without range and azimuth or a geodetic position");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    // A geodetic target is placed relative to the configured radar site
+    if let Some(position) = target.geodetic {
+        let Some(site) = site else {
+            metrics.increment_failure().await;
+            warn!(
+                "Geodetic target {} without a configured radar site",
+                target.id
+            );
+            return Err(StatusCode::BAD_REQUEST);
+        };
+        let valid = position.latitude_deg.abs() <= 90.0
+            && position.longitude_deg.is_finite()
+            && position.height_m.is_finite();
+        if !valid {
+            metrics.increment_failure().await;
+            warn!(
+                "Invalid geodetic position: lat={}, lon={}",
+                position.latitude_deg, position.longitude_deg
+            );
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        site.resolve(&mut target);
+    }
+
     // Validate input
     if target.range_m < 0.0 || target.range_m > 100_000.0 {
         metrics.increment_failure().await;
@@ -52,7 +110,19 @@ pub async fn analyze_handler(
 
     // Run analysis on a separate thread (blocking task)
     // This ensures it doesn't block the async runtime
-    let analysis_result = tokio::task::spawn_blocking(move || analyze_drone(&target)).await;
+    let analysis_result = tokio::task::spawn_blocking(move || {
+        let mut analysis = analyze_drone(&target);
+        if let Some(site) = site {
+            analysis.geodetic = Some(site.locate(&target));
+            // Headings are relative to the radar's zero azimuth until turned to true north
+            analysis.trajectory_analysis.heading_deg = analysis
+                .trajectory_analysis
+                .heading_deg
+                .map(|heading| (heading + site.heading_deg).rem_euclid(360.0));
+        }
+        analysis
+    })
+    .await;
 
     match analysis_result {
         Ok(analysis) => {
@@ -72,14 +142,447 @@ pub async fn analyze_handler(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/link-budget",
+    request_body = LinkBudgetRequest,
+    responses(
+        (status = 200, description = "Received power and SNR per target", body = LinkBudgetResponse),
+        (status = 400, description = "Bad request")
+    ),
+    tag = "Radar Performance"
+)]
+pub async fn link_budget_handler(
+    State(metrics): State<Arc<AppMetrics>>,
+    axum::extract::Json(request): axum::extract::Json<LinkBudgetRequest>,
+) -> Result<Json<LinkBudgetResponse>, StatusCode> {
+    metrics.increment_requests().await;
+
+    // Validate input
+    let radar = &request.radar;
+    if let Err(e) = radar.validate() {
+        metrics.increment_failure().await;
+        warn!("{}", e);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if let Some(target) = request
+        .targets
+        .iter()
+        .find(|t| t.range_m <= 0.0 || t.range_m > 100_000.0 || t.rcs < 0.0)
+    {
+        metrics.increment_failure().await;
+        warn!(
+            "Invalid target: id={}, range={}m, rcs={}",
+            target.id, target.range_m, target.rcs
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let response = compute_link_budget(radar, &request.targets);
+    metrics.increment_success().await;
+    info!(
+        "Link budget computed for {} targets",
+        response.targets.len()
+    );
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/performance/roc",
+    request_body = RocRequest,
+    responses(
+        (status = 200, description = "Pd vs range and ROC curves", body = RocResponse),
+        (status = 400, description = "Bad request")
+    ),
+    tag = "Radar Performance"
+)]
+pub async fn roc_handler(
+    State(metrics): State<Arc<AppMetrics>>,
+    axum::extract::Json(request): axum::extract::Json<RocRequest>,
+) -> Result<Json<RocResponse>, StatusCode> {
+    metrics.increment_requests().await;
+
+    // Validate input
+    if let Err(e) = request.radar.validate() {
+        metrics.increment_failure().await;
+        warn!("{}", e);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if request.pfa <= 0.0 || request.pfa >= 1.0 || request.rcs <= 0.0 {
+        metrics.increment_failure().await;
+        warn!("Invalid pfa={} or rcs={}", request.pfa, request.rcs);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Bound the number of ranges before building the sweep
+    let sweep = &request.range_sweep;
+    if sweep.num_points == 0 || sweep.num_points > 500 || request.roc_ranges_m.len() > 20 {
+        metrics.increment_failure().await;
+        warn!(
+            "Invalid range sweep: {} points, {} ROC ranges",
+            sweep.num_points,
+            request.roc_ranges_m.len()
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let ranges_valid = sweep_ranges(sweep)
+        .iter()
+        .chain(&request.roc_ranges_m)
+        .all(|&r| r > 0.0 && r <= 100_000.0);
+    if !ranges_valid {
+        metrics.increment_failure().await;
+        warn!(
+            "Invalid range sweep: {}..{}m, {} points",
+            sweep.start_m, sweep.stop_m, sweep.num_points
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if request.num_pulses == 0
+        || request.num_pulses > 100
+        || request.monte_carlo_trials == 0
+        || request.monte_carlo_trials > 100_000
+    {
+        metrics.increment_failure().await;
+        warn!(
+            "Invalid integration: pulses={}, trials={}",
+            request.num_pulses, request.monte_carlo_trials
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Each limit alone still allows billions of draws, so bound their product
+    let work = monte_carlo_work(&request);
+    if work > MAX_MONTE_CARLO_WORK {
+        metrics.increment_failure().await;
+        warn!(
+            "Monte Carlo workload too large: {} (limit {})",
+            work, MAX_MONTE_CARLO_WORK
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Monte Carlo runs are CPU bound, so keep them off the async runtime
+    let result = tokio::task::spawn_blocking(move || evaluate_roc(&request)).await;
+
+    match result {
+        Ok(response) => {
+            metrics.increment_success().await;
+            info!(
+                "ROC computed: {} ranges, {} curves",
+                response.pd_vs_range.len(),
+                response.roc_curves.len()
+            );
+            Ok(Json(response))
+        }
+        Err(e) => {
+            metrics.increment_failure().await;
+            error!("ROC task error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/waveforms/ambiguity",
+    request_body = AmbiguityRequest,
+    responses(
+        (status = 200, description = "Ambiguity surface and resolution figures", body = AmbiguityResponse),
+        (status = 400, description = "Bad request")
+    ),
+    tag = "Radar Performance"
+)]
+pub async fn ambiguity_handler(
+    State(metrics): State<Arc<AppMetrics>>,
+    axum::extract::Json(request): axum::extract::Json<AmbiguityRequest>,
+) -> Result<Json<AmbiguityResponse>, StatusCode> {
+    metrics.increment_requests().await;
+
+    // Validate input: bound the waveform length and grid so a request stays cheap.
+    // Every chip takes at least one sample, which also caps the number of chips.
+    let num_samples = request.waveform.num_samples(request.sample_rate_hz);
+    if request.sample_rate_hz <= 0.0 || num_samples.is_none_or(|n| n > 4096) {
+        metrics.increment_failure().await;
+        warn!(
+            "Invalid waveform sampling: fs={}Hz, {:?} samples",
+            request.sample_rate_hz, num_samples
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if request.num_delay_points == 0
+        || request.num_delay_points > 512
+        || request.num_doppler_points == 0
+        || request.num_doppler_points > 512
+    {
+        metrics.increment_failure().await;
+        warn!(
+            "Invalid ambiguity grid: {} delays x {} Dopplers",
+            request.num_delay_points, request.num_doppler_points
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Each limit alone still allows about a billion multiply-adds, so bound their product
+    let work = ambiguity_work(&request);
+    if work.is_none_or(|work| work > MAX_AMBIGUITY_WORK) {
+        metrics.increment_failure().await;
+        warn!(
+            "Ambiguity workload too large: {:?} (limit {})",
+            work, MAX_AMBIGUITY_WORK
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let result = tokio::task::spawn_blocking(move || evaluate_ambiguity(&request)).await;
+
+    match result {
+        Ok(Ok(response)) => {
+            metrics.increment_success().await;
+            info!(
+                "Ambiguity computed: {}x{} grid, PSL {:.1} dB",
+                response.dopplers_hz.len(),
+                response.delays_s.len(),
+                response.peak_sidelobe_db
+            );
+            Ok(Json(response))
+        }
+        Ok(Err(e)) => {
+            metrics.increment_failure().await;
+            warn!("Invalid waveform: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(e) => {
+            metrics.increment_failure().await;
+            error!("Ambiguity task error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/coverage/horizon",
+    params(HorizonQuery),
+    responses(
+        (status = 200, description = "Radar horizon and lowest visible height along the ground", body = HorizonProfile),
+        (status = 400, description = "Bad request")
+    ),
+    tag = "Radar Performance"
+)]
+pub async fn horizon_handler(
+    State(metrics): State<Arc<AppMetrics>>,
+    Query(query): Query<HorizonQuery>,
+) -> Result<Json<HorizonProfile>, StatusCode> {
+    metrics.increment_requests().await;
+
+    // Validate input
+    let horizon = RadarHorizon {
+        antenna_height_m: query.antenna_height_m,
+        k_factor: query.k_factor,
+    };
+    let query_valid =
+        horizon.validate().is_ok() && (0.0..=100_000.0).contains(&query.target_altitude_m);
+    if !query_valid {
+        metrics.increment_failure().await;
+        warn!(
+            "Invalid horizon query: antenna={}m, target={}m, k={}",
+            query.antenna_height_m, query.target_altitude_m, query.k_factor
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let max_range_m = query
+        .max_range_m
+        .unwrap_or_else(|| 1.5 * horizon.radar_horizon_m(query.target_altitude_m));
+    let range_valid = max_range_m > 0.0 && max_range_m <= 2_000_000.0;
+    if !range_valid || query.points < 2 || query.points > 10_000 {
+        metrics.increment_failure().await;
+        warn!(
+            "Invalid horizon profile: max_range={}m, points={}",
+            max_range_m, query.points
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let profile = horizon.profile(query.target_altitude_m, max_range_m, query.points);
+    metrics.increment_success().await;
+    info!(
+        "Horizon profile: {:.1}km for a {}m antenna and {}m target",
+        profile.radar_horizon_m / 1_000.0,
+        query.antenna_height_m,
+        query.target_altitude_m
+    );
+    Ok(Json(profile))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tracks/{id}/smoothed",
+    params(("id" = u32, Path, description = "Track id in the latest tracking session")),
+    responses(
+        (status = 200, description = "Fixed-interval smoothed trajectory of the track", body = SmoothedTrack),
+        (status = 404, description = "No history recorded for this track")
+    ),
+    tag = "Tracking"
+)]
+pub async fn smoothed_track_handler(
+    State(metrics): State<Arc<AppMetrics>>,
+    State(track_history): State<Arc<RwLock<TrackHistoryStore>>>,
+    Path(id): Path<u32>,
+) -> Result<Json<SmoothedTrack>, StatusCode> {
+    metrics.increment_requests().await;
+
+    let Some(history) = track_history.read().await.history(id).cloned() else {
+        metrics.increment_failure().await;
+        warn!("No history for track {}", id);
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let result = tokio::task::spawn_blocking(move || history.smooth(id)).await;
+
+    match result {
+        Ok(track) => {
+            metrics.increment_success().await;
+            info!("Track {} smoothed over {} scans", id, track.points.len());
+            Ok(Json(track))
+        }
+        Err(e) => {
+            metrics.increment_failure().await;
+            error!("Smoothing task error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/evaluation",
+    responses(
+        (status = 200, description = "Scores of the latest tracking session against ground truth", body = EvaluationReport),
+        (status = 404, description = "No tracking session yet")
+    ),
+    tag = "Tracking"
+)]
+pub async fn evaluation_handler(
+    State(metrics): State<Arc<AppMetrics>>,
+    State(evaluation): State<Arc<RwLock<EvaluationStore>>>,
+) -> Result<Json<EvaluationReport>, StatusCode> {
+    metrics.increment_requests().await;
+
+    let Some(report) = evaluation.read().await.report() else {
+        metrics.increment_failure().await;
+        warn!("No tracking session to evaluate");
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    metrics.increment_success().await;
+    info!(
+        "Evaluated {} scans: mean OSPA {:.1}m, {} ID switches",
+        report.scans, report.mean_ospa_m, report.id_switches
+    );
+    Ok(Json(report))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/evaluation",
+    request_body = EvaluationRequest,
+    responses(
+        (status = 200, description = "Scores of the recorded run against its ground truth", body = EvaluationReport),
+        (status = 400, description = "Bad request")
+    ),
+    tag = "Tracking"
+)]
+pub async fn evaluate_run_handler(
+    State(metrics): State<Arc<AppMetrics>>,
+    axum::extract::Json(request): axum::extract::Json<EvaluationRequest>,
+) -> Result<Json<EvaluationReport>, StatusCode> {
+    metrics.increment_requests().await;
+
+    // Validate input
+    let config = request.config;
+    let config_valid = config.cutoff_m > 0.0
+        && config.cutoff_m.is_finite()
+        && config.order >= 1.0
+        && config.order.is_finite();
+    if !config_valid {
+        metrics.increment_failure().await;
+        warn!(
+            "Invalid evaluation config: cutoff={}m, order={}",
+            config.cutoff_m, config.order
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // One cubic assignment per scan: bound the total, not just each dimension
+    let oversized = request
+        .scans
+        .iter()
+        .any(|scan| scan.truth.len() > 500 || scan.tracks.len() > 500);
+    let work = assignment_work(&request.scans);
+    if request.scans.len() > 100_000 || oversized || work > MAX_ASSIGNMENT_WORK {
+        metrics.increment_failure().await;
+        warn!(
+            "Recorded run too large: {} scans, assignment work {}",
+            request.scans.len(),
+            work
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let result = tokio::task::spawn_blocking(move || evaluate(config, &request.scans)).await;
+
+    match result {
+        Ok(report) => {
+            metrics.increment_success().await;
+            info!(
+                "Evaluated recorded run of {} scans: mean OSPA {:.1}m",
+                report.scans, report.mean_ospa_m
+            );
+            Ok(Json(report))
+        }
+        Err(e) => {
+            metrics.increment_failure().await;
+            error!("Evaluation task error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Send an error message over the socket, ignoring a closed connection
+async fn send_error(sender: &Mutex<SplitSink<WebSocket, Message>>, message: &str) {
+    let error_msg = WebSocketMessage::Error {
+        message: message.to_string(),
+    };
+    if let Ok(json) = serde_json::to_string(&error_msg) {
+        let mut s = sender.lock().await;
+        let _ = s.send(Message::Text(json.into())).await;
+    }
+}
+
 pub async fn websocket_handler(
     State(metrics): State<Arc<AppMetrics>>,
+    State(track_history): State<Arc<RwLock<TrackHistoryStore>>>,
+    State(evaluation): State<Arc<RwLock<EvaluationStore>>>,
+    State(site): State<Option<RadarSite>>,
     ws: WebSocketUpgrade,
 ) -> axum::response::Response {
-    ws.on_upgrade(move |socket| handle_socket(socket, metrics))
+    ws.on_upgrade(move |socket| handle_socket(socket, metrics, track_history, evaluation, site))
 }
 
-async fn handle_socket(socket: WebSocket, metrics: Arc<AppMetrics>) {
+async fn handle_socket(
+    socket: WebSocket,
+    metrics: Arc<AppMetrics>,
+    track_history: Arc<RwLock<TrackHistoryStore>>,
+    evaluation: Arc<RwLock<EvaluationStore>>,
+    site: Option<RadarSite>,
+) {
     metrics.increment_websocket_connection().await;
     info!("WebSocket connection established");
     let (sender, mut receiver) = socket.split();
@@ -91,73 +594,268 @@ async fn handle_socket(socket: WebSocket, metrics: Arc<AppMetrics>) {
         match msg {
             Message::Text(text) => {
                 match serde_json::from_str::<WebSocketMessage>(&text) {
-                    Ok(WebSocketMessage::StartTracking) => {
+                    Ok(WebSocketMessage::StartTracking {
+                        scenario,
+                        mht,
+                        tracker,
+                        site: session_site,
+                        geodetic,
+                    }) => {
                         // Stop existing tracking if any
                         if let Some(handle) = tracking_handle.take() {
                             handle.abort();
                         }
 
+                        // A session's own site is checked like the configured one
+                        if let Err(e) = session_site.map_or(Ok(()), |site| site.validate()) {
+                            warn!("Rejected tracking session: {}", e);
+                            send_error(&sender_arc, &format!("Invalid radar site: {}", e)).await;
+                            continue;
+                        }
+
+                        // Geodetic tracks need a site, from the session or the configuration
+                        let geodetic_site = match (geodetic, session_site.or(site)) {
+                            (false, _) => None,
+                            (true, Some(site)) => Some(site),
+                            (true, None) => {
+                                send_error(&sender_arc, "Geodetic tracks need a radar site").await;
+                                continue;
+                            }
+                        };
+
+                        // Reject settings the session could not simulate every update in time
+                        let checked = scenario
+                            .as_ref()
+                            .map_or(Ok(()), |scenario| scenario.validate(&FmcwParams::default()))
+                            .and_then(|()| match (&mht, &tracker) {
+                                (Some(mht), _) => mht.validate(),
+                                (None, Some(tracker)) => tracker.validate(),
+                                (None, None) => Ok(()),
+                            });
+                        if let Err(e) = checked {
+                            warn!("Rejected tracking session: {}", e);
+                            send_error(&sender_arc, &e.to_string()).await;
+                            continue;
+                        }
+
                         // Start new tracking with default drone targets
                         let sender_clone = sender_arc.clone();
-                        // Default drone targets for demonstration
-                        let mut target_positions: Vec<TargetPosition> = vec![
-                            TargetPosition {
-                                id: 0,
-                                range_m: 10_000.0,
-                                azimuth_deg: 0.0,
-                                vel_m_s: 30.0,
-                                rcs: 1.0,
-                            },
-                            TargetPosition {
-                                id: 1,
-                                range_m: 15_000.0,
-                                azimuth_deg: 120.0,
-                                vel_m_s: -50.0,
-                                rcs: 0.6,
-                            },
-                            TargetPosition {
-                                id: 2,
-                                range_m: 8_000.0,
-                                azimuth_deg: 240.0,
-                                vel_m_s: 25.0,
-                                rcs: 0.8,
-                            },
+                        // Default drone targets for demonstration, each flying a repeating
+                        // pattern of cruise legs, turns and hovers at its own altitude
+                        let template =
+                            |id, range_m, azimuth_deg, altitude_m, rcs, swerling, rotor| {
+                                TargetPosition {
+                                    id,
+                                    range_m,
+                                    azimuth_deg,
+                                    altitude_m: Some(altitude_m),
+                                    rcs,
+                                    swerling,
+                                    rotor,
+                                    ..TargetPosition::default()
+                                }
+                            };
+                        let mut targets = vec![
+                            // Square patrol, pausing at every corner
+                            ManeuveringTarget::new(
+                                template(
+                                    0,
+                                    10_000.0,
+                                    0.0,
+                                    120.0,
+                                    1.0,
+                                    SwerlingModel::One,
+                                    Some(RotorModel::quadcopter()),
+                                ),
+                                90.0,
+                                15.0,
+                                vec![
+                                    Maneuver::Cruise { duration_s: 20.0 },
+                                    Maneuver::Turn {
+                                        duration_s: 10.0,
+                                        turn_rate_deg_s: 9.0,
+                                    },
+                                    Maneuver::Hover { duration_s: 15.0 },
+                                ],
+                            ),
+                            // Racetrack with left turns
+                            ManeuveringTarget::new(
+                                template(
+                                    1,
+                                    15_000.0,
+                                    120.0,
+                                    400.0,
+                                    0.6,
+                                    SwerlingModel::Three,
+                                    None,
+                                ),
+                                300.0,
+                                40.0,
+                                vec![
+                                    Maneuver::Cruise { duration_s: 40.0 },
+                                    Maneuver::Turn {
+                                        duration_s: 20.0,
+                                        turn_rate_deg_s: -9.0,
+                                    },
+                                ],
+                            ),
+                            // Hover, dash and turn back
+                            ManeuveringTarget::new(
+                                template(
+                                    2,
+                                    8_000.0,
+                                    240.0,
+                                    60.0,
+                                    0.8,
+                                    SwerlingModel::One,
+                                    Some(RotorModel::quadcopter()),
+                                ),
+                                30.0,
+                                10.0,
+                                vec![
+                                    Maneuver::Hover { duration_s: 20.0 },
+                                    Maneuver::Cruise { duration_s: 20.0 },
+                                    Maneuver::Turn {
+                                        duration_s: 6.0,
+                                        turn_rate_deg_s: 30.0,
+                                    },
+                                ],
+                            ),
                         ];
 
+                        // The IMM tracker unless the session asks for another one
+                        let mut tracker: Box<dyn MultiTargetTracker> = match mht {
+                            Some(config) => Box::new(MhtTracker::new(*config)),
+                            None => Box::new(Tracker::new(tracker.map_or_else(
+                                || TrackerConfig {
+                                    imm: Some(ImmConfig::default()),
+                                    ..TrackerConfig::default()
+                                },
+                                |config| *config,
+                            ))),
+                        };
+                        let noise = tracker.tracker_config().measurement_noise;
+                        // Record the session's tracks for smoothing after the fact
+                        let smoother = SmootherModel::for_tracker(tracker.tracker_config());
+                        let session = track_history.write().await.start_session();
+                        let track_history = track_history.clone();
+                        // Score the published tracks against the simulated truth
+                        let evaluation_session = evaluation
+                            .write()
+                            .await
+                            .start_session(EvaluationConfig::default());
+                        let evaluation = evaluation.clone();
+                        // The radar and horizon of the scenario, or of a default one, decide
+                        // which targets the tracker sees, as they do for the detector
+                        let sensor = scenario.clone().unwrap_or_default();
+                        let detection = DetectionModel {
+                            radar: Some(sensor.radar),
+                            pfa: sensor.cfar.pfa,
+                            horizon: sensor.horizon,
+                            ..DetectionModel::default()
+                        };
+                        // Each dwell's scene is detected with its own random draws
+                        let mut scene = scenario.map(|scenario| {
+                            (SceneDetector::new(*scenario), StdRng::from_entropy())
+                        });
+                        let metrics = metrics.clone();
+
                         let handle = tokio::spawn(async move {
+                            let mut rng = StdRng::from_entropy();
+                            let mut elapsed_s = 0.0;
                             let mut interval =
                                 tokio::time::interval(tokio::time::Duration::from_millis(100));
                             loop {
                                 interval.tick().await;
 
-                                // Update target positions
-                                for pos in &mut target_positions {
-                                    // Update range based on velocity (positive velocity = moving away, negative = moving toward)
-                                    pos.range_m += pos.vel_m_s * 0.1; // 0.1 seconds per update
-
-                                    // Update azimuth (circular motion for demo)
-                                    pos.azimuth_deg = (pos.azimuth_deg + 0.5) % 360.0;
-
-                                    // Keep range within reasonable bounds
-                                    if pos.range_m < 1000.0 {
-                                        pos.range_m = 1000.0;
-                                        pos.vel_m_s = -pos.vel_m_s; // Bounce back
-                                    } else if pos.range_m > 50_000.0 {
-                                        pos.range_m = 50_000.0;
-                                        pos.vel_m_s = -pos.vel_m_s; // Bounce back
+                                // Update target positions, 0.1 seconds per update
+                                for target in &mut targets {
+                                    target.step(0.1);
+                                }
+                                elapsed_s += 0.1;
+                                let target_positions: Vec<TargetPosition> =
+                                    targets.iter().map(ManeuveringTarget::report).collect();
+
+                                // Each update is one dwell: draw the observed RCS from each target's Swerling model,
+                                // then detect and measure the targets with misses, noise and false alarms
+                                let observed: Vec<TargetPosition> = target_positions
+                                    .iter()
+                                    .map(|target| TargetPosition {
+                                        rcs: target.swerling.sample_rcs(target.rcs, &mut rng),
+                                        ..target.clone()
+                                    })
+                                    .collect();
+                                let measurements = detection.scan(&observed, &noise, &mut rng);
+                                let started = Instant::now();
+                                tracker.step(&measurements, 0.1);
+                                let mut tracks = tracker.estimates();
+                                metrics.record_tracker_scan(started.elapsed()).await;
+                                // Tentative tracks may be false alarms: only confirmed, coasting
+                                // and just-deleted tracks reach the operator
+                                tracks.retain(|track| track.status != TrackStatus::Tentative);
+                                if let Some(site) = geodetic_site {
+                                    for track in &mut tracks {
+                                        track.georeference(&site);
+                                    }
+                                }
+                                track_history
+                                    .write()
+                                    .await
+                                    .record(session, elapsed_s, smoother, &tracks);
+                                evaluation.write().await.add_scan(
+                                    evaluation_session,
+                                    &EvaluationScan {
+                                        time_s: elapsed_s,
+                                        truth: targets.iter().map(TruthState::from).collect(),
+                                        tracks: tracks.clone(),
+                                    },
+                                );
+
+                                // With a scenario, also run the detector over a detection-level scene.
+                                // Scene synthesis, CFAR and track-before-detect are CPU bound, so they
+                                // run on the blocking pool rather than on this runtime worker.
+                                let mut detections = None;
+                                if let Some((mut detector, mut scene_rng)) = scene.take() {
+                                    let targets = target_positions.clone();
+                                    match tokio::task::spawn_blocking(move || {
+                                        let detected = detector.detect(
+                                            &FmcwParams::default(),
+                                            &targets,
+                                            &mut scene_rng,
+                                        );
+                                        (detector, scene_rng, detected)
+                                    })
+                                    .await
+                                    {
+                                        Ok((detector, scene_rng, detected)) => {
+                                            scene = Some((detector, scene_rng));
+                                            detections = Some(detected);
+                                        }
+                                        Err(e) => {
+                                            error!("Scene detection task error: {}", e);
+                                            break;
+                                        }
                                     }
                                 }
 
-                                // Send updated positions
-                                let msg = WebSocketMessage::Targets {
-                                    targets: target_positions.clone(),
-                                };
-                                if let Ok(json) = serde_json::to_string(&msg) {
-                                    let mut s = sender_clone.lock().await;
-                                    if s.send(Message::Text(json.into())).await.is_err() {
-                                        break; // Connection closed
+                                // Send track estimates rather than ground truth
+                                let mut messages = vec![WebSocketMessage::Tracks { tracks }];
+                                if let Some(detections) = detections {
+                                    messages.push(WebSocketMessage::Detections { detections });
+                                }
+                                let mut closed = false;
+                                for msg in messages {
+                                    if let Ok(json) = serde_json::to_string(&msg) {
+                                        let mut s = sender_clone.lock().await;
+                                        if s.send(Message::Text(json.into())).await.is_err() {
+                                            closed = true; // Connection closed
+                                            break;
+                                        }
                                     }
                                 }
+                                if closed {
+                                    break;
+                                }
                             }
                         });
                         tracking_handle = Some(handle);
@@ -166,13 +864,7 @@ async fn handle_socket(socket: WebSocket, metrics: Arc<AppMetrics>) {
                         // Other message types can be handled here
                     }
                     Err(e) => {
-                        let error_msg = WebSocketMessage::Error {
-                            message: format!("Invalid message format: {}", e),
-                        };
-                        if let Ok(json) = serde_json::to_string(&error_msg) {
-                            let mut s = sender_arc.lock().await;
-                            let _ = s.send(Message::Text(json.into())).await;
-                        }
+                        send_error(&sender_arc, &format!("Invalid message format: {}", e)).await;
                     }
                 }
             }
@@ -192,19 +884,20 @@ async fn handle_socket(socket: WebSocket, metrics: Arc<AppMetrics>) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::routes::create_router;
+    use crate::routes::{create_router, AppState};
     use axum::body::Body;
     use axum::http::{Request, StatusCode};
     use http_body_util::BodyExt;
     use tower::ServiceExt;
 
     use crate::observability::AppMetrics;
+    use crate::types::RadarConfig;
     use std::sync::Arc;
 
     #[tokio::test]
     async fn test_analyze_handler_success() {
         let metrics = Arc::new(AppMetrics::new());
-        let app = create_router(metrics);
+        let app = create_router(AppState::new(metrics));
 
         let target = TargetPosition {
             id: 1,
@@ -212,6 +905,7 @@ mod tests {
             azimuth_deg: 45.0,
             vel_m_s: 30.0,
             rcs: 0.8,
+            ..TargetPosition::default()
         };
 
         let response = app
@@ -241,7 +935,7 @@ mod tests {
     #[tokio::test]
     async fn test_analyze_handler_invalid_json() {
         let metrics = Arc::new(AppMetrics::new());
-        let app = create_router(metrics);
+        let app = create_router(AppState::new(metrics));
 
         let response = app
             .oneshot(
@@ -262,7 +956,7 @@ mod tests {
     #[tokio::test]
     async fn test_analyze_handler_missing_body() {
         let metrics = Arc::new(AppMetrics::new());
-        let app = create_router(metrics);
+        let app = create_router(AppState::new(metrics));
 
         let response = app
             .oneshot(
@@ -281,28 +975,15 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_analyze_handler_different_targets() {
+    async fn test_analyze_handler_missing_range() {
         let metrics = Arc::new(AppMetrics::new());
-        let app = create_router(metrics);
-
-        let targets = vec![
-            TargetPosition {
-                id: 1,
-                range_m: 3_000.0,
-                azimuth_deg: 0.0,
-                vel_m_s: 50.0,
-                rcs: 0.9,
-            },
-            TargetPosition {
-                id: 2,
-                range_m: 20_000.0,
-                azimuth_deg: 180.0,
-                vel_m_s: 15.0,
-                rcs: 0.5,
-            },
-        ];
+        let app = create_router(AppState::new(metrics));
 
-        for target in targets {
+        for body in [
+            r#"{"id": 1, "azimuth_deg": 45.0, "rcs": 0.8}"#,
+            r#"{"id": 1, "range_m": null, "azimuth_deg": 45.0, "rcs": 0.8}"#,
+            r#"{"id": 1, "rcs": 0.8}"#,
+        ] {
             let response = app
                 .clone()
                 .oneshot(
@@ -310,19 +991,658 @@ mod tests {
                         .uri("/api/analyze")
                         .method("POST")
                         .header("content-type", "application/json")
-                        .body(Body::from(serde_json::to_string(&target).unwrap()))
+                        .body(Body::from(body))
                         .unwrap(),
                 )
                 .await
                 .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{body}");
+        }
+    }
 
-            assert_eq!(response.status(), StatusCode::OK);
+    #[tokio::test]
+    async fn test_link_budget_handler_success() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
 
-            let body = response.into_body().collect().await.unwrap().to_bytes();
-            let analysis: DroneAnalysis = serde_json::from_slice(&body).unwrap();
+        let request = LinkBudgetRequest {
+            radar: RadarConfig::default(),
+            targets: vec![
+                TargetPosition {
+                    id: 1,
+                    range_m: 2_000.0,
+                    vel_m_s: 10.0,
+                    rcs: 1.0,
+                    ..TargetPosition::default()
+                },
+                TargetPosition {
+                    id: 2,
+                    range_m: 20_000.0,
+                    azimuth_deg: 90.0,
+                    vel_m_s: 10.0,
+                    rcs: 0.01,
+                    ..TargetPosition::default()
+                },
+            ],
+        };
 
-            assert_eq!(analysis.drone_id, target.id);
-            assert!(!analysis.threat_level.is_empty());
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/link-budget")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_string(&request).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let budget: LinkBudgetResponse = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(budget.targets.len(), 2);
+        assert_eq!(budget.targets[0].target_id, 1);
+        assert!(budget.targets[0].snr_db > budget.targets[1].snr_db);
+    }
+
+    #[tokio::test]
+    async fn test_link_budget_handler_requires_target_range() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
+
+        // Only analyzed targets may be placed geodetically instead
+        let body = serde_json::json!({
+            "radar": RadarConfig::default(),
+            "targets": [{ "id": 1, "azimuth_deg": 45.0, "rcs": 1.0 }]
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/link-budget")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert!(response.status().is_client_error());
+    }
+
+    #[tokio::test]
+    async fn test_link_budget_handler_invalid_radar() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
+
+        let request = LinkBudgetRequest {
+            radar: RadarConfig {
+                wavelength_m: 0.0,
+                ..RadarConfig::default()
+            },
+            targets: vec![],
+        };
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/link-budget")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_string(&request).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_roc_handler_success() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
+
+        let body = serde_json::json!({
+            "radar": RadarConfig::default(),
+            "rcs": 0.1,
+            "swerling": "swerling_1",
+            "range_sweep": { "start_m": 1000.0, "stop_m": 30000.0, "num_points": 10 },
+            "pfa": 1e-6,
+            "roc_ranges_m": [10000.0, 20000.0],
+            "monte_carlo_trials": 500
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/performance/roc")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let roc: RocResponse = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(roc.pd_vs_range.len(), 10);
+        assert_eq!(roc.roc_curves.len(), 2);
+        assert!(roc.pd_vs_range[0].pd_analytic > roc.pd_vs_range[9].pd_analytic);
+    }
+
+    #[tokio::test]
+    async fn test_roc_handler_invalid_pfa() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
+
+        let body = serde_json::json!({
+            "radar": RadarConfig::default(),
+            "rcs": 0.1,
+            "range_sweep": { "start_m": 1000.0, "stop_m": 30000.0, "num_points": 10 },
+            "pfa": 1.5
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/performance/roc")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_roc_handler_rejects_zero_trials() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
+
+        // No trials would report a Monte Carlo Pd of zero that was never measured
+        let body = serde_json::json!({
+            "radar": RadarConfig::default(),
+            "rcs": 0.1,
+            "range_sweep": { "start_m": 1000.0, "stop_m": 30000.0, "num_points": 10 },
+            "pfa": 1e-6,
+            "monte_carlo_trials": 0
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/performance/roc")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_roc_handler_rejects_oversized_work() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
+
+        let request = |num_points: u64, trials: usize, pulses: usize| {
+            let body = serde_json::json!({
+                "radar": RadarConfig::default(),
+                "rcs": 0.1,
+                "range_sweep": { "start_m": 1000.0, "stop_m": 30000.0, "num_points": num_points },
+                "pfa": 1e-6,
+                "roc_ranges_m": vec![10_000.0; 20],
+                "num_pulses": pulses,
+                "monte_carlo_trials": trials
+            });
+            Request::builder()
+                .uri("/api/performance/roc")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        // A huge sweep is rejected before it is allocated
+        let response = app
+            .clone()
+            .oneshot(request(10_000_000_000, 10, 1))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Every limit met on its own, but billions of draws together
+        let response = app
+            .clone()
+            .oneshot(request(500, 100_000, 100))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // One set of draws per ROC range serves every false-alarm probability
+        // of its curve: 4.2M draws, not 15 sets per range or 60M
+        let response = app.oneshot(request(1, 100_000, 2)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_ambiguity_handler_success() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
+
+        let body = serde_json::json!({
+            "waveform": { "type": "barker", "length": 13, "chip_width_s": 1e-6 },
+            "sample_rate_hz": 4e6,
+            "num_delay_points": 21,
+            "num_doppler_points": 11
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/waveforms/ambiguity")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let surface: AmbiguityResponse = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(surface.magnitude.len(), 11);
+        assert_eq!(surface.magnitude[0].len(), 21);
+        assert!((surface.peak_sidelobe_db + 22.3).abs() < 0.1);
+    }
+
+    #[tokio::test]
+    async fn test_ambiguity_handler_rejects_invalid_waveform() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
+
+        let body = serde_json::json!({
+            "waveform": { "type": "barker", "length": 6, "chip_width_s": 1e-6 },
+            "sample_rate_hz": 4e6
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/waveforms/ambiguity")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_ambiguity_handler_rejects_oversized_waveforms() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
+
+        // Short in duration, but one sample per chip makes them huge
+        for waveform in [
+            serde_json::json!({ "type": "p4", "length": 100_000_000u64, "chip_width_s": 1e-14 }),
+            serde_json::json!({ "type": "frank", "order": 1u64 << 33, "chip_width_s": 1e-14 }),
+            serde_json::json!({
+                "type": "stepped_frequency",
+                "num_steps": 100_000_000u64,
+                "step_hz": 1e3,
+                "subpulse_width_s": 1e-14
+            }),
+        ] {
+            let body = serde_json::json!({ "waveform": waveform, "sample_rate_hz": 1e6 });
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri("/api/waveforms/ambiguity")
+                        .method("POST")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{body}");
+        }
+
+        // Waveform and grid each within their limits, but a billion multiply-adds together
+        let body = serde_json::json!({
+            "waveform": { "type": "p4", "length": 4096, "chip_width_s": 1e-6 },
+            "sample_rate_hz": 1e6,
+            "num_delay_points": 512,
+            "num_doppler_points": 512
+        });
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/waveforms/ambiguity")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_handler_different_targets() {
+        let metrics = Arc::new(AppMetrics::new());
+        let app = create_router(AppState::new(metrics));
+
+        let targets = vec![
+            TargetPosition {
+                id: 1,
+                range_m: 3_000.0,
+                vel_m_s: 50.0,
+                rcs: 0.9,
+                ..TargetPosition::default()
+            },
+            TargetPosition {
+                id: 2,
+                range_m: 20_000.0,
+                azimuth_deg: 180.0,
+                vel_m_s: 15.0,
+                rcs: 0.5,
+                ..TargetPosition::default()
+            },
+        ];
+
+        for target in targets {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri("/api/analyze")
+                        .method("POST")
+                        .header("content-type", "application/json")
+                        .body(Body::from(serde_json::to_string(&target).unwrap()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let analysis: DroneAnalysis = serde_json::from_slice(&body).unwrap();
+
+            assert_eq!(analysis.drone_id, target.id);
+            assert!(!analysis.threat_level.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_handler_geodetic_target() {
+        let site: RadarSite = "52.0,4.5,30,90".parse().unwrap();
+        // About 2.2 km north of the radar and 200 m above it, flying east
+        let body = r#"{"id": 4, "rcs": 0.05,
+            "geodetic": {"latitude_deg": 52.02, "longitude_deg": 4.5, "height_m": 230.0},
+            "velocity": {"vx_m_s": 12.0, "vy_m_s": 0.0, "vz_m_s": 0.0}}"#;
+        let request = || {
+            Request::builder()
+                .uri("/api/analyze")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        // Without a site the target cannot be placed
+        let app = create_router(AppState::new(Arc::new(AppMetrics::new())));
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let app = create_router(AppState::new(Arc::new(AppMetrics::new())).with_site(site));
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let analysis: DroneAnalysis = serde_json::from_slice(&body).unwrap();
+
+        let geodetic = analysis.geodetic.unwrap();
+        assert!((geodetic.latitude_deg - 52.02).abs() < 1e-9);
+        assert!((geodetic.height_m - 230.0).abs() < 1e-3);
+        // Curvature lowers the target by under half a meter relative to the radar
+        let trajectory = &analysis.trajectory_analysis;
+        assert!((trajectory.altitude_estimate_m - 199.7).abs() < 0.2);
+        assert!((trajectory.speed_m_s - 12.0).abs() < 1e-6);
+        // East in true bearings, although the radar's zero azimuth points east
+        assert!((trajectory.heading_deg.unwrap() - 90.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_smoothed_track_handler() {
+        use crate::tracking::measure;
+
+        let state = AppState::new(Arc::new(AppMetrics::new()));
+        let config = TrackerConfig::default();
+        let model = SmootherModel::for_tracker(&config);
+        let noise = config.measurement_noise;
+        let mut tracker = Tracker::new(config);
+        let mut rng = StdRng::seed_from_u64(21);
+        let target = TargetPosition {
+            range_m: 5_000.0,
+            azimuth_deg: 30.0,
+            rcs: 0.5,
+            ..TargetPosition::default()
+        };
+        {
+            let mut history = state.track_history.write().await;
+            let session = history.start_session();
+            for step in 0..20 {
+                tracker.step(&[measure(&target, &noise, &mut rng)], 0.1);
+                history.record(session, step as f64 * 0.1, model, &tracker.estimates());
+            }
+        }
+        let app = create_router(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/tracks/0/smoothed")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let track: SmoothedTrack = serde_json::from_slice(&body).unwrap();
+        assert_eq!(track.id, 0);
+        assert_eq!(track.points.len(), 20);
+        assert!((track.points[10].range_m - 5_000.0).abs() < 20.0);
+        assert!(track.points[10].position_std_m < track.points[10].filtered_position_std_m);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/tracks/7/smoothed")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_handlers() {
+        use crate::tracking::measure;
+
+        let state = AppState::new(Arc::new(AppMetrics::new()));
+        let config = TrackerConfig::default();
+        let noise = config.measurement_noise;
+        let mut tracker = Tracker::new(config);
+        let mut rng = StdRng::seed_from_u64(22);
+        let mut target = ManeuveringTarget::new(
+            TargetPosition {
+                range_m: 3_000.0,
+                azimuth_deg: 60.0,
+                rcs: 0.5,
+                ..TargetPosition::default()
+            },
+            180.0,
+            15.0,
+            Vec::new(),
+        );
+        let scans: Vec<EvaluationScan> = (0..30)
+            .map(|step| {
+                target.step(0.1);
+                tracker.step(&[measure(&target.report(), &noise, &mut rng)], 0.1);
+                EvaluationScan {
+                    time_s: step as f64 * 0.1,
+                    truth: vec![TruthState::from(&target)],
+                    tracks: tracker.estimates(),
+                }
+            })
+            .collect();
+        let app = create_router(state.clone());
+
+        let get = || {
+            Request::builder()
+                .uri("/api/evaluation")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(get()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let post = |config: EvaluationConfig| {
+            let request = EvaluationRequest {
+                config,
+                scans: scans.clone(),
+            };
+            Request::builder()
+                .uri("/api/evaluation")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_string(&request).unwrap()))
+                .unwrap()
+        };
+        let response = app
+            .clone()
+            .oneshot(post(EvaluationConfig::default()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let report: EvaluationReport = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report.scans, 30);
+        assert_eq!(report.id_switches, 0);
+        assert_eq!(report.tracks.len(), 1);
+        assert!(report.position_rmse_m < 50.0, "{report:?}");
+
+        let response = app
+            .clone()
+            .oneshot(post(EvaluationConfig {
+                cutoff_m: 0.0,
+                order: 2.0,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // 500 targets in each of 10 scans: 1.25e9 assignment steps
+        let crowded = EvaluationScan {
+            time_s: 0.0,
+            truth: vec![scans[0].truth[0]; 500],
+            tracks: Vec::new(),
+        };
+        let request = EvaluationRequest {
+            config: EvaluationConfig::default(),
+            scans: vec![crowded; 10],
+        };
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/evaluation")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_string(&request).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        {
+            let mut evaluation = state.evaluation.write().await;
+            let session = evaluation.start_session(EvaluationConfig::default());
+            for scan in &scans[..10] {
+                evaluation.add_scan(session, scan);
+            }
+        }
+        let response = app.oneshot(get()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let report: EvaluationReport = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report.scans, 10);
+    }
+
+    #[tokio::test]
+    async fn test_horizon_handler() {
+        let app = create_router(AppState::new(Arc::new(AppMetrics::new())));
+        let get = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .method("GET")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(get(
+                "/api/coverage/horizon?antenna_height_m=10&target_altitude_m=100&points=11",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let profile: HorizonProfile = serde_json::from_slice(&body).unwrap();
+        // 4/3 earth: 4.12 km × (√10 + √100)
+        assert!((profile.radar_horizon_m - 54_250.0).abs() < 100.0);
+        assert_eq!(profile.points.len(), 11);
+        assert!(profile.points[..7].iter().all(|p| p.visible));
+        assert!(profile.points[7..].iter().all(|p| !p.visible));
+
+        for uri in [
+            "/api/coverage/horizon?antenna_height_m=-1&target_altitude_m=100",
+            "/api/coverage/horizon?antenna_height_m=10&target_altitude_m=100&k_factor=0",
+            "/api/coverage/horizon?antenna_height_m=10&target_altitude_m=100&points=1",
+            "/api/coverage/horizon?antenna_height_m=10",
+        ] {
+            let response = app.clone().oneshot(get(uri)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{uri}");
         }
     }
 }
@@ -0,0 +1,281 @@
+use crate::constants::EARTH_RADIUS_M;
+use crate::error::{AppError, AppResult};
+use crate::types::TargetPosition;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::FRAC_PI_2;
+use utoipa::ToSchema;
+
+/// Effective earth radius factor of a standard atmosphere
+pub const STANDARD_K_FACTOR: f64 = 4.0 / 3.0;
+
+fn default_k_factor() -> f64 {
+    STANDARD_K_FACTOR
+}
+
+/// Line of sight from a radar antenna over a smooth spherical earth.
+///
+/// Refraction bends rays back toward the ground; it is modelled by a
+/// straight-line earth of `k_factor` times the real radius, so that targets
+/// are hidden once the effective earth's bulge rises between them and the
+/// antenna. Heights are above the ground, distances along it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RadarHorizon {
+    /// Antenna height above the ground in meters
+    pub antenna_height_m: f64,
+    /// Effective earth radius factor: 4/3 in a standard atmosphere, 1 without refraction
+    #[serde(default = "default_k_factor")]
+    pub k_factor: f64,
+}
+
+impl Default for RadarHorizon {
+    /// Mast-mounted surveillance radar in a standard atmosphere
+    fn default() -> Self {
+        Self {
+            antenna_height_m: 10.0,
+            k_factor: STANDARD_K_FACTOR,
+        }
+    }
+}
+
+/// Horizon of the simulator's sensors unless a flat earth is asked for
+pub(crate) fn default_horizon() -> Option<RadarHorizon> {
+    Some(RadarHorizon::default())
+}
+
+/// One ground range of a horizon profile
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HorizonPoint {
+    pub ground_range_m: f64,
+    /// Lowest target height above the ground in line of sight
+    pub min_visible_height_m: f64,
+    /// Elevation of the profile's target above the antenna's horizontal
+    pub target_elevation_deg: f64,
+    /// Whether the profile's target is in line of sight
+    pub visible: bool,
+}
+
+/// Radar horizon of an antenna for a target at a fixed altitude, with the
+/// lowest visible height along the ground
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HorizonProfile {
+    pub antenna_height_m: f64,
+    pub target_altitude_m: f64,
+    pub k_factor: f64,
+    pub effective_earth_radius_m: f64,
+    /// Ground range to the antenna's own horizon, where targets on the ground disappear
+    pub antenna_horizon_m: f64,
+    /// Ground range beyond which the target is hidden
+    pub radar_horizon_m: f64,
+    pub points: Vec<HorizonPoint>,
+}
+
+impl RadarHorizon {
+    /// Reject an antenna below the ground or above 100 km, or a refraction
+    /// factor outside (0, 100]
+    pub fn validate(&self) -> AppResult<()> {
+        let valid = (0.0..=100_000.0).contains(&self.antenna_height_m)
+            && self.k_factor > 0.0
+            && self.k_factor <= 100.0;
+        if !valid {
+            return Err(AppError::InvalidInput(format!(
+                "invalid horizon: antenna={}m, k={}",
+                self.antenna_height_m, self.k_factor
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn effective_radius_m(&self) -> f64 {
+        self.k_factor * EARTH_RADIUS_M
+    }
+
+    /// Earth-central angle from a point `height_m` above the ground to its horizon
+    fn horizon_angle(&self, height_m: f64) -> f64 {
+        let radius = self.effective_radius_m();
+        (radius / (radius + height_m.max(0.0))).acos()
+    }
+
+    /// Ground range to the horizon of a point `height_m` above the ground
+    pub fn horizon_distance_m(&self, height_m: f64) -> f64 {
+        self.effective_radius_m() * self.horizon_angle(height_m)
+    }
+
+    /// Ground range at which a target `target_height_m` above the ground
+    /// drops below the radar horizon
+    pub fn radar_horizon_m(&self, target_height_m: f64) -> f64 {
+        self.horizon_distance_m(self.antenna_height_m) + self.horizon_distance_m(target_height_m)
+    }
+
+    /// Lowest height above the ground in line of sight at a ground range;
+    /// zero inside the antenna's own horizon
+    pub fn min_visible_height_m(&self, ground_range_m: f64) -> f64 {
+        let radius = self.effective_radius_m();
+        let beyond = ground_range_m / radius - self.horizon_angle(self.antenna_height_m);
+        if beyond <= 0.0 {
+            0.0
+        } else if beyond >= FRAC_PI_2 {
+            f64::INFINITY
+        } else {
+            radius / beyond.cos() - radius
+        }
+    }
+
+    /// Apparent elevation of a point `target_height_m` above the ground at a
+    /// ground range, in the effective earth's geometry
+    pub fn elevation_deg(&self, ground_range_m: f64, target_height_m: f64) -> f64 {
+        let radius = self.effective_radius_m();
+        let (sin, cos) = (ground_range_m / radius).sin_cos();
+        let up = (radius + target_height_m) * cos - (radius + self.antenna_height_m);
+        let across = (radius + target_height_m) * sin;
+        up.atan2(across).to_degrees()
+    }
+
+    /// Ground range and height above the ground of a target.
+    ///
+    /// The target's position is in the radar's level local frame, which is
+    /// tangent to the real earth at the antenna: the ground falls away
+    /// beneath it with range.
+    pub fn ground_geometry(&self, target: &TargetPosition) -> (f64, f64) {
+        let [x, y, z] = target.position_m();
+        let horizontal = x.hypot(y);
+        let vertical = EARTH_RADIUS_M + self.antenna_height_m + z;
+        (
+            EARTH_RADIUS_M * horizontal.atan2(vertical),
+            horizontal.hypot(vertical) - EARTH_RADIUS_M,
+        )
+    }
+
+    /// Whether a target is in line of sight of the antenna
+    pub fn is_visible(&self, target: &TargetPosition) -> bool {
+        let (ground_range_m, height_m) = self.ground_geometry(target);
+        height_m >= self.min_visible_height_m(ground_range_m)
+    }
+
+    /// Horizon profile for a target at `target_altitude_m` above the ground,
+    /// sampled at `points` ground ranges up to `max_range_m`
+    pub fn profile(
+        &self,
+        target_altitude_m: f64,
+        max_range_m: f64,
+        points: usize,
+    ) -> HorizonProfile {
+        let radar_horizon_m = self.radar_horizon_m(target_altitude_m);
+        let step = max_range_m / points.saturating_sub(1).max(1) as f64;
+        let points = (0..points)
+            .map(|i| {
+                let ground_range_m = i as f64 * step;
+                let min_visible_height_m = self.min_visible_height_m(ground_range_m);
+                HorizonPoint {
+                    ground_range_m,
+                    min_visible_height_m,
+                    target_elevation_deg: self.elevation_deg(ground_range_m, target_altitude_m),
+                    visible: target_altitude_m >= min_visible_height_m,
+                }
+            })
+            .collect();
+        HorizonProfile {
+            antenna_height_m: self.antenna_height_m,
+            target_altitude_m,
+            k_factor: self.k_factor,
+            effective_earth_radius_m: self.effective_radius_m(),
+            antenna_horizon_m: self.horizon_distance_m(self.antenna_height_m),
+            radar_horizon_m,
+            points,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Target at a ground range and height above the ground, in the local
+    /// frame of a radar `antenna_height_m` up
+    fn target(antenna_height_m: f64, ground_range_m: f64, height_m: f64) -> TargetPosition {
+        let (sin, cos) = (ground_range_m / EARTH_RADIUS_M).sin_cos();
+        let horizontal = (EARTH_RADIUS_M + height_m) * sin;
+        let altitude_m = (EARTH_RADIUS_M + height_m) * cos - EARTH_RADIUS_M - antenna_height_m;
+        TargetPosition {
+            range_m: horizontal.hypot(altitude_m),
+            azimuth_deg: 30.0,
+            elevation_deg: altitude_m.atan2(horizontal).to_degrees(),
+            altitude_m: Some(altitude_m),
+            rcs: 1.0,
+            ..TargetPosition::default()
+        }
+    }
+
+    #[test]
+    fn test_four_thirds_horizon_rule_of_thumb() {
+        // d ≈ 4.12 km × √h for h in meters
+        let horizon = RadarHorizon::default();
+        let antenna = horizon.horizon_distance_m(10.0);
+        assert!((antenna - 4_122.0 * 10f64.sqrt()).abs() < 20.0, "{antenna}");
+        let radar = horizon.radar_horizon_m(100.0);
+        assert!(
+            (radar - 4_122.0 * (10f64.sqrt() + 10.0)).abs() < 50.0,
+            "{radar}"
+        );
+
+        // Without refraction the horizon is closer
+        let geometric = RadarHorizon {
+            k_factor: 1.0,
+            ..horizon
+        };
+        assert!(geometric.radar_horizon_m(100.0) < radar);
+    }
+
+    #[test]
+    fn test_min_visible_height_matches_horizon() {
+        let horizon = RadarHorizon::default();
+        assert_eq!(horizon.min_visible_height_m(5_000.0), 0.0);
+        for height in [20.0, 150.0, 1_000.0] {
+            let range = horizon.radar_horizon_m(height);
+            assert!((horizon.min_visible_height_m(range) - height).abs() < 1e-3);
+            // A target at the horizon is seen grazing the bulge, below the antenna's horizontal
+            assert!(horizon.elevation_deg(range, height) < 0.0);
+        }
+        // Close in, the elevation is the flat-earth one
+        let elevation = horizon.elevation_deg(1_000.0, 110.0);
+        assert!((elevation - 100f64.atan2(1_000.0).to_degrees()).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_low_targets_hidden_beyond_horizon() {
+        let horizon = RadarHorizon::default();
+        // 50 m above the ground at 60 km sits 240 m below the radar's
+        // horizontal, and the effective earth hides everything under 130 m there
+        let low = target(10.0, 60_000.0, 50.0);
+        assert!(
+            (low.altitude_m() + 242.0).abs() < 2.0,
+            "{}",
+            low.altitude_m()
+        );
+        let (ground, height) = horizon.ground_geometry(&low);
+        assert!((ground - 60_000.0).abs() < 1e-3);
+        assert!((height - 50.0).abs() < 1e-3);
+        assert!(!horizon.is_visible(&low));
+        assert!(horizon.is_visible(&target(10.0, 60_000.0, 200.0)));
+        // The same low target is visible close in
+        assert!(horizon.is_visible(&target(10.0, 10_000.0, 50.0)));
+        assert!((horizon.min_visible_height_m(60_000.0) - 130.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_profile_visibility_ends_at_radar_horizon() {
+        let horizon = RadarHorizon::default();
+        let profile = horizon.profile(100.0, 80_000.0, 81);
+        assert_eq!(profile.points.len(), 81);
+        assert_eq!(profile.points[80].ground_range_m, 80_000.0);
+        for point in &profile.points {
+            assert_eq!(
+                point.visible,
+                point.ground_range_m <= profile.radar_horizon_m
+            );
+        }
+        assert!(profile.points.windows(2).all(|w| {
+            w[1].min_visible_height_m >= w[0].min_visible_height_m
+                && w[1].target_elevation_deg < w[0].target_elevation_deg
+        }));
+    }
+}
@@ -0,0 +1,549 @@
+use crate::linalg::invert;
+use crate::tracking::{
+    pda_update, polar_innovation, ExtendedKalmanFilter, MeasurementNoise, MotionModel,
+    PolarMeasurement,
+};
+use ndarray::{Array1, Array2};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Length of the state shared by all IMM modes
+const STATE_LEN: usize = 5;
+
+/// Motion mode of the IMM estimator.
+///
+/// Every mode works on the state `[x, y, vx, vy, turn_rate]`, with positions
+/// as in [`MotionModel`] and the turn rate in rad/s, positive clockwise like
+/// azimuth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImmMode {
+    /// Straight flight at constant velocity
+    ConstantVelocity,
+    /// Constant-speed turn at the estimated turn rate
+    CoordinatedTurn,
+    /// Hovering: velocity decays towards zero
+    NearStationary,
+}
+
+impl ImmMode {
+    pub const ALL: [ImmMode; 3] = [
+        ImmMode::ConstantVelocity,
+        ImmMode::CoordinatedTurn,
+        ImmMode::NearStationary,
+    ];
+
+    /// Flight behavior the mode stands for
+    pub fn behavior(&self) -> FlightBehavior {
+        match self {
+            ImmMode::ConstantVelocity => FlightBehavior::Cruising,
+            ImmMode::CoordinatedTurn => FlightBehavior::Maneuvering,
+            ImmMode::NearStationary => FlightBehavior::Hovering,
+        }
+    }
+
+    /// Predicted state and transition Jacobian over `dt` seconds
+    fn propagate(
+        &self,
+        state: &Array1<f64>,
+        dt: f64,
+        config: &ImmConfig,
+    ) -> (Array1<f64>, Array2<f64>) {
+        let mut f = Array2::eye(STATE_LEN);
+        match self {
+            ImmMode::ConstantVelocity => {
+                f[[0, 2]] = dt;
+                f[[1, 3]] = dt;
+            }
+            ImmMode::NearStationary => {
+                let tau = config.hover_damping_s.max(1e-3);
+                let decay = (-dt / tau).exp();
+                f[[0, 2]] = tau * (1.0 - decay);
+                f[[1, 3]] = tau * (1.0 - decay);
+                f[[2, 2]] = decay;
+                f[[3, 3]] = decay;
+            }
+            ImmMode::CoordinatedTurn => {
+                let (vx, vy, w) = (state[2], state[3], state[4]);
+                let (s, c) = (w * dt).sin_cos();
+                // a = sin(wT)/w, b = (1 - cos(wT))/w and their derivatives in w,
+                // by series near zero turn rate
+                let (a, b, da, db) = if (w * dt).abs() < 1e-4 {
+                    (dt, 0.5 * w * dt * dt, -w * dt.powi(3) / 3.0, 0.5 * dt * dt)
+                } else {
+                    (
+                        s / w,
+                        (1.0 - c) / w,
+                        (dt * c * w - s) / (w * w),
+                        (dt * s * w - (1.0 - c)) / (w * w),
+                    )
+                };
+                f[[0, 2]] = a;
+                f[[0, 3]] = b;
+                f[[1, 2]] = -b;
+                f[[1, 3]] = a;
+                f[[2, 2]] = c;
+                f[[2, 3]] = s;
+                f[[3, 2]] = -s;
+                f[[3, 3]] = c;
+                f[[0, 4]] = da * vx + db * vy;
+                f[[1, 4]] = -db * vx + da * vy;
+                f[[2, 4]] = -dt * s * vx + dt * c * vy;
+                f[[3, 4]] = -dt * c * vx - dt * s * vy;
+
+                let mut predicted = state.clone();
+                predicted[0] += a * vx + b * vy;
+                predicted[1] += -b * vx + a * vy;
+                predicted[2] = c * vx + s * vy;
+                predicted[3] = -s * vx + c * vy;
+                return (predicted, f);
+            }
+        }
+        (f.dot(state), f)
+    }
+
+    /// Discrete process noise over `dt` seconds
+    fn process_noise(&self, dt: f64, config: &ImmConfig) -> Array2<f64> {
+        let acceleration = match self {
+            ImmMode::ConstantVelocity => config.cruise_noise_m_s2,
+            ImmMode::CoordinatedTurn => config.turn_noise_m_s2,
+            ImmMode::NearStationary => config.hover_noise_m_s2,
+        };
+        let planar = MotionModel::ConstantVelocity.process_noise(dt, acceleration);
+        let mut q = Array2::zeros((STATE_LEN, STATE_LEN));
+        q.slice_mut(ndarray::s![..4, ..4]).assign(&planar);
+        if *self == ImmMode::CoordinatedTurn {
+            q[[4, 4]] = (config.turn_rate_noise_deg_s2.to_radians() * dt).powi(2);
+        }
+        q
+    }
+}
+
+/// What a tracked target is currently doing, judged from its IMM mode probabilities
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FlightBehavior {
+    Cruising,
+    Maneuvering,
+    Hovering,
+}
+
+impl FlightBehavior {
+    /// Behavior of the most likely mode; `None` without mode probabilities
+    pub fn from_mode_probabilities(probabilities: &[ModeProbability]) -> Option<Self> {
+        probabilities
+            .iter()
+            .max_by(|a, b| a.probability.total_cmp(&b.probability))
+            .map(|p| p.mode.behavior())
+    }
+}
+
+/// Probability of one IMM mode
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ModeProbability {
+    pub mode: ImmMode,
+    /// Probability (0.0 to 1.0)
+    pub probability: f64,
+}
+
+/// IMM settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ImmConfig {
+    /// Acceleration noise standard deviation of the constant-velocity mode (m/s²)
+    pub cruise_noise_m_s2: f64,
+    /// Acceleration noise standard deviation of the coordinated-turn mode (m/s²)
+    pub turn_noise_m_s2: f64,
+    /// Turn-rate noise standard deviation of the coordinated-turn mode (deg/s²)
+    pub turn_rate_noise_deg_s2: f64,
+    /// Acceleration noise standard deviation of the near-stationary mode (m/s²)
+    pub hover_noise_m_s2: f64,
+    /// Time constant with which the near-stationary mode damps velocity (s)
+    pub hover_damping_s: f64,
+    /// Largest turn rate of a new track, sizing its initial turn-rate uncertainty (deg/s)
+    pub max_turn_rate_deg_s: f64,
+    /// Expected time spent in a mode before switching to another (s)
+    pub mean_sojourn_s: f64,
+}
+
+impl Default for ImmConfig {
+    fn default() -> Self {
+        Self {
+            cruise_noise_m_s2: 0.5,
+            turn_noise_m_s2: 0.5,
+            turn_rate_noise_deg_s2: 3.0,
+            hover_noise_m_s2: 0.05,
+            hover_damping_s: 1.0,
+            max_turn_rate_deg_s: 20.0,
+            mean_sojourn_s: 5.0,
+        }
+    }
+}
+
+impl ImmConfig {
+    /// Markov mode-switching matrix over `dt` seconds: the mode is kept with
+    /// probability `exp(-dt / mean_sojourn_s)`, otherwise any other mode is
+    /// equally likely
+    pub fn transition(&self, dt: f64) -> Array2<f64> {
+        let n = ImmMode::ALL.len();
+        let stay = (-dt / self.mean_sojourn_s.max(1e-3)).exp();
+        let switch = (1.0 - stay) / (n - 1) as f64;
+        Array2::from_shape_fn((n, n), |(i, j)| if i == j { stay } else { switch })
+    }
+}
+
+/// Interacting Multiple Model estimator over [`ImmMode::ALL`], one EKF per
+/// mode, with polar measurements
+#[derive(Debug, Clone)]
+pub struct ImmFilter {
+    pub config: ImmConfig,
+    /// Mode-matched state estimates, in [`ImmMode::ALL`] order
+    pub states: Vec<Array1<f64>>,
+    /// Mode-matched covariances, in [`ImmMode::ALL`] order
+    pub covariances: Vec<Array2<f64>>,
+    /// Mode probabilities, in [`ImmMode::ALL`] order
+    pub probabilities: Array1<f64>,
+}
+
+impl ImmFilter {
+    /// Start every mode from a single measurement as in
+    /// [`ExtendedKalmanFilter::initialize`], with zero turn rate of
+    /// `max_turn_rate_deg_s` uncertainty and equal mode probabilities
+    pub fn initialize(
+        config: ImmConfig,
+        measurement: &PolarMeasurement,
+        noise: &MeasurementNoise,
+        max_speed_m_s: f64,
+    ) -> Self {
+        let planar = ExtendedKalmanFilter::initialize(
+            MotionModel::ConstantVelocity,
+            measurement,
+            noise,
+            0.0,
+            max_speed_m_s,
+        );
+        let mut state = Array1::zeros(STATE_LEN);
+        state.slice_mut(ndarray::s![..4]).assign(&planar.state);
+        let mut covariance = Array2::zeros((STATE_LEN, STATE_LEN));
+        covariance
+            .slice_mut(ndarray::s![..4, ..4])
+            .assign(&planar.covariance);
+        covariance[[4, 4]] = config.max_turn_rate_deg_s.to_radians().powi(2);
+
+        let n = ImmMode::ALL.len();
+        Self {
+            config,
+            states: vec![state; n],
+            covariances: vec![covariance; n],
+            probabilities: Array1::from_elem(n, 1.0 / n as f64),
+        }
+    }
+
+    /// Mix the mode-matched estimates and predict each mode `dt` seconds ahead.
+    ///
+    /// The mode probabilities become the predicted ones until the next update.
+    pub fn predict(&mut self, dt: f64) {
+        let transition = self.config.transition(dt);
+        let predicted = transition.t().dot(&self.probabilities);
+
+        let mut states = Vec::with_capacity(ImmMode::ALL.len());
+        let mut covariances = Vec::with_capacity(ImmMode::ALL.len());
+        for (j, mode) in ImmMode::ALL.iter().enumerate() {
+            let weights: Vec<f64> = (0..ImmMode::ALL.len())
+                .map(|i| transition[[i, j]] * self.probabilities[i] / predicted[j].max(1e-300))
+                .collect();
+            let (mixed_state, mixed_covariance) =
+                moment_match(&weights, &self.states, &self.covariances);
+            let (state, f) = mode.propagate(&mixed_state, dt, &self.config);
+            covariances
+                .push(f.dot(&mixed_covariance).dot(&f.t()) + mode.process_noise(dt, &self.config));
+            states.push(state);
+        }
+        self.states = states;
+        self.covariances = covariances;
+        self.probabilities = predicted;
+    }
+
+    /// Update every mode with a measurement and reweight the modes by their
+    /// measurement likelihoods
+    pub fn update(&mut self, measurement: &PolarMeasurement, noise: &MeasurementNoise) {
+        self.update_weighted(&[(measurement, 1.0)], noise);
+    }
+
+    /// Update every mode with measurements weighted by their association
+    /// probabilities and reweight the modes by the weighted likelihoods
+    pub fn update_weighted(
+        &mut self,
+        measurements: &[(&PolarMeasurement, f64)],
+        noise: &MeasurementNoise,
+    ) {
+        let log_likelihoods: Vec<f64> = self
+            .states
+            .iter_mut()
+            .zip(self.covariances.iter_mut())
+            .map(|(state, covariance)| {
+                pda_update(state, covariance, measurements, noise).unwrap_or(f64::NEG_INFINITY)
+            })
+            .collect();
+
+        // Normalise in the log domain so distant measurements do not underflow
+        let max = log_likelihoods
+            .iter()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if !max.is_finite() {
+            return;
+        }
+        let weights: Array1<f64> = log_likelihoods
+            .iter()
+            .zip(&self.probabilities)
+            .map(|(l, p)| (l - max).exp() * p)
+            .collect();
+        let total = weights.sum();
+        if total > 0.0 {
+            self.probabilities = weights / total;
+        }
+    }
+
+    /// Combined state estimate and covariance over all modes
+    pub fn estimate(&self) -> (Array1<f64>, Array2<f64>) {
+        moment_match(
+            &self.probabilities.to_vec(),
+            &self.states,
+            &self.covariances,
+        )
+    }
+
+    /// Normalised innovation squared of a measurement against the combined
+    /// estimate, used for gating
+    pub fn mahalanobis(&self, measurement: &PolarMeasurement, noise: &MeasurementNoise) -> f64 {
+        let (state, covariance) = self.estimate();
+        let (nu, s, _) = polar_innovation(&state, &covariance, measurement, noise);
+        invert(&s).map_or(f64::INFINITY, |s_inv| nu.dot(&s_inv.dot(&nu)))
+    }
+
+    pub fn mode_probabilities(&self) -> Vec<ModeProbability> {
+        ImmMode::ALL
+            .iter()
+            .zip(&self.probabilities)
+            .map(|(&mode, &probability)| ModeProbability { mode, probability })
+            .collect()
+    }
+}
+
+/// Mean and covariance (including the spread of the means) of a Gaussian mixture
+fn moment_match(
+    weights: &[f64],
+    states: &[Array1<f64>],
+    covariances: &[Array2<f64>],
+) -> (Array1<f64>, Array2<f64>) {
+    let mut mean = Array1::zeros(STATE_LEN);
+    for (w, x) in weights.iter().zip(states) {
+        mean.scaled_add(*w, x);
+    }
+    let mut covariance = Array2::zeros((STATE_LEN, STATE_LEN));
+    for ((w, x), p) in weights.iter().zip(states).zip(covariances) {
+        let d = (x - &mean).insert_axis(ndarray::Axis(1));
+        covariance.scaled_add(*w, &(p + &d.dot(&d.t())));
+    }
+    (mean, covariance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracking::measure;
+    use crate::trajectory::{Maneuver, ManeuveringTarget};
+    use crate::types::TargetPosition;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn probability(filter: &ImmFilter, mode: ImmMode) -> f64 {
+        filter
+            .mode_probabilities()
+            .iter()
+            .find(|p| p.mode == mode)
+            .unwrap()
+            .probability
+    }
+
+    #[test]
+    fn test_transition_rows_sum_to_one() {
+        let transition = ImmConfig::default().transition(0.5);
+        for row in transition.outer_iter() {
+            assert!((row.sum() - 1.0).abs() < 1e-12);
+        }
+        assert!(transition[[0, 0]] > 0.9);
+    }
+
+    #[test]
+    fn test_turn_jacobian_matches_finite_differences() {
+        let config = ImmConfig::default();
+        for w in [0.2, -0.05, 1e-7] {
+            let state = Array1::from(vec![1_000.0, 2_000.0, 12.0, -5.0, w]);
+            let (predicted, f) = ImmMode::CoordinatedTurn.propagate(&state, 1.5, &config);
+            // A turn keeps the speed
+            assert!((predicted[2].hypot(predicted[3]) - 13.0).abs() < 1e-9);
+            for j in 0..STATE_LEN {
+                let step = 1e-6;
+                let mut plus = state.clone();
+                plus[j] += step;
+                let mut minus = state.clone();
+                minus[j] -= step;
+                let numeric = (ImmMode::CoordinatedTurn.propagate(&plus, 1.5, &config).0
+                    - ImmMode::CoordinatedTurn.propagate(&minus, 1.5, &config).0)
+                    / (2.0 * step);
+                for i in 0..STATE_LEN {
+                    assert!((f[[i, j]] - numeric[i]).abs() < 1e-5, "F[{i},{j}] at w={w}");
+                }
+            }
+        }
+        // A clockwise turn from northbound heads east
+        let north = Array1::from(vec![0.0, 0.0, 0.0, 10.0, 0.1]);
+        let (turned, _) = ImmMode::CoordinatedTurn.propagate(&north, 1.0, &config);
+        assert!(turned[2] > 0.0 && turned[0] > 0.0);
+    }
+
+    #[test]
+    fn test_mode_probabilities_follow_behavior() {
+        let noise = MeasurementNoise::default();
+        let mut rng = StdRng::seed_from_u64(15);
+        let template = TargetPosition {
+            range_m: 2_000.0,
+            azimuth_deg: 30.0,
+            rcs: 0.1,
+            ..TargetPosition::default()
+        };
+        let legs = [
+            (
+                Maneuver::Cruise { duration_s: 20.0 },
+                ImmMode::ConstantVelocity,
+            ),
+            (
+                Maneuver::Turn {
+                    duration_s: 12.0,
+                    turn_rate_deg_s: 15.0,
+                },
+                ImmMode::CoordinatedTurn,
+            ),
+            (
+                Maneuver::Hover { duration_s: 20.0 },
+                ImmMode::NearStationary,
+            ),
+        ];
+        let mut target = ManeuveringTarget::new(
+            template,
+            120.0,
+            15.0,
+            // Keep hovering after the last judged leg
+            legs.iter()
+                .map(|(leg, _)| *leg)
+                .chain([Maneuver::Hover { duration_s: 60.0 }])
+                .collect(),
+        );
+        let dt = 0.5;
+        let mut filter = ImmFilter::initialize(
+            ImmConfig::default(),
+            &measure(&target.report(), &noise, &mut rng),
+            &noise,
+            50.0,
+        );
+        for (leg, expected) in legs {
+            let steps = (leg.duration_s() / dt).round() as usize;
+            let mut mean = 0.0;
+            for step in 0..steps {
+                target.step(dt);
+                filter.predict(dt);
+                filter.update(&measure(&target.report(), &noise, &mut rng), &noise);
+                // Judge each leg on its second half, after the filter has switched
+                if step >= steps / 2 {
+                    mean += probability(&filter, expected) / (steps - steps / 2) as f64;
+                }
+            }
+            assert!(mean > 0.5, "{expected:?} probability {mean}");
+            let probabilities = filter.mode_probabilities();
+            let total: f64 = probabilities.iter().map(|p| p.probability).sum();
+            assert!((total - 1.0).abs() < 1e-9);
+        }
+        assert_eq!(
+            FlightBehavior::from_mode_probabilities(&filter.mode_probabilities()),
+            Some(FlightBehavior::Hovering)
+        );
+    }
+
+    #[test]
+    fn test_imm_follows_turn_better_than_cv() {
+        let noise = MeasurementNoise::default();
+        let mut rng = StdRng::seed_from_u64(7);
+        let template = TargetPosition {
+            range_m: 3_000.0,
+            azimuth_deg: 300.0,
+            rcs: 0.1,
+            ..TargetPosition::default()
+        };
+        let mut target = ManeuveringTarget::new(
+            template,
+            0.0,
+            20.0,
+            vec![
+                Maneuver::Cruise { duration_s: 10.0 },
+                Maneuver::Turn {
+                    duration_s: 60.0,
+                    turn_rate_deg_s: 12.0,
+                },
+            ],
+        );
+        let first = measure(&target.report(), &noise, &mut rng);
+        let mut imm = ImmFilter::initialize(ImmConfig::default(), &first, &noise, 50.0);
+        let mut cv = ExtendedKalmanFilter::initialize(
+            MotionModel::ConstantVelocity,
+            &first,
+            &noise,
+            0.5,
+            50.0,
+        );
+        let (mut imm_error, mut cv_error) = (0.0, 0.0);
+        for step in 0..140 {
+            target.step(0.5);
+            let z = measure(&target.report(), &noise, &mut rng);
+            imm.predict(0.5);
+            imm.update(&z, &noise);
+            cv.predict(0.5);
+            cv.update(&z, &noise);
+            if step >= 40 {
+                let (x, y) = target.position();
+                let (state, _) = imm.estimate();
+                imm_error += (state[0] - x).hypot(state[1] - y);
+                cv_error += (cv.state[0] - x).hypot(cv.state[1] - y);
+            }
+        }
+        assert!(
+            imm_error < 0.7 * cv_error,
+            "IMM {imm_error} vs CV {cv_error}"
+        );
+        let (state, _) = imm.estimate();
+        assert!((state[4].to_degrees() - 12.0).abs() < 3.0);
+    }
+
+    #[test]
+    fn test_behavior_from_probabilities() {
+        let probabilities = [
+            ModeProbability {
+                mode: ImmMode::ConstantVelocity,
+                probability: 0.2,
+            },
+            ModeProbability {
+                mode: ImmMode::CoordinatedTurn,
+                probability: 0.7,
+            },
+            ModeProbability {
+                mode: ImmMode::NearStationary,
+                probability: 0.1,
+            },
+        ];
+        assert_eq!(
+            FlightBehavior::from_mode_probabilities(&probabilities),
+            Some(FlightBehavior::Maneuvering)
+        );
+        assert_eq!(FlightBehavior::from_mode_probabilities(&[]), None);
+    }
+}
@@ -1,7 +1,35 @@
 pub mod analysis;
+pub mod aoa;
+pub mod association;
+pub mod cfar;
+pub mod classifier;
+pub mod clutter;
 pub mod constants;
 pub mod error;
+pub mod evaluation;
+pub mod fft;
+pub mod geo;
 pub mod handlers;
+pub mod horizon;
+pub mod imm;
+pub mod linalg;
+pub mod link_budget;
+pub mod mht;
+pub mod micro_doppler;
+pub mod mti;
 pub mod observability;
+pub mod particle;
+pub mod performance;
+pub mod processing;
+pub mod pulse_doppler;
 pub mod routes;
+pub mod scenario;
+pub mod signal;
+pub mod smoothing;
+pub mod special;
+pub mod swerling;
+pub mod tbd;
+pub mod tracking;
+pub mod trajectory;
 pub mod types;
+pub mod waveform;
@@ -0,0 +1,236 @@
+use ndarray::{Array1, Array2};
+use num_complex::{Complex, ComplexFloat};
+
+/// Inverse of a square real or complex matrix by Gauss-Jordan elimination
+/// with partial pivoting; `None` if the matrix is singular
+pub fn invert<T: ComplexFloat<Real = f64>>(matrix: &Array2<T>) -> Option<Array2<T>> {
+    let n = matrix.nrows();
+    if n != matrix.ncols() {
+        return None;
+    }
+    let mut a = matrix.clone();
+    let mut inv = Array2::<T>::eye(n);
+    let scale = a.iter().map(|c| c.abs()).fold(0.0, f64::max);
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[[i, col]].abs().total_cmp(&a[[j, col]].abs()))?;
+        if a[[pivot, col]].abs() <= 1e-14 * scale.max(f64::MIN_POSITIVE) {
+            return None;
+        }
+        if pivot != col {
+            for k in 0..n {
+                a.swap([pivot, k], [col, k]);
+                inv.swap([pivot, k], [col, k]);
+            }
+        }
+        let p = a[[col, col]];
+        for k in 0..n {
+            a[[col, k]] = a[[col, k]] / p;
+            inv[[col, k]] = inv[[col, k]] / p;
+        }
+        for row in 0..n {
+            if row == col {
+                continue;
+            }
+            let factor = a[[row, col]];
+            if factor.abs() == 0.0 {
+                continue;
+            }
+            for k in 0..n {
+                let (ak, ik) = (a[[col, k]], inv[[col, k]]);
+                a[[row, k]] = a[[row, k]] - factor * ak;
+                inv[[row, k]] = inv[[row, k]] - factor * ik;
+            }
+        }
+    }
+    Some(inv)
+}
+
+/// Determinant of a square real matrix by Gaussian elimination with partial pivoting
+pub fn determinant(matrix: &Array2<f64>) -> f64 {
+    let n = matrix.nrows();
+    let mut a = matrix.clone();
+    let mut det = 1.0;
+    for col in 0..n {
+        let Some(pivot) = (col..n).max_by(|&i, &j| a[[i, col]].abs().total_cmp(&a[[j, col]].abs()))
+        else {
+            return 0.0;
+        };
+        if a[[pivot, col]] == 0.0 {
+            return 0.0;
+        }
+        if pivot != col {
+            for k in 0..n {
+                a.swap([pivot, k], [col, k]);
+            }
+            det = -det;
+        }
+        let p = a[[col, col]];
+        det *= p;
+        for row in col + 1..n {
+            let factor = a[[row, col]] / p;
+            for k in col..n {
+                a[[row, k]] -= factor * a[[col, k]];
+            }
+        }
+    }
+    det
+}
+
+/// Eigen-decomposition of a real symmetric matrix by cyclic Jacobi rotations.
+///
+/// Returns eigenvalues in ascending order and the matching unit eigenvectors
+/// as columns.
+pub fn symmetric_eigen(matrix: &Array2<f64>) -> (Array1<f64>, Array2<f64>) {
+    let n = matrix.nrows();
+    let mut a = matrix.clone();
+    let mut v = Array2::<f64>::eye(n);
+
+    for _sweep in 0..100 {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[[i, j]] * a[[i, j]])
+            .sum();
+        let total: f64 = a.iter().map(|x| x * x).sum();
+        if off <= 1e-24 * total.max(f64::MIN_POSITIVE) {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[[p, q]].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[[q, q]] - a[[p, p]]) / (2.0 * a[[p, q]]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (akp, akq) = (a[[k, p]], a[[k, q]]);
+                    a[[k, p]] = c * akp - s * akq;
+                    a[[k, q]] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a[[p, k]], a[[q, k]]);
+                    a[[p, k]] = c * apk - s * aqk;
+                    a[[q, k]] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (v[[k, p]], v[[k, q]]);
+                    v[[k, p]] = c * vkp - s * vkq;
+                    v[[k, q]] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| a[[i, i]].total_cmp(&a[[j, j]]));
+    let values = Array1::from_iter(order.iter().map(|&i| a[[i, i]]));
+    let vectors = Array2::from_shape_fn((n, n), |(row, col)| v[[row, order[col]]]);
+    (values, vectors)
+}
+
+/// Real symmetric embedding `[[Re, -Im], [Im, Re]]` of a Hermitian matrix.
+///
+/// It has the Hermitian matrix's eigenvalues, each twice, and complex
+/// eigenvectors `a + jb` appear as `[a; b]` and `[-b; a]`.
+pub fn hermitian_embedding(matrix: &Array2<Complex<f64>>) -> Array2<f64> {
+    let n = matrix.nrows();
+    Array2::from_shape_fn((2 * n, 2 * n), |(i, j)| {
+        let c = matrix[[i % n, j % n]];
+        match (i < n, j < n) {
+            (true, true) | (false, false) => c.re,
+            (true, false) => -c.im,
+            (false, true) => c.im,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invert_round_trip() {
+        let m = Array2::from_shape_vec(
+            (3, 3),
+            vec![
+                Complex::new(4.0, 0.0),
+                Complex::new(1.0, 2.0),
+                Complex::new(0.0, -1.0),
+                Complex::new(1.0, -2.0),
+                Complex::new(5.0, 0.0),
+                Complex::new(2.0, 0.5),
+                Complex::new(0.0, 1.0),
+                Complex::new(2.0, -0.5),
+                Complex::new(3.0, 0.0),
+            ],
+        )
+        .unwrap();
+        let inv = invert(&m).unwrap();
+        let identity = m.dot(&inv);
+        for ((i, j), c) in identity.indexed_iter() {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            assert!((c - Complex::new(expected, 0.0)).norm() < 1e-12);
+        }
+        assert!(invert(&Array2::<Complex<f64>>::zeros((2, 2))).is_none());
+
+        let real = Array2::from_shape_vec((2, 2), vec![4.0, 7.0, 2.0, 6.0]).unwrap();
+        let expected = Array2::from_shape_vec((2, 2), vec![0.6, -0.7, -0.2, 0.4]).unwrap();
+        let inv = invert(&real).unwrap();
+        assert!(inv
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| (a - b).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_determinant() {
+        let m = Array2::from_shape_vec((3, 3), vec![0.0, 2.0, 1.0, 1.0, 1.0, 0.0, 3.0, 0.0, 2.0])
+            .unwrap();
+        // Expanding along the first row: -2 (2 - 0) + (0 - 3)
+        assert!((determinant(&m) + 7.0).abs() < 1e-12);
+        assert_eq!(determinant(&Array2::zeros((2, 2))), 0.0);
+        assert_eq!(determinant(&Array2::eye(4)), 1.0);
+    }
+
+    #[test]
+    fn test_symmetric_eigen_reconstructs() {
+        let m = Array2::from_shape_vec(
+            (3, 3),
+            vec![2.0, -1.0, 0.0, -1.0, 2.0, -1.0, 0.0, -1.0, 2.0],
+        )
+        .unwrap();
+        let (values, vectors) = symmetric_eigen(&m);
+        // Eigenvalues of the 1D Laplacian: 2 - 2 cos(k pi / 4)
+        let expected = [2.0 - 2f64.sqrt(), 2.0, 2.0 + 2f64.sqrt()];
+        for (v, e) in values.iter().zip(expected) {
+            assert!((v - e).abs() < 1e-12);
+        }
+        let rebuilt = vectors.dot(&Array2::from_diag(&values)).dot(&vectors.t());
+        assert!(rebuilt
+            .iter()
+            .zip(m.iter())
+            .all(|(a, b)| (a - b).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_hermitian_embedding_doubles_eigenvalues() {
+        let m = Array2::from_shape_vec(
+            (2, 2),
+            vec![
+                Complex::new(2.0, 0.0),
+                Complex::new(0.0, 1.0),
+                Complex::new(0.0, -1.0),
+                Complex::new(2.0, 0.0),
+            ],
+        )
+        .unwrap();
+        let (values, _) = symmetric_eigen(&hermitian_embedding(&m));
+        let expected = [1.0, 1.0, 3.0, 3.0];
+        assert!(values
+            .iter()
+            .zip(expected)
+            .all(|(v, e)| (v - e).abs() < 1e-12));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::constants::{BOLTZMANN, T0};
+use crate::types::{LinkBudgetResponse, RadarConfig, TargetLinkBudget, TargetPosition};
+use std::f64::consts::PI;
+
+/// Convert decibels to a linear power ratio
+pub fn db_to_linear(db: f64) -> f64 {
+    10f64.powf(db / 10.0)
+}
+
+/// Convert a linear power ratio to decibels
+pub fn linear_to_db(linear: f64) -> f64 {
+    10.0 * linear.log10()
+}
+
+/// Echo power at the receiver in watts from the monostatic radar range equation
+pub fn received_power_w(config: &RadarConfig, range_m: f64, rcs: f64) -> f64 {
+    let gt = db_to_linear(config.tx_gain_db);
+    let gr = db_to_linear(config.rx_gain_db);
+    let losses = db_to_linear(config.system_losses_db);
+    config.transmit_power_w * gt * gr * config.wavelength_m.powi(2) * rcs
+        / ((4.0 * PI).powi(3) * range_m.powi(4) * losses)
+}
+
+/// Thermal noise power at the receiver in watts (k T0 B F)
+pub fn noise_power_w(config: &RadarConfig) -> f64 {
+    BOLTZMANN * T0 * config.bandwidth_hz * db_to_linear(config.noise_figure_db)
+}
+
+/// Signal-to-noise ratio in dB after processing gain
+pub fn snr_db(config: &RadarConfig, range_m: f64, rcs: f64) -> f64 {
+    linear_to_db(received_power_w(config, range_m, rcs) / noise_power_w(config))
+        + config.processing_gain_db
+}
+
+/// Range in meters at which a target of the given RCS reaches `min_snr_db`
+pub fn max_detection_range_m(config: &RadarConfig, rcs: f64, min_snr_db: f64) -> f64 {
+    // SNR falls as R^-4, so scale from the SNR at 1 m
+    let snr_at_1m = snr_db(config, 1.0, rcs);
+    10f64.powf((snr_at_1m - min_snr_db) / 40.0)
+}
+
+/// Link budget for a single target
+pub fn target_link_budget(config: &RadarConfig, target: &TargetPosition) -> TargetLinkBudget {
+    let received = received_power_w(config, target.range_m, target.rcs);
+    TargetLinkBudget {
+        target_id: target.id,
+        range_m: target.range_m,
+        rcs: target.rcs,
+        received_power_dbm: linear_to_db(received) + 30.0,
+        snr_db: snr_db(config, target.range_m, target.rcs),
+    }
+}
+
+/// Link budget for every target in a scene
+pub fn compute_link_budget(config: &RadarConfig, targets: &[TargetPosition]) -> LinkBudgetResponse {
+    LinkBudgetResponse {
+        noise_power_dbm: linear_to_db(noise_power_w(config)) + 30.0,
+        targets: targets
+            .iter()
+            .map(|target| target_link_budget(config, target))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(range_m: f64, rcs: f64) -> TargetPosition {
+        TargetPosition {
+            range_m,
+            rcs,
+            ..TargetPosition::default()
+        }
+    }
+
+    #[test]
+    fn test_noise_power_reference() {
+        // kT0 = -174 dBm/Hz; 1 MHz and 3 dB noise figure gives about -111 dBm
+        let config = RadarConfig::default();
+        let noise_dbm = linear_to_db(noise_power_w(&config)) + 30.0;
+        assert!((noise_dbm + 110.98).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_received_power_hand_calculation() {
+        let config = RadarConfig {
+            transmit_power_w: 1.0,
+            tx_gain_db: 0.0,
+            rx_gain_db: 0.0,
+            wavelength_m: 1.0,
+            noise_figure_db: 0.0,
+            system_losses_db: 0.0,
+            bandwidth_hz: 1.0,
+            processing_gain_db: 0.0,
+        };
+        let expected = 1.0 / (4.0 * PI).powi(3);
+        assert!((received_power_w(&config, 1.0, 1.0) - expected).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_snr_falls_40_db_per_decade() {
+        let config = RadarConfig::default();
+        let near = snr_db(&config, 1_000.0, 1.0);
+        let far = snr_db(&config, 10_000.0, 1.0);
+        assert!((near - far - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_snr_scales_with_rcs() {
+        let config = RadarConfig::default();
+        let large = snr_db(&config, 5_000.0, 1.0);
+        let small = snr_db(&config, 5_000.0, 0.01);
+        assert!((large - small - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_max_detection_range_round_trip() {
+        let config = RadarConfig::default();
+        let range = max_detection_range_m(&config, 0.1, 13.0);
+        assert!((snr_db(&config, range, 0.1) - 13.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_compute_link_budget_per_target() {
+        let config = RadarConfig::default();
+        let result = compute_link_budget(&config, &[target(2_000.0, 1.0), target(20_000.0, 0.01)]);
+        assert_eq!(result.targets.len(), 2);
+        assert!(result.targets[0].snr_db > result.targets[1].snr_db);
+        assert!(result.targets[0].received_power_dbm > result.noise_power_dbm);
+    }
+}
@@ -1,12 +1,43 @@
 use axum::http::HeaderValue;
 use axum::http::Method;
+use radar_sim::aoa::{AngleEstimate, AoaMethod, ArrayConfig, ArrayGeometry};
+use radar_sim::association::AssociationMethod;
+use radar_sim::cfar::{CfarConfig, CfarKind, Detection};
+use radar_sim::classifier::{
+    set_default_classifier, ClassProbability, FeatureVector, GaussianNaiveBayes, TargetClass,
+};
+use radar_sim::clutter::{ClutterConfig, GroundClutter, RainClutter, SeaAmplitude, SeaClutter};
+use radar_sim::evaluation::{
+    EvaluationConfig, EvaluationReport, EvaluationScan, ScanScore, TargetContinuity, TrackAccuracy,
+    TruthState,
+};
+use radar_sim::geo::{GeodeticPosition, RadarSite};
+use radar_sim::horizon::{HorizonPoint, HorizonProfile, RadarHorizon};
+use radar_sim::imm::{FlightBehavior, ImmConfig, ImmMode, ModeProbability};
+use radar_sim::mht::MhtConfig;
+use radar_sim::micro_doppler::RotorModel;
 use radar_sim::observability::{
-    init_tracing, AppMetrics, HealthChecks, HealthStatus, MetricsResponse,
+    init_tracing, AppMetrics, HealthChecks, HealthStatus, MetricsResponse, ScanTiming,
+};
+use radar_sim::particle::{MeasurementKind, ParticleFilterConfig};
+use radar_sim::pulse_doppler::{PrfDwell, PulseDopplerParams, ResolvedTarget, ResolverConfig};
+use radar_sim::routes::{create_router, AppState};
+use radar_sim::scenario::ScenarioConfig;
+use radar_sim::smoothing::{SmoothedPoint, SmoothedTrack};
+use radar_sim::swerling::SwerlingModel;
+use radar_sim::tbd::TbdConfig;
+use radar_sim::tracking::{
+    ConfirmationLogic, DetectionModel, MeasurementNoise, MotionModel, PolarMeasurement,
+    TrackEstimate, TrackStatus, TrackerConfig,
 };
-use radar_sim::routes::create_router;
+use radar_sim::trajectory::Maneuver;
 use radar_sim::types::{
-    DroneAnalysis, RiskAssessment, TargetPosition, TrajectoryAnalysis, WebSocketMessage,
+    AmbiguityRequest, AmbiguityResponse, AnalyzeRequest, DroneAnalysis, EvaluationRequest,
+    LinkBudgetRequest, LinkBudgetResponse, RadarConfig, RangePerformance, RangeSweep,
+    RiskAssessment, RocCurve, RocPoint, RocRequest, RocResponse, TargetLinkBudget, TargetPosition,
+    TrajectoryAnalysis, Velocity3, WebSocketMessage,
 };
+use radar_sim::waveform::Waveform;
 use std::sync::Arc;
 use tower::ServiceBuilder;
 use tower_http::{
@@ -14,7 +45,7 @@ use tower_http::{
     timeout::TimeoutLayer,
     trace::TraceLayer,
 };
-use tracing::info;
+use tracing::{info, warn};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
@@ -22,21 +53,101 @@ use utoipa_swagger_ui::SwaggerUi;
 #[openapi(
     paths(
         radar_sim::handlers::analyze_handler,
+        radar_sim::handlers::link_budget_handler,
+        radar_sim::handlers::roc_handler,
+        radar_sim::handlers::ambiguity_handler,
+        radar_sim::handlers::horizon_handler,
+        radar_sim::handlers::smoothed_track_handler,
+        radar_sim::handlers::evaluation_handler,
+        radar_sim::handlers::evaluate_run_handler,
         radar_sim::observability::health_handler,
         radar_sim::observability::metrics_handler
     ),
     components(schemas(
+        AnalyzeRequest,
         TargetPosition,
+        Velocity3,
+        GeodeticPosition,
+        RadarSite,
         DroneAnalysis,
         TrajectoryAnalysis,
         RiskAssessment,
         WebSocketMessage,
+        RadarConfig,
+        LinkBudgetRequest,
+        LinkBudgetResponse,
+        TargetLinkBudget,
+        SwerlingModel,
+        RotorModel,
+        TargetClass,
+        ClassProbability,
+        FeatureVector,
+        ScenarioConfig,
+        TbdConfig,
+        RadarHorizon,
+        HorizonProfile,
+        HorizonPoint,
+        ClutterConfig,
+        GroundClutter,
+        RainClutter,
+        SeaClutter,
+        SeaAmplitude,
+        CfarConfig,
+        CfarKind,
+        Detection,
+        ArrayConfig,
+        ArrayGeometry,
+        AoaMethod,
+        AngleEstimate,
+        PulseDopplerParams,
+        PrfDwell,
+        ResolverConfig,
+        ResolvedTarget,
+        Waveform,
+        AmbiguityRequest,
+        AmbiguityResponse,
+        MotionModel,
+        PolarMeasurement,
+        MeasurementNoise,
+        TrackerConfig,
+        TrackEstimate,
+        TrackStatus,
+        SmoothedTrack,
+        SmoothedPoint,
+        EvaluationRequest,
+        EvaluationConfig,
+        EvaluationScan,
+        TruthState,
+        EvaluationReport,
+        ScanScore,
+        TrackAccuracy,
+        TargetContinuity,
+        ConfirmationLogic,
+        DetectionModel,
+        AssociationMethod,
+        MhtConfig,
+        ParticleFilterConfig,
+        MeasurementKind,
+        ImmMode,
+        ImmConfig,
+        ModeProbability,
+        FlightBehavior,
+        Maneuver,
+        RangeSweep,
+        RocRequest,
+        RocResponse,
+        RangePerformance,
+        RocCurve,
+        RocPoint,
         HealthStatus,
         HealthChecks,
-        MetricsResponse
+        MetricsResponse,
+        ScanTiming
     )),
     tags(
         (name = "Analysis", description = "Drone analysis endpoints"),
+        (name = "Radar Performance", description = "Link budget, detection performance, waveform and coverage endpoints"),
+        (name = "Tracking", description = "Track review and evaluation endpoints"),
         (name = "Health & Metrics", description = "Health check and metrics endpoints")
     ),
     info(
@@ -52,6 +163,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize structured logging
     init_tracing();
 
+    // Replace the pretrained classifier with one trained on labeled features
+    if let Ok(path) = std::env::var("CLASSIFIER_TRAINING_CSV") {
+        match GaussianNaiveBayes::from_csv_path(&path) {
+            Ok(model) => {
+                let _ = set_default_classifier(Box::new(model));
+                info!("Classifier trained from {}", path);
+            }
+            Err(e) => warn!("Failed to train classifier from {}: {}", path, e),
+        }
+    }
+
     // Initialize application metrics
     let metrics = Arc::new(AppMetrics::new());
 
@@ -93,7 +215,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .layer(TimeoutLayer::new(std::time::Duration::from_secs(30)))
         .layer(cors);
 
-    let app = create_router(metrics.clone())
+    // Place the radar on the earth for geodetic coordinates
+    let mut state = AppState::new(metrics.clone());
+    if let Ok(value) = std::env::var("RADAR_SITE") {
+        match value.parse::<RadarSite>() {
+            Ok(site) => {
+                info!(
+                    "Radar site at lat={}, lon={}, height={}m, heading={}°",
+                    site.position.latitude_deg,
+                    site.position.longitude_deg,
+                    site.position.height_m,
+                    site.heading_deg
+                );
+                state = state.with_site(site);
+            }
+            Err(e) => warn!("Ignoring RADAR_SITE: {}", e),
+        }
+    }
+
+    let app = create_router(state)
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .layer(middleware_stack);
 
@@ -101,6 +241,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("Server starting on http://127.0.0.1:3001");
     info!("Analysis API endpoint: http://127.0.0.1:3001/api/analyze");
+    info!("Link budget endpoint: http://127.0.0.1:3001/api/link-budget");
+    info!("ROC endpoint: http://127.0.0.1:3001/api/performance/roc");
+    info!("Radar horizon endpoint: http://127.0.0.1:3001/api/coverage/horizon");
+    info!("Smoothed track endpoint: http://127.0.0.1:3001/api/tracks/{{id}}/smoothed");
+    info!("Tracking evaluation endpoint: http://127.0.0.1:3001/api/evaluation");
     info!("Drone Tracking WebSocket endpoint: ws://127.0.0.1:3001/ws");
     info!("Health check endpoint: http://127.0.0.1:3001/health");
     info!("Metrics endpoint: http://127.0.0.1:3001/metrics");
@@ -0,0 +1,594 @@
+use crate::association::{k_best_assignments, PairScore, FORBIDDEN};
+use crate::error::{AppError, AppResult};
+use crate::observability::ScanTiming;
+use crate::tracking::{
+    MultiTargetTracker, PolarMeasurement, Track, TrackEstimate, TrackStatus, TrackerConfig,
+};
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+use utoipa::ToSchema;
+
+/// Multiple hypothesis tracker settings
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MhtConfig {
+    /// Filter, gate and deletion settings of every track; `association` is unused
+    pub tracking: TrackerConfig,
+    /// Probability that a target produces a measurement in a scan
+    pub detection_probability: f64,
+    /// Expected false measurements per unit of measurement space
+    /// (per meter · radian · meter per second)
+    pub clutter_density: f64,
+    /// Expected measurements of new targets per unit of measurement space
+    pub new_target_density: f64,
+    /// Global hypotheses kept after every scan
+    pub max_hypotheses: usize,
+    /// Best children generated from every global hypothesis per scan
+    pub k_best: usize,
+    /// Scans after which an association decision is final: hypotheses that
+    /// disagree with the best one about decisions this old are pruned
+    pub n_scan: usize,
+}
+
+/// Most global hypotheses a tracker may keep
+pub const MAX_HYPOTHESES: usize = 1_000;
+/// Most children a hypothesis may spawn per scan
+pub const MAX_K_BEST: usize = 50;
+/// Deepest N-scan window: scans an association decision may stay open
+pub const MAX_N_SCAN: usize = 20;
+/// Most particles the branches of one track may hold across all kept
+/// hypotheses, when tracks run particle filters
+pub const MAX_PARTICLES_PER_TREE: usize = 100_000;
+
+impl MhtConfig {
+    /// Reject settings whose scores are undefined, or whose hypothesis lists
+    /// and ranked assignments could outgrow a scan interval
+    pub fn validate(&self) -> AppResult<()> {
+        if !(self.detection_probability > 0.0 && self.detection_probability < 1.0) {
+            return Err(AppError::InvalidInput(format!(
+                "MHT detection probability must lie between 0 and 1: {}",
+                self.detection_probability
+            )));
+        }
+        if self.clutter_density <= 0.0 || self.new_target_density <= 0.0 {
+            return Err(AppError::InvalidInput(format!(
+                "MHT densities must be positive: clutter={}, new targets={}",
+                self.clutter_density, self.new_target_density
+            )));
+        }
+        let bounded = (1..=MAX_HYPOTHESES).contains(&self.max_hypotheses)
+            && (1..=MAX_K_BEST).contains(&self.k_best)
+            && (1..=MAX_N_SCAN).contains(&self.n_scan);
+        if !bounded {
+            return Err(AppError::InvalidInput(format!(
+                "MHT needs 1-{} hypotheses, 1-{} best children and a 1-{} scan window: {}, {}, {}",
+                MAX_HYPOTHESES,
+                MAX_K_BEST,
+                MAX_N_SCAN,
+                self.max_hypotheses,
+                self.k_best,
+                self.n_scan
+            )));
+        }
+        self.tracking.validate()?;
+        if let Some(particle) = &self.tracking.particle {
+            let particles = particle.num_particles.saturating_mul(self.max_hypotheses);
+            if particles > MAX_PARTICLES_PER_TREE {
+                return Err(AppError::InvalidInput(format!(
+                    "{} particles in each of {} hypotheses exceed {} particles per track",
+                    particle.num_particles, self.max_hypotheses, MAX_PARTICLES_PER_TREE
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for MhtConfig {
+    fn default() -> Self {
+        Self {
+            tracking: TrackerConfig::default(),
+            detection_probability: 0.9,
+            clutter_density: 1e-4,
+            new_target_density: 1e-5,
+            max_hypotheses: 20,
+            k_best: 5,
+            n_scan: 3,
+        }
+    }
+}
+
+/// Node of a track tree: one track under one sequence of association decisions
+#[derive(Debug, Clone)]
+struct Branch {
+    track: Track,
+    /// Measurement taken in each of the last `n_scan` scans, oldest first;
+    /// `None` for a miss
+    decisions: VecDeque<Option<usize>>,
+}
+
+/// Compatible set of branches, at most one per track, with its log
+/// likelihood relative to the best hypothesis
+#[derive(Debug, Clone)]
+struct Hypothesis {
+    branches: Vec<usize>,
+    score: f64,
+}
+
+/// Where a track of a new hypothesis comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Origin {
+    /// An existing branch with a measurement or a miss
+    Continue {
+        branch: usize,
+        measurement: Option<usize>,
+    },
+    /// A track started from a measurement no existing track takes
+    New { measurement: usize },
+}
+
+/// Track-oriented multiple hypothesis tracker.
+///
+/// Tracks are kept as trees of branches shared between global hypotheses.
+/// Every scan, each global hypothesis spawns its `k_best` children by
+/// Murty's ranked assignment over its tracks and the gated measurements; the
+/// children are pruned to `max_hypotheses` and by N-scan pruning, so memory
+/// is bounded by `max_hypotheses` times the number of tracks. Measurements
+/// no track takes start tentative tracks, which then follow the same
+/// confirmation and coasting lifecycle as in the [`Tracker`](crate::tracking::Tracker).
+/// The published tracks are those of the best hypothesis.
+#[derive(Debug, Clone)]
+pub struct MhtTracker {
+    pub config: MhtConfig,
+    branches: Vec<Branch>,
+    /// Best first
+    hypotheses: Vec<Hypothesis>,
+    next_id: u32,
+    timing: ScanTiming,
+}
+
+impl MhtTracker {
+    pub fn new(config: MhtConfig) -> Self {
+        Self {
+            config,
+            branches: Vec::new(),
+            hypotheses: vec![Hypothesis {
+                branches: Vec::new(),
+                score: 0.0,
+            }],
+            next_id: 0,
+            timing: ScanTiming::default(),
+        }
+    }
+
+    /// Estimates of the tracks in the best hypothesis, by id
+    pub fn estimates(&self) -> Vec<TrackEstimate> {
+        let mut estimates: Vec<TrackEstimate> = self.hypotheses[0]
+            .branches
+            .iter()
+            .map(|&b| self.branches[b].track.estimate())
+            .collect();
+        estimates.sort_by_key(|e| e.id);
+        estimates
+    }
+
+    /// Global hypotheses currently kept
+    pub fn hypothesis_count(&self) -> usize {
+        self.hypotheses.len()
+    }
+
+    /// Track branches currently kept across all hypotheses
+    pub fn branch_count(&self) -> usize {
+        self.branches.len()
+    }
+
+    /// Processing time per scan so far
+    pub fn timing(&self) -> ScanTiming {
+        self.timing
+    }
+
+    /// Advance all tracks by `dt` seconds and process one scan of measurements
+    pub fn step(&mut self, measurements: &[PolarMeasurement], dt: f64) {
+        let started = Instant::now();
+        let noise = self.config.tracking.measurement_noise;
+        let gate = self.config.tracking.gate;
+        for branch in &mut self.branches {
+            branch.track.predict(dt);
+        }
+        let scores: Vec<Vec<Option<PairScore>>> = self
+            .branches
+            .iter()
+            .map(|branch| {
+                measurements
+                    .iter()
+                    .map(|z| {
+                        branch
+                            .track
+                            .filter
+                            .score(z, &noise)
+                            .filter(|s| s.d2 <= gate)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let candidates = self.expand(&scores, measurements.len());
+        let kept = self.prune(candidates);
+        self.materialize(kept, measurements);
+        self.timing.record(started.elapsed());
+    }
+
+    /// Ranked children of every hypothesis, deduplicated, best first.
+    ///
+    /// Scores are log likelihood ratios against every measurement being
+    /// clutter or a new target: a track taking a measurement gains
+    /// `ln(Pd · N(nu; 0, S) / (clutter_density + new_target_density))` and a
+    /// miss `ln(1 - Pd)`.
+    fn expand(
+        &self,
+        scores: &[Vec<Option<PairScore>>],
+        num_measurements: usize,
+    ) -> Vec<(Vec<Origin>, f64)> {
+        let pd = self.config.detection_probability.clamp(1e-6, 1.0 - 1e-9);
+        let miss_cost = -(1.0 - pd).ln();
+        let unexplained =
+            (self.config.clutter_density + self.config.new_target_density).max(1e-300);
+
+        let mut children: HashMap<Vec<Origin>, f64> = HashMap::new();
+        for hypothesis in &self.hypotheses {
+            // Tracks deleted last scan have been reported and are dropped now
+            let rows: Vec<usize> = hypothesis
+                .branches
+                .iter()
+                .copied()
+                .filter(|&b| self.branches[b].track.status != TrackStatus::Deleted)
+                .collect();
+            // One miss column per track keeps every row assignable
+            let cost =
+                Array2::from_shape_fn((rows.len(), num_measurements + rows.len()), |(r, c)| {
+                    if c < num_measurements {
+                        scores[rows[r]][c].map_or(FORBIDDEN, |s| {
+                            -(pd.ln() + s.log_likelihood - unexplained.ln())
+                        })
+                    } else if c - num_measurements == r {
+                        miss_cost
+                    } else {
+                        FORBIDDEN
+                    }
+                });
+            for (assignment, cost) in k_best_assignments(&cost, self.config.k_best.max(1)) {
+                let mut taken = vec![false; num_measurements];
+                let mut origins = Vec::with_capacity(rows.len() + num_measurements);
+                for (&branch, &column) in rows.iter().zip(&assignment) {
+                    let measurement = (column < num_measurements).then_some(column);
+                    if let Some(m) = measurement {
+                        taken[m] = true;
+                    }
+                    origins.push(Origin::Continue {
+                        branch,
+                        measurement,
+                    });
+                }
+                origins.extend(
+                    (0..num_measurements)
+                        .filter(|&m| !taken[m])
+                        .map(|measurement| Origin::New { measurement }),
+                );
+                // Parents that differ only in a deleted track give the same child
+                origins.sort_unstable();
+                let score = hypothesis.score - cost;
+                let best = children.entry(origins).or_insert(f64::NEG_INFINITY);
+                *best = best.max(score);
+            }
+        }
+
+        let mut children: Vec<(Vec<Origin>, f64)> = children.into_iter().collect();
+        children.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        children
+    }
+
+    /// Decisions of a child that are `n_scan` scans old, by track
+    fn settled(&self, origins: &[Origin]) -> Vec<(Option<u32>, Option<usize>)> {
+        let n_scan = self.config.n_scan;
+        let mut settled: Vec<(Option<u32>, Option<usize>)> = origins
+            .iter()
+            .filter_map(|origin| match *origin {
+                Origin::Continue {
+                    branch,
+                    measurement,
+                } => {
+                    // The child's decisions are the branch's followed by this
+                    // scan's, so the one made n_scan scans ago is at index
+                    // len - n_scan (this scan's own when n_scan is zero)
+                    let branch = &self.branches[branch];
+                    let i = branch.decisions.len().checked_sub(n_scan)?;
+                    let settled = branch.decisions.get(i).copied().unwrap_or(measurement);
+                    Some((Some(branch.track.id), settled))
+                }
+                Origin::New { measurement } => (n_scan == 0).then_some((None, Some(measurement))),
+            })
+            .collect();
+        settled.sort_unstable();
+        settled
+    }
+
+    /// N-scan pruning against the best child, then the `max_hypotheses` best
+    fn prune(&self, candidates: Vec<(Vec<Origin>, f64)>) -> Vec<(Vec<Origin>, f64)> {
+        let Some(best) = candidates.first().map(|(origins, _)| self.settled(origins)) else {
+            return Vec::new();
+        };
+        candidates
+            .into_iter()
+            .filter(|(origins, _)| self.settled(origins) == best)
+            .take(self.config.max_hypotheses.max(1))
+            .collect()
+    }
+
+    /// Build the branches the kept hypotheses refer to and make them current
+    fn materialize(&mut self, kept: Vec<(Vec<Origin>, f64)>, measurements: &[PolarMeasurement]) {
+        let noise = self.config.tracking.measurement_noise;
+        let n_scan = self.config.n_scan;
+        let best_score = kept.first().map_or(0.0, |(_, score)| *score);
+
+        let mut branches = Vec::new();
+        let mut index: HashMap<Origin, usize> = HashMap::new();
+        let mut hypotheses = Vec::with_capacity(kept.len());
+        for (origins, score) in kept {
+            let mut members = Vec::with_capacity(origins.len());
+            for origin in origins {
+                let b = *index.entry(origin).or_insert_with(|| {
+                    let branch = match origin {
+                        Origin::Continue {
+                            branch,
+                            measurement,
+                        } => {
+                            let mut child = self.branches[branch].clone();
+                            match measurement {
+                                Some(m) => {
+                                    let z = &measurements[m];
+                                    child.track.filter.update(&[(z, 1.0)], &noise);
+                                    child.track.hit(z, &self.config.tracking);
+                                }
+                                None => child.track.miss(&self.config.tracking),
+                            }
+                            child.decisions.push_back(measurement);
+                            child
+                        }
+                        Origin::New { measurement } => {
+                            let z = &measurements[measurement];
+                            Branch {
+                                // Numbered below, once all new tracks are known
+                                track: Track::start(u32::MAX, &self.config.tracking, z),
+                                decisions: VecDeque::from([Some(measurement)]),
+                            }
+                        }
+                    };
+                    branches.push(branch);
+                    branches.len() - 1
+                });
+                let decisions = &mut branches[b].decisions;
+                while decisions.len() > n_scan {
+                    decisions.pop_front();
+                }
+                members.push(b);
+            }
+            hypotheses.push(Hypothesis {
+                branches: members,
+                score: score - best_score,
+            });
+        }
+
+        // New tracks in measurement order, each shared by all hypotheses that start it
+        let mut new_tracks: Vec<(usize, usize)> = index
+            .iter()
+            .filter_map(|(origin, &b)| match origin {
+                Origin::New { measurement } => Some((*measurement, b)),
+                Origin::Continue { .. } => None,
+            })
+            .collect();
+        new_tracks.sort_unstable();
+        for (_, b) in new_tracks {
+            branches[b].track.id = self.next_id;
+            self.next_id += 1;
+        }
+
+        self.branches = branches;
+        self.hypotheses = if hypotheses.is_empty() {
+            vec![Hypothesis {
+                branches: Vec::new(),
+                score: 0.0,
+            }]
+        } else {
+            hypotheses
+        };
+    }
+}
+
+impl MultiTargetTracker for MhtTracker {
+    fn tracker_config(&self) -> &TrackerConfig {
+        &self.config.tracking
+    }
+
+    fn step(&mut self, measurements: &[PolarMeasurement], dt: f64) {
+        MhtTracker::step(self, measurements, dt)
+    }
+
+    fn estimates(&self) -> Vec<TrackEstimate> {
+        MhtTracker::estimates(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::particle::ParticleFilterConfig;
+    use crate::tracking::{measure, Tracker};
+    use crate::types::TargetPosition;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Eight drones in two ranks of four, 60 m apart, flying north-east together
+    fn swarm(t: f64) -> Vec<TargetPosition> {
+        let (vx, vy) = (
+            12.0 * 45f64.to_radians().sin(),
+            12.0 * 45f64.to_radians().cos(),
+        );
+        (0..8)
+            .map(|id| {
+                let (rank, file) = ((id / 4) as f64, (id % 4) as f64);
+                let (x, y) = (
+                    1_000.0 + 60.0 * file + vx * t,
+                    3_000.0 + 60.0 * rank + vy * t,
+                );
+                let range_m = x.hypot(y);
+                TargetPosition {
+                    id,
+                    range_m,
+                    azimuth_deg: x.atan2(y).to_degrees(),
+                    vel_m_s: (x * vx + y * vy) / range_m,
+                    rcs: 0.05,
+                    ..TargetPosition::default()
+                }
+            })
+            .collect()
+    }
+
+    /// Scans of the swarm, each target detected with probability 0.9
+    fn scans(seed: u64, steps: usize) -> Vec<Vec<PolarMeasurement>> {
+        let noise = MhtConfig::default().tracking.measurement_noise;
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..steps)
+            .map(|step| {
+                let mut scan = Vec::new();
+                for target in swarm(step as f64) {
+                    if rng.gen_bool(0.9) {
+                        scan.push(measure(&target, &noise, &mut rng));
+                    }
+                }
+                scan
+            })
+            .collect()
+    }
+
+    /// Targets with a track estimate within 50 m
+    fn covered(estimates: &[TrackEstimate], t: f64) -> usize {
+        swarm(t)
+            .iter()
+            .filter(|target| {
+                let (az, range) = (target.azimuth_deg.to_radians(), target.range_m);
+                estimates.iter().any(|e| {
+                    let (x, y) = (e.state[0], e.state[1]);
+                    (x - range * az.sin()).hypot(y - range * az.cos()) < 50.0
+                })
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_mht_tracks_swarm() {
+        let config = MhtConfig::default();
+        let steps = 30;
+        for seed in 0..2 {
+            let mut mht = MhtTracker::new(config.clone());
+            let mut gnn = Tracker::new(config.tracking.clone());
+            let mut peak = 0;
+            for scan in scans(seed, steps) {
+                mht.step(&scan, 1.0);
+                gnn.step(&scan, 1.0);
+                peak = peak.max(mht.hypothesis_count());
+            }
+            // The swarm's ambiguities fill the hypothesis budget
+            assert_eq!(peak, config.max_hypotheses, "seed {seed}");
+            let t = (steps - 1) as f64;
+            let estimates = mht.estimates();
+            assert!(covered(&estimates, t) >= 7, "seed {seed}");
+            assert!(covered(&estimates, t) >= covered(&gnn.estimates(), t) - 1);
+            // Missed detections do not leave duplicate tracks behind
+            assert!(
+                estimates.len() <= 9,
+                "seed {seed}: {} tracks",
+                estimates.len()
+            );
+            assert_eq!(mht.timing().scans, steps as u64);
+            assert!(mht.timing().max_ms >= mht.timing().mean_ms);
+        }
+    }
+
+    #[test]
+    fn test_validate_caps_hypotheses() {
+        assert!(MhtConfig::default().validate().is_ok());
+        let invalid = [
+            MhtConfig {
+                max_hypotheses: 1_000_000,
+                ..MhtConfig::default()
+            },
+            MhtConfig {
+                k_best: 0,
+                ..MhtConfig::default()
+            },
+            MhtConfig {
+                k_best: 500,
+                ..MhtConfig::default()
+            },
+            MhtConfig {
+                n_scan: 0,
+                ..MhtConfig::default()
+            },
+            MhtConfig {
+                detection_probability: 1.0,
+                ..MhtConfig::default()
+            },
+            MhtConfig {
+                clutter_density: 0.0,
+                ..MhtConfig::default()
+            },
+            MhtConfig {
+                new_target_density: -1e-5,
+                ..MhtConfig::default()
+            },
+            // Each particle cloud fits, but not one per hypothesis
+            MhtConfig {
+                tracking: TrackerConfig {
+                    particle: Some(ParticleFilterConfig {
+                        num_particles: 10_000,
+                        ..ParticleFilterConfig::default()
+                    }),
+                    ..TrackerConfig::default()
+                },
+                max_hypotheses: 100,
+                ..MhtConfig::default()
+            },
+        ];
+        for config in invalid {
+            assert!(config.validate().is_err(), "{config:?}");
+        }
+    }
+
+    #[test]
+    fn test_mht_pruning_bounds_memory() {
+        let config = MhtConfig::default();
+        let mut mht = MhtTracker::new(config.clone());
+        let mut pruned_scans = 0;
+        for scan in scans(8, 100) {
+            mht.step(&scan, 1.0);
+            assert!(mht.hypothesis_count() <= config.max_hypotheses);
+            pruned_scans += usize::from(mht.hypothesis_count() == config.max_hypotheses);
+            // Every hypothesis holds one branch per track
+            assert!(mht.branch_count() <= config.max_hypotheses * mht.estimates().len().max(10));
+        }
+        // The cap is what bounds the tree, not a lack of ambiguity
+        assert!(pruned_scans >= 50, "{pruned_scans} scans at the cap");
+
+        // Settling every decision at once leaves a single hypothesis
+        let mut hard = MhtTracker::new(MhtConfig {
+            n_scan: 0,
+            ..config
+        });
+        for scan in scans(8, 20) {
+            hard.step(&scan, 1.0);
+            assert_eq!(hard.hypothesis_count(), 1);
+        }
+        assert_eq!(hard.estimates().len(), 8);
+    }
+}
@@ -0,0 +1,516 @@
+use crate::fft::{fft_in_place, fftshift};
+use crate::processing::WindowFunction;
+use crate::tracking::TrackEstimate;
+use crate::types::TargetPosition;
+use ndarray::Array2;
+use num_complex::Complex;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use utoipa::ToSchema;
+
+/// Rotor hub configuration of a multirotor target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RotorModel {
+    /// Number of rotors (4 for a quadcopter)
+    pub rotor_count: usize,
+    /// Blades per rotor
+    pub blade_count: usize,
+    /// Blade length (hub to tip) in meters
+    pub blade_length_m: f64,
+    /// Rotation rate in revolutions per minute
+    pub rpm: f64,
+    /// Peak RCS of a single blade flash in square meters
+    #[serde(default = "default_blade_rcs")]
+    pub blade_rcs: f64,
+}
+
+fn default_blade_rcs() -> f64 {
+    0.01
+}
+
+impl RotorModel {
+    /// Typical small quadcopter: four two-blade 12 cm rotors at 6000 rpm
+    pub fn quadcopter() -> Self {
+        Self {
+            rotor_count: 4,
+            blade_count: 2,
+            blade_length_m: 0.12,
+            rpm: 6_000.0,
+            blade_rcs: default_blade_rcs(),
+        }
+    }
+
+    /// Angular rate in radians per second
+    pub fn angular_rate(&self) -> f64 {
+        self.rpm / 60.0 * 2.0 * PI
+    }
+
+    /// Blade tip speed in meters per second
+    pub fn tip_speed_m_s(&self) -> f64 {
+        self.angular_rate() * self.blade_length_m
+    }
+
+    /// Peak one-sided micro-Doppler shift of the blade tips in Hz
+    pub fn max_doppler_hz(&self, wavelength_m: f64) -> f64 {
+        2.0 * self.tip_speed_m_s() / wavelength_m
+    }
+
+    /// Blade flashes per second from a single rotor.
+    ///
+    /// A blade flashes when it is broadside to the radar, twice per revolution.
+    /// With an even blade count opposite blades flash together.
+    pub fn blade_flash_rate_hz(&self) -> f64 {
+        let revs = self.rpm / 60.0;
+        let blades = self.blade_count as f64;
+        if self.blade_count.is_multiple_of(2) {
+            blades * revs
+        } else {
+            2.0 * blades * revs
+        }
+    }
+
+    /// Complex rotor echo at time `t` relative to the body return, using the
+    /// rotating-blade (sinc) model. Rotors start at fixed, evenly spread phases.
+    pub fn echo(&self, wavelength_m: f64, t: f64) -> Complex<f64> {
+        let omega = self.angular_rate();
+        let k = 2.0 * PI * self.blade_length_m / wavelength_m;
+        let amplitude = self.blade_rcs.max(0.0).sqrt();
+        let mut total = Complex::new(0.0, 0.0);
+        for rotor in 0..self.rotor_count {
+            let rotor_phase = 2.0 * PI * rotor as f64 / self.rotor_count.max(1) as f64;
+            for blade in 0..self.blade_count {
+                let theta = omega * t
+                    + rotor_phase
+                    + 2.0 * PI * blade as f64 / self.blade_count.max(1) as f64;
+                let x = k * theta.sin();
+                let sinc = if x.abs() < 1e-12 { 1.0 } else { x.sin() / x };
+                total += Complex::from_polar(amplitude * sinc, -x);
+            }
+        }
+        total
+    }
+}
+
+/// Short-time Fourier transform settings
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StftConfig {
+    /// Samples per analysis window
+    pub window_len: usize,
+    /// Samples between consecutive windows
+    pub hop: usize,
+    /// Taper applied to each window
+    pub window: WindowFunction,
+    /// FFT length (zero-padded, power of two, at least `window_len`)
+    pub fft_len: usize,
+}
+
+impl Default for StftConfig {
+    fn default() -> Self {
+        Self {
+            window_len: 64,
+            hop: 16,
+            window: WindowFunction::Hann,
+            fft_len: 128,
+        }
+    }
+}
+
+/// Time-frequency power map of a slow-time signal
+#[derive(Debug, Clone)]
+pub struct Spectrogram {
+    /// Power indexed as (time frame, frequency bin); zero Doppler at `fft_len / 2`
+    pub power: Array2<f64>,
+    /// Seconds between frames
+    pub time_step_s: f64,
+    /// Hertz per frequency bin
+    pub freq_bin_hz: f64,
+}
+
+impl Spectrogram {
+    /// Doppler frequency in Hz of a (shifted) bin
+    pub fn bin_to_hz(&self, bin: usize) -> f64 {
+        (bin as f64 - (self.power.ncols() / 2) as f64) * self.freq_bin_hz
+    }
+}
+
+/// Spectrogram of a slow-time signal sampled at `sample_rate_hz`
+pub fn stft(signal: &[Complex<f64>], sample_rate_hz: f64, config: &StftConfig) -> Spectrogram {
+    let fft_len = config.fft_len.max(config.window_len).next_power_of_two();
+    let window = config.window.coefficients(config.window_len);
+    let hop = config.hop.max(1);
+    let num_frames = if signal.len() >= config.window_len {
+        (signal.len() - config.window_len) / hop + 1
+    } else {
+        0
+    };
+
+    let mut power = Array2::<f64>::zeros((num_frames, fft_len));
+    let mut buf = vec![Complex::new(0.0, 0.0); fft_len];
+    for frame in 0..num_frames {
+        buf.iter_mut().for_each(|c| *c = Complex::new(0.0, 0.0));
+        let start = frame * hop;
+        for (i, w) in window.iter().enumerate() {
+            buf[i] = signal[start + i] * *w;
+        }
+        fft_in_place(&mut buf);
+        fftshift(&mut buf);
+        for (bin, value) in buf.iter().enumerate() {
+            power[[frame, bin]] = value.norm_sqr();
+        }
+    }
+
+    Spectrogram {
+        power,
+        time_step_s: hop as f64 / sample_rate_hz,
+        freq_bin_hz: sample_rate_hz / fft_len as f64,
+    }
+}
+
+/// Slow-time return of a target's range cell: body Doppler plus rotor modulation
+pub fn slow_time_return(
+    target: &TargetPosition,
+    wavelength_m: f64,
+    prf_hz: f64,
+    num_pulses: usize,
+) -> Vec<Complex<f64>> {
+    let body = target.rcs.max(0.0).sqrt();
+    (0..num_pulses)
+        .map(|n| {
+            let t = n as f64 / prf_hz;
+            let phase = 4.0 * PI * (target.range_m + target.vel_m_s * t) / wavelength_m;
+            let rotor = target
+                .rotor
+                .as_ref()
+                .map_or(Complex::new(0.0, 0.0), |r| r.echo(wavelength_m, t));
+            (rotor + body) * Complex::from_polar(1.0, phase)
+        })
+        .collect()
+}
+
+/// Spectrogram of a single target's slow-time return
+pub fn target_spectrogram(
+    target: &TargetPosition,
+    wavelength_m: f64,
+    prf_hz: f64,
+    num_pulses: usize,
+    config: &StftConfig,
+) -> Spectrogram {
+    stft(
+        &slow_time_return(target, wavelength_m, prf_hz, num_pulses),
+        prf_hz,
+        config,
+    )
+}
+
+/// Spectrogram of a track's gate in a simulated scene.
+///
+/// The slow-time return sums the echoes of the targets within `gate_m` of
+/// the track's estimated position and is referenced to the track's
+/// estimated range and radial velocity, so the body line of the target it
+/// follows sits near zero Doppler with any blade modulation spread around
+/// it. A track with no target in its gate (a false track) gives zero power.
+pub fn track_spectrogram(
+    track: &TrackEstimate,
+    scene: &[TargetPosition],
+    gate_m: f64,
+    wavelength_m: f64,
+    prf_hz: f64,
+    num_pulses: usize,
+    config: &StftConfig,
+) -> Spectrogram {
+    let centre = TargetPosition {
+        range_m: track.range_m,
+        azimuth_deg: track.azimuth_deg,
+        elevation_deg: track.elevation_deg,
+        ..TargetPosition::default()
+    }
+    .position_m();
+    let in_gate = |target: &&TargetPosition| {
+        let position = target.position_m();
+        let distance = (0..3)
+            .map(|i| (position[i] - centre[i]).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        distance <= gate_m
+    };
+
+    let mut signal = vec![Complex::new(0.0, 0.0); num_pulses];
+    for target in scene.iter().filter(in_gate) {
+        let echoes = slow_time_return(target, wavelength_m, prf_hz, num_pulses);
+        for (sample, echo) in signal.iter_mut().zip(echoes) {
+            *sample += echo;
+        }
+    }
+    for (n, sample) in signal.iter_mut().enumerate() {
+        let t = n as f64 / prf_hz;
+        let phase = 4.0 * PI * (track.range_m + track.vel_m_s * t) / wavelength_m;
+        *sample *= Complex::from_polar(1.0, -phase);
+    }
+    stft(&signal, prf_hz, config)
+}
+
+/// Micro-Doppler features measured from a spectrogram
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MicroDopplerFeatures {
+    /// Spread of Doppler frequencies above the detection floor, in Hz
+    pub doppler_bandwidth_hz: f64,
+    /// Rate of broadband blade flashes, in Hz (0 if none are found)
+    pub blade_flash_rate_hz: f64,
+}
+
+/// Measure micro-Doppler bandwidth and blade-flash rate.
+///
+/// Cells within `dynamic_range_db` of the strongest cell count as occupied.
+/// The bandwidth is the extent of occupied bins over all frames; the flash
+/// rate is the fundamental period of the per-frame occupied width, found by
+/// autocorrelation.
+pub fn extract_features(spectrogram: &Spectrogram, dynamic_range_db: f64) -> MicroDopplerFeatures {
+    let power = &spectrogram.power;
+    let peak = power.iter().cloned().fold(0.0, f64::max);
+    if peak <= 0.0 || power.nrows() == 0 {
+        return MicroDopplerFeatures {
+            doppler_bandwidth_hz: 0.0,
+            blade_flash_rate_hz: 0.0,
+        };
+    }
+    let floor = peak * 10f64.powf(-dynamic_range_db / 10.0);
+
+    let mut min_bin = usize::MAX;
+    let mut max_bin = 0;
+    let widths: Vec<f64> = power
+        .outer_iter()
+        .map(|frame| {
+            let occupied: Vec<usize> = frame
+                .iter()
+                .enumerate()
+                .filter(|(_, &p)| p > floor)
+                .map(|(bin, _)| bin)
+                .collect();
+            if let (Some(&lo), Some(&hi)) = (occupied.first(), occupied.last()) {
+                min_bin = min_bin.min(lo);
+                max_bin = max_bin.max(hi);
+            }
+            occupied.len() as f64
+        })
+        .collect();
+
+    let doppler_bandwidth_hz = if min_bin <= max_bin {
+        (max_bin - min_bin + 1) as f64 * spectrogram.freq_bin_hz
+    } else {
+        0.0
+    };
+
+    MicroDopplerFeatures {
+        doppler_bandwidth_hz,
+        blade_flash_rate_hz: fundamental_rate(&widths, spectrogram.time_step_s),
+    }
+}
+
+/// Fundamental repetition rate of a series from its autocorrelation
+fn fundamental_rate(series: &[f64], time_step_s: f64) -> f64 {
+    let n = series.len();
+    let mean = series.iter().sum::<f64>() / n.max(1) as f64;
+    let centred: Vec<f64> = series.iter().map(|x| x - mean).collect();
+    let energy: f64 = centred.iter().map(|x| x * x).sum();
+    if n < 4 || energy <= 1e-12 {
+        return 0.0;
+    }
+    let acf: Vec<f64> = (0..n / 2)
+        .map(|lag| {
+            centred[..n - lag]
+                .iter()
+                .zip(&centred[lag..])
+                .map(|(a, b)| a * b)
+                .sum::<f64>()
+                / energy
+        })
+        .collect();
+
+    // Skip the zero-lag lobe, then take the first peak close to the strongest one;
+    // later multiples of the period can score higher when it is not a whole
+    // number of frames.
+    let Some(first_negative) = acf.iter().position(|&r| r < 0.0) else {
+        return 0.0;
+    };
+    let peaks: Vec<usize> = (first_negative.max(1)..acf.len().saturating_sub(1))
+        .filter(|&lag| acf[lag] >= acf[lag - 1] && acf[lag] >= acf[lag + 1])
+        .collect();
+    let strongest = peaks.iter().map(|&lag| acf[lag]).fold(0.0, f64::max);
+    if strongest <= 0.3 {
+        return 0.0;
+    }
+    let Some(&lag) = peaks.iter().find(|&&lag| acf[lag] >= 0.8 * strongest) else {
+        return 0.0;
+    };
+
+    // Parabolic interpolation for a sub-frame period estimate
+    let (a, b, c) = (acf[lag - 1], acf[lag], acf[lag + 1]);
+    let curvature = a - 2.0 * b + c;
+    let offset = if curvature.abs() > 1e-12 {
+        (0.5 * (a - c) / curvature).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+    1.0 / ((lag as f64 + offset) * time_step_s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAVELENGTH: f64 = 0.0316;
+    const PRF: f64 = 40_000.0;
+
+    fn drone(rotor: Option<RotorModel>) -> TargetPosition {
+        TargetPosition {
+            range_m: 2_000.0,
+            vel_m_s: 5.0,
+            rcs: 0.05,
+            rotor,
+            ..TargetPosition::default()
+        }
+    }
+
+    #[test]
+    fn test_rotor_kinematics() {
+        let rotor = RotorModel::quadcopter();
+        assert!((rotor.tip_speed_m_s() - 2.0 * PI * 100.0 * 0.12).abs() < 1e-9);
+        assert!((rotor.blade_flash_rate_hz() - 200.0).abs() < 1e-9);
+
+        let three_blade = RotorModel {
+            blade_count: 3,
+            ..RotorModel::quadcopter()
+        };
+        assert!((three_blade.blade_flash_rate_hz() - 600.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_blade_flash_peaks_when_broadside() {
+        let rotor = RotorModel {
+            rotor_count: 1,
+            blade_count: 1,
+            ..RotorModel::quadcopter()
+        };
+        // theta = 0 at t = 0: blade broadside, full blade amplitude
+        assert!((rotor.echo(WAVELENGTH, 0.0).norm() - 0.1).abs() < 1e-9);
+        // Quarter turn later the blade points at the radar and the return collapses
+        let quarter = 0.25 * 60.0 / rotor.rpm;
+        assert!(rotor.echo(WAVELENGTH, quarter).norm() < 0.01);
+    }
+
+    #[test]
+    fn test_stft_locates_body_doppler() {
+        let target = drone(None);
+        let spectrogram =
+            target_spectrogram(&target, WAVELENGTH, PRF, 2_048, &StftConfig::default());
+        let frame = spectrogram.power.row(10);
+        let (bin, _) =
+            frame.iter().enumerate().fold(
+                (0, 0.0),
+                |best, (i, &p)| if p > best.1 { (i, p) } else { best },
+            );
+        let expected = 2.0 * target.vel_m_s / WAVELENGTH;
+        assert!((spectrogram.bin_to_hz(bin) - expected).abs() <= spectrogram.freq_bin_hz);
+    }
+
+    #[test]
+    fn test_rotor_widens_doppler_spread() {
+        let plain =
+            target_spectrogram(&drone(None), WAVELENGTH, PRF, 4_096, &StftConfig::default());
+        let rotor = RotorModel::quadcopter();
+        let quad = target_spectrogram(
+            &drone(Some(rotor.clone())),
+            WAVELENGTH,
+            PRF,
+            4_096,
+            &StftConfig::default(),
+        );
+
+        let plain_features = extract_features(&plain, 30.0);
+        let quad_features = extract_features(&quad, 30.0);
+        // A rigid body occupies little more than the Hann main lobe (4 DFT bins)
+        let main_lobe_hz = 4.0 * PRF / StftConfig::default().window_len as f64;
+        assert!(plain_features.doppler_bandwidth_hz <= main_lobe_hz);
+        assert!(quad_features.doppler_bandwidth_hz > rotor.max_doppler_hz(WAVELENGTH));
+        assert_eq!(plain_features.blade_flash_rate_hz, 0.0);
+    }
+
+    #[test]
+    fn test_track_spectrogram_follows_gated_target() {
+        use crate::tracking::{measure, Tracker, TrackerConfig};
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let rotor = RotorModel::quadcopter();
+        let quad = drone(Some(rotor.clone()));
+        // A rigid target well outside the quadcopter's gate
+        let bird = TargetPosition {
+            id: 1,
+            range_m: 2_600.0,
+            azimuth_deg: 40.0,
+            ..drone(None)
+        };
+        let scene = [quad.clone(), bird];
+
+        let config = TrackerConfig::default();
+        let noise = config.measurement_noise;
+        let mut tracker = Tracker::new(config);
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..10 {
+            tracker.step(&[measure(&quad, &noise, &mut rng)], 0.1);
+        }
+        let track = tracker.estimates().remove(0);
+        let spectrogram = |track: &TrackEstimate| {
+            track_spectrogram(
+                track,
+                &scene,
+                50.0,
+                WAVELENGTH,
+                PRF,
+                4_096,
+                &StftConfig::default(),
+            )
+        };
+
+        // The body line is compensated to near zero Doppler, blades spread around it
+        let gated = spectrogram(&track);
+        let frame = gated.power.row(10);
+        let (bin, _) =
+            frame.iter().enumerate().fold(
+                (0, 0.0),
+                |best, (i, &p)| if p > best.1 { (i, p) } else { best },
+            );
+        assert!(gated.bin_to_hz(bin).abs() <= 2.0 * gated.freq_bin_hz);
+        let features = extract_features(&gated, 30.0);
+        assert!(features.doppler_bandwidth_hz > rotor.max_doppler_hz(WAVELENGTH));
+
+        // Nothing lies in the gate of a false track
+        let false_track = TrackEstimate {
+            range_m: 5_000.0,
+            ..track
+        };
+        assert!(spectrogram(&false_track).power.iter().all(|&p| p == 0.0));
+    }
+
+    #[test]
+    fn test_blade_flash_rate_recovered() {
+        let rotor = RotorModel {
+            rotor_count: 1,
+            ..RotorModel::quadcopter()
+        };
+        let spectrogram = target_spectrogram(
+            &drone(Some(rotor.clone())),
+            WAVELENGTH,
+            PRF,
+            8_192,
+            &StftConfig::default(),
+        );
+        let features = extract_features(&spectrogram, 30.0);
+        let expected = rotor.blade_flash_rate_hz();
+        assert!(
+            (features.blade_flash_rate_hz - expected).abs() < 0.1 * expected,
+            "measured {} Hz, expected {expected} Hz",
+            features.blade_flash_rate_hz
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use ndarray::{Array1, Array2, Array3, ArrayView1, Axis};
+use num_complex::Complex;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use utoipa::ToSchema;
+
+/// Moving-target-indication filter applied along slow time (pulse to pulse)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MtiFilter {
+    /// Single delay-line canceller: x[n] - x[n-1]
+    TwoPulse,
+    /// Double delay-line canceller: x[n] - 2x[n-1] + x[n-2]
+    ThreePulse,
+    /// Arbitrary FIR taps, `coefficients[k]` weighting pulse n-k
+    Fir { coefficients: Vec<f64> },
+}
+
+impl MtiFilter {
+    pub fn coefficients(&self) -> Vec<f64> {
+        match self {
+            MtiFilter::TwoPulse => vec![1.0, -1.0],
+            MtiFilter::ThreePulse => vec![1.0, -2.0, 1.0],
+            MtiFilter::Fir { coefficients } => coefficients.clone(),
+        }
+    }
+
+    /// Number of pulses consumed per output sample
+    pub fn len(&self) -> usize {
+        match self {
+            MtiFilter::TwoPulse => 2,
+            MtiFilter::ThreePulse => 3,
+            MtiFilter::Fir { coefficients } => coefficients.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Power gain |H(f)|² at a Doppler frequency for a given PRF
+    pub fn power_response(&self, doppler_hz: f64, prf_hz: f64) -> f64 {
+        let omega = 2.0 * PI * doppler_hz / prf_hz;
+        self.coefficients()
+            .iter()
+            .enumerate()
+            .map(|(k, h)| Complex::from_polar(*h, -omega * k as f64))
+            .sum::<Complex<f64>>()
+            .norm_sqr()
+    }
+
+    /// Power gain averaged over all Doppler frequencies (sum of squared taps)
+    pub fn average_gain(&self) -> f64 {
+        self.coefficients().iter().map(|h| h * h).sum()
+    }
+
+    /// Improvement factor in dB against clutter with a Gaussian spectrum of
+    /// standard deviation `clutter_spread_hz` centred on zero Doppler.
+    ///
+    /// This is the signal-to-clutter gain averaged over target Doppler,
+    /// `sum h_k² / sum_ij h_i h_j rho(i - j)`, with the clutter correlation
+    /// `rho(k) = exp(-2 pi² sigma² k² / PRF²)`.
+    pub fn improvement_factor_db(&self, clutter_spread_hz: f64, prf_hz: f64) -> f64 {
+        let h = self.coefficients();
+        let sigma_t = clutter_spread_hz / prf_hz;
+        let clutter_gain: f64 = h
+            .iter()
+            .enumerate()
+            .flat_map(|(i, hi)| {
+                h.iter().enumerate().map(move |(j, hj)| {
+                    let lag = i as f64 - j as f64;
+                    hi * hj * (-2.0 * PI * PI * sigma_t * sigma_t * lag * lag).exp()
+                })
+            })
+            .sum();
+        10.0 * (self.average_gain() / clutter_gain.max(f64::MIN_POSITIVE)).log10()
+    }
+
+    /// Filter one slow-time sequence, keeping only fully overlapped outputs
+    /// (`pulses.len() - len() + 1` samples)
+    pub fn apply(&self, pulses: ArrayView1<Complex<f64>>) -> Array1<Complex<f64>> {
+        let h = self.coefficients();
+        let taps = h.len();
+        if taps == 0 || pulses.len() < taps {
+            return Array1::zeros(0);
+        }
+        Array1::from_iter((taps - 1..pulses.len()).map(|n| {
+            h.iter()
+                .enumerate()
+                .map(|(k, hk)| pulses[n - k] * *hk)
+                .sum::<Complex<f64>>()
+        }))
+    }
+}
+
+/// Filter every row of a (range cell × pulse) array along slow time
+pub fn filter_slow_time(pulses: &Array2<Complex<f64>>, filter: &MtiFilter) -> Array2<Complex<f64>> {
+    let (rows, num_pulses) = pulses.dim();
+    let out_len = (num_pulses + 1).saturating_sub(filter.len().max(1));
+    let mut out = Array2::zeros((rows, out_len));
+    for (mut out_row, row) in out.outer_iter_mut().zip(pulses.outer_iter()) {
+        out_row.assign(&filter.apply(row));
+    }
+    out
+}
+
+/// Filter an IQ cube (fast time × chirp × channel) along the chirp axis
+pub fn filter_cube(cube: &Array3<Complex<f64>>, filter: &MtiFilter) -> Array3<Complex<f64>> {
+    let (samples, chirps, channels) = cube.dim();
+    let out_len = (chirps + 1).saturating_sub(filter.len().max(1));
+    let mut out = Array3::zeros((samples, out_len, channels));
+    for n in 0..samples {
+        for k in 0..channels {
+            let filtered = filter.apply(cube.slice(ndarray::s![n, .., k]));
+            out.slice_mut(ndarray::s![n, .., k]).assign(&filtered);
+        }
+    }
+    out
+}
+
+/// Improvement factor in dB measured on clutter-only data (range cell × pulse):
+/// clutter attenuation times the filter's average signal gain
+pub fn measured_improvement_factor_db(clutter: &Array2<Complex<f64>>, filter: &MtiFilter) -> f64 {
+    let mean_power = |a: &Array2<Complex<f64>>| {
+        a.iter().map(|c| c.norm_sqr()).sum::<f64>() / a.len().max(1) as f64
+    };
+    let input = mean_power(clutter);
+    let output = mean_power(&filter_slow_time(clutter, filter));
+    10.0 * (input / output.max(f64::MIN_POSITIVE) * filter.average_gain()).log10()
+}
+
+/// Slow-time history of each fast-time sample of one channel: (sample × pulse).
+///
+/// MTI is linear, so filtering before or after the range FFT is equivalent.
+pub fn slow_time_matrix(cube: &Array3<Complex<f64>>, channel: usize) -> Array2<Complex<f64>> {
+    cube.index_axis(Axis(2), channel).to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clutter::{add_clutter_to_cube, ClutterConfig, GroundClutter};
+    use crate::processing::{range_doppler_map, ProcessingConfig};
+    use crate::signal::{simulate_iq_cube, FmcwParams};
+    use crate::types::TargetPosition;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rand_distr::{Distribution, Normal};
+
+    const PRF: f64 = 10_000.0;
+
+    #[test]
+    fn test_canceller_responses() {
+        for filter in [MtiFilter::TwoPulse, MtiFilter::ThreePulse] {
+            assert!(filter.power_response(0.0, PRF) < 1e-20);
+        }
+        assert!((MtiFilter::TwoPulse.power_response(PRF / 2.0, PRF) - 4.0).abs() < 1e-12);
+        assert!((MtiFilter::ThreePulse.power_response(PRF / 2.0, PRF) - 16.0).abs() < 1e-12);
+        assert_eq!(MtiFilter::TwoPulse.average_gain(), 2.0);
+        assert_eq!(MtiFilter::ThreePulse.average_gain(), 6.0);
+    }
+
+    #[test]
+    fn test_apply_rejects_constant_and_keeps_alternating() {
+        let constant = Array1::from_elem(8, Complex::new(3.0, -1.0));
+        let out = MtiFilter::ThreePulse.apply(constant.view());
+        assert_eq!(out.len(), 6);
+        assert!(out.iter().all(|c| c.norm() < 1e-12));
+
+        let alternating = Array1::from_iter((0..8).map(|n| Complex::new((-1f64).powi(n), 0.0)));
+        let out = MtiFilter::TwoPulse.apply(alternating.view());
+        assert!(out.iter().all(|c| (c.norm() - 2.0).abs() < 1e-12));
+
+        let fir = MtiFilter::Fir {
+            coefficients: vec![1.0, -1.0],
+        };
+        assert_eq!(fir.apply(alternating.view()), out);
+    }
+
+    #[test]
+    fn test_improvement_factor_small_spread_approximations() {
+        // I2 ~ PRF² / (2 pi² sigma²), I3 ~ PRF⁴ / (8 pi⁴ sigma⁴)
+        let sigma = 10.0;
+        let i2 = 10.0 * (PRF * PRF / (2.0 * PI * PI * sigma * sigma)).log10();
+        let i3 = 10.0 * (PRF.powi(4) / (8.0 * PI.powi(4) * sigma.powi(4))).log10();
+        assert!((MtiFilter::TwoPulse.improvement_factor_db(sigma, PRF) - i2).abs() < 0.1);
+        assert!((MtiFilter::ThreePulse.improvement_factor_db(sigma, PRF) - i3).abs() < 0.1);
+        assert!(
+            MtiFilter::ThreePulse.improvement_factor_db(sigma, PRF)
+                > MtiFilter::TwoPulse.improvement_factor_db(sigma, PRF)
+        );
+    }
+
+    #[test]
+    fn test_measured_improvement_matches_analytic() {
+        // Clutter rows as sums of scatterers with Gaussian-distributed Doppler
+        let sigma_hz = 100.0;
+        let mut rng = StdRng::seed_from_u64(11);
+        let doppler = Normal::new(0.0, sigma_hz).unwrap();
+        let mut clutter = Array2::<Complex<f64>>::zeros((400, 32));
+        for mut row in clutter.outer_iter_mut() {
+            for _ in 0..64 {
+                let f: f64 = doppler.sample(&mut rng);
+                let phase = rng.gen_range(0.0..2.0 * PI);
+                for (n, c) in row.iter_mut().enumerate() {
+                    *c += Complex::from_polar(1.0, phase + 2.0 * PI * f * n as f64 / PRF);
+                }
+            }
+        }
+        for filter in [MtiFilter::TwoPulse, MtiFilter::ThreePulse] {
+            let measured = measured_improvement_factor_db(&clutter, &filter);
+            let analytic = filter.improvement_factor_db(sigma_hz, PRF);
+            assert!(
+                (measured - analytic).abs() < 1.5,
+                "{filter:?}: measured {measured} dB, analytic {analytic} dB"
+            );
+        }
+    }
+
+    #[test]
+    fn test_mti_reveals_slow_drone_in_ground_clutter() {
+        let params = FmcwParams {
+            chirps_per_frame: 64,
+            sample_rate_hz: 2.56e6,
+            num_rx: 2,
+            ..FmcwParams::default()
+        };
+        let drone = TargetPosition {
+            range_m: 1_500.0,
+            vel_m_s: 5.0,
+            rcs: 0.05,
+            ..TargetPosition::default()
+        };
+        let clutter = ClutterConfig {
+            ground: Some(GroundClutter::default()),
+            ..ClutterConfig::default()
+        };
+        let mut rng = StdRng::seed_from_u64(12);
+        let mut cube = simulate_iq_cube(&params, std::slice::from_ref(&drone));
+        add_clutter_to_cube(&mut cube, &params, &clutter, &mut rng);
+        let config = ProcessingConfig::default();
+
+        let is_drone = |map: &crate::processing::RangeDopplerMap| {
+            let peak = map.peak();
+            (peak.range_m - drone.range_m).abs() < 2.0 * map.range_bin_m
+                && (peak.velocity_m_s - drone.vel_m_s).abs() < 2.0 * map.velocity_bin_m_s
+        };
+
+        let raw = range_doppler_map(&cube, &params, &config);
+        assert!(!is_drone(&raw), "clutter should dominate without MTI");
+
+        for filter in [MtiFilter::TwoPulse, MtiFilter::ThreePulse] {
+            let filtered = range_doppler_map(&filter_cube(&cube, &filter), &params, &config);
+            assert!(is_drone(&filtered), "{filter:?} should reveal the drone");
+        }
+    }
+
+    #[test]
+    fn test_slow_time_matrix_shape() {
+        let cube = Array3::<Complex<f64>>::zeros((16, 8, 3));
+        assert_eq!(slow_time_matrix(&cube, 1).dim(), (16, 8));
+        assert_eq!(
+            filter_slow_time(&slow_time_matrix(&cube, 1), &MtiFilter::ThreePulse).dim(),
+            (16, 6)
+        );
+    }
+}
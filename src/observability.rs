@@ -5,7 +5,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::info;
 use utoipa::ToSchema;
@@ -26,6 +26,25 @@ pub struct HealthChecks {
     pub analysis_service: String,
 }
 
+/// Running statistics of how long a tracker takes per scan
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, ToSchema)]
+pub struct ScanTiming {
+    pub scans: u64,
+    pub last_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+}
+
+impl ScanTiming {
+    pub fn record(&mut self, elapsed: Duration) {
+        let ms = elapsed.as_secs_f64() * 1e3;
+        self.scans += 1;
+        self.last_ms = ms;
+        self.mean_ms += (ms - self.mean_ms) / self.scans as f64;
+        self.max_ms = self.max_ms.max(ms);
+    }
+}
+
 /// Application metrics
 #[derive(Debug, Clone)]
 pub struct AppMetrics {
@@ -35,6 +54,7 @@ pub struct AppMetrics {
     pub failed_requests: Arc<RwLock<u64>>,
     pub active_websocket_connections: Arc<RwLock<u32>>,
     pub analysis_operations: Arc<RwLock<u64>>,
+    pub tracker_scans: Arc<RwLock<ScanTiming>>,
 }
 
 impl AppMetrics {
@@ -46,6 +66,7 @@ impl AppMetrics {
             failed_requests: Arc::new(RwLock::new(0)),
             active_websocket_connections: Arc::new(RwLock::new(0)),
             analysis_operations: Arc::new(RwLock::new(0)),
+            tracker_scans: Arc::new(RwLock::new(ScanTiming::default())),
         }
     }
 
@@ -76,6 +97,11 @@ impl AppMetrics {
         *self.analysis_operations.write().await += 1;
     }
 
+    /// Time one WebSocket tracker scan took
+    pub async fn record_tracker_scan(&self, elapsed: Duration) {
+        self.tracker_scans.write().await.record(elapsed);
+    }
+
     pub fn uptime_seconds(&self) -> u64 {
         self.start_time.elapsed().as_secs()
     }
@@ -127,6 +153,8 @@ pub struct MetricsResponse {
     pub active_websocket_connections: u32,
     pub analysis_operations: u64,
     pub success_rate: f64,
+    /// Tracker cost per scan across all WebSocket sessions
+    pub tracker_scans: ScanTiming,
 }
 
 /// Metrics endpoint handler
@@ -145,6 +173,7 @@ pub async fn metrics_handler(State(metrics): State<Arc<AppMetrics>>) -> impl Int
     let failed = *metrics.failed_requests.read().await;
     let ws_connections = *metrics.active_websocket_connections.read().await;
     let analysis_ops = *metrics.analysis_operations.read().await;
+    let tracker_scans = *metrics.tracker_scans.read().await;
 
     let success_rate = if total > 0 {
         (success as f64 / total as f64) * 100.0
@@ -160,6 +189,7 @@ pub async fn metrics_handler(State(metrics): State<Arc<AppMetrics>>) -> impl Int
         active_websocket_connections: ws_connections,
         analysis_operations: analysis_ops,
         success_rate,
+        tracker_scans,
     };
 
     (StatusCode::OK, Json(response))
@@ -0,0 +1,540 @@
+use crate::association::PairScore;
+use crate::error::{AppError, AppResult};
+use crate::linalg::invert;
+use crate::tracking::{log_likelihood, wrap_angle, MeasurementNoise, PolarMeasurement};
+use ndarray::{Array1, Array2};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Normal, StandardNormal};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use utoipa::ToSchema;
+
+/// Chi-square 99% gate for a one-dimensional innovation, for bearing- or range-only tracking
+pub const GATE_CHI2_1DOF_99: f64 = 6.635;
+
+/// Components of a [`PolarMeasurement`] a sensor actually observes; the
+/// others are ignored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MeasurementKind {
+    /// Range, azimuth and radial velocity, as from an active radar
+    #[default]
+    Polar,
+    /// Azimuth only, as from a passive direction finder
+    BearingOnly,
+    /// Range only, as from a non-directional ranging sensor
+    RangeOnly,
+}
+
+impl MeasurementKind {
+    /// Indices of the observed components in `[range, azimuth, radial velocity]`
+    fn components(&self) -> &'static [usize] {
+        match self {
+            MeasurementKind::Polar => &[0, 1, 2],
+            MeasurementKind::BearingOnly => &[1],
+            MeasurementKind::RangeOnly => &[0],
+        }
+    }
+}
+
+/// Particle filter settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ParticleFilterConfig {
+    /// Particles per track
+    pub num_particles: usize,
+    /// Acceleration noise standard deviation of the constant-velocity motion (m/s²)
+    pub process_noise: f64,
+    /// Measured components; the tracker `gate` applies to these, so bearing-
+    /// and range-only tracking want a one-degree-of-freedom gate such as
+    /// [`GATE_CHI2_1DOF_99`]
+    #[serde(default)]
+    pub measurement: MeasurementKind,
+    /// Resample once the effective sample size falls below this fraction of the particles
+    pub resample_threshold: f64,
+    /// Nearest range at which a track's particles start when range is not measured
+    pub min_range_m: f64,
+    /// Farthest range at which a track's particles start when range is not measured
+    pub max_range_m: f64,
+    /// Seed of every track's random draws, mixed with its first measurement
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl Default for ParticleFilterConfig {
+    fn default() -> Self {
+        Self {
+            num_particles: 2_000,
+            process_noise: 5.0,
+            measurement: MeasurementKind::Polar,
+            resample_threshold: 0.5,
+            min_range_m: 100.0,
+            max_range_m: 20_000.0,
+            seed: 0,
+        }
+    }
+}
+
+/// Most particles a track may carry. A session runs one cloud per track,
+/// tentative tracks started by every false alarm included, so a scan costs
+/// this many particles times the tracks alive.
+pub const MAX_PARTICLES_PER_TRACK: usize = 10_000;
+
+impl ParticleFilterConfig {
+    /// Reject settings that would allocate unbounded particle clouds or
+    /// leave the particles without motion noise
+    pub fn validate(&self) -> AppResult<()> {
+        if !(1..=MAX_PARTICLES_PER_TRACK).contains(&self.num_particles) {
+            return Err(AppError::InvalidInput(format!(
+                "A particle filter needs 1-{} particles per track: {}",
+                MAX_PARTICLES_PER_TRACK, self.num_particles
+            )));
+        }
+        if !(self.process_noise > 0.0 && self.process_noise.is_finite()) {
+            return Err(AppError::InvalidInput(format!(
+                "Particle process noise must be positive: {}",
+                self.process_noise
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.resample_threshold) {
+            return Err(AppError::InvalidInput(format!(
+                "Resample threshold must lie between 0 and 1: {}",
+                self.resample_threshold
+            )));
+        }
+        if !(0.0 <= self.min_range_m
+            && self.min_range_m <= self.max_range_m
+            && self.max_range_m.is_finite())
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Particle ranges must satisfy 0 <= min <= max: {} {}",
+                self.min_range_m, self.max_range_m
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// `[range, azimuth (rad), radial velocity]` of a particle `[x, y, vx, vy]`
+fn particle_measurement(particle: &[f64; 4]) -> [f64; 3] {
+    let [x, y, vx, vy] = *particle;
+    let r = x.hypot(y).max(1e-6);
+    [r, x.atan2(y), (x * vx + y * vy) / r]
+}
+
+/// Sequential importance resampling particle filter on a constant-velocity
+/// state `[x, y, vx, vy]`.
+///
+/// The posterior is carried by weighted samples instead of a mean and
+/// covariance, so it can stay multimodal or stretched along a bearing line
+/// where an EKF's linearisation breaks down, and any subset of the polar
+/// measurement can be used. Particles move by the constant-velocity model
+/// with random accelerations, are reweighted by the measurement likelihood
+/// and are resampled (systematically) when their weights degenerate.
+#[derive(Debug, Clone)]
+pub struct ParticleFilter {
+    pub config: ParticleFilterConfig,
+    particles: Vec<[f64; 4]>,
+    /// Normalised particle weights
+    weights: Vec<f64>,
+    rng: StdRng,
+}
+
+impl ParticleFilter {
+    /// Spread particles over the states consistent with one measurement.
+    ///
+    /// Measured components are drawn around their measured values with the
+    /// measurement noise. An unmeasured range is uniform over
+    /// `[min_range_m, max_range_m]`, an unmeasured azimuth uniform over the
+    /// circle, and velocity has a uniform direction and speed up to
+    /// `max_speed_m_s` unless the radial velocity is measured, in which case
+    /// only the tangential component is uniform.
+    pub fn initialize(
+        config: ParticleFilterConfig,
+        measurement: &PolarMeasurement,
+        noise: &MeasurementNoise,
+        max_speed_m_s: f64,
+    ) -> Self {
+        let seed = config.seed
+            ^ measurement.range_m.to_bits()
+            ^ measurement.azimuth_deg.to_bits().rotate_left(32);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let observed = config.measurement.components();
+        let n = config.num_particles.max(1);
+        let max_speed = max_speed_m_s.max(0.0);
+        let (min_range, max_range) = (
+            config.min_range_m.max(0.0),
+            config.max_range_m.max(config.min_range_m),
+        );
+
+        let gaussian = |rng: &mut StdRng, std: f64| std * rng.sample::<f64, _>(StandardNormal);
+
+        let mut particles = Vec::with_capacity(n);
+        for _ in 0..n {
+            let range = if observed.contains(&0) {
+                (measurement.ground_range_m() + gaussian(&mut rng, noise.range_std_m)).max(0.0)
+            } else {
+                rng.gen_range(min_range..=max_range)
+            };
+            let azimuth = if observed.contains(&1) {
+                (measurement.azimuth_deg + gaussian(&mut rng, noise.azimuth_std_deg)).to_radians()
+            } else {
+                rng.gen_range(0.0..2.0 * PI)
+            };
+            let los = [azimuth.sin(), azimuth.cos()];
+            let across = [azimuth.cos(), -azimuth.sin()];
+            let (radial, tangential) = if observed.contains(&2) {
+                (
+                    measurement.radial_velocity_m_s
+                        + gaussian(&mut rng, noise.radial_velocity_std_m_s),
+                    rng.gen_range(-max_speed..=max_speed),
+                )
+            } else {
+                let (speed, heading) =
+                    (rng.gen_range(0.0..=max_speed), rng.gen_range(0.0..2.0 * PI));
+                (speed * heading.cos(), speed * heading.sin())
+            };
+            particles.push([
+                range * los[0],
+                range * los[1],
+                radial * los[0] + tangential * across[0],
+                radial * los[1] + tangential * across[1],
+            ]);
+        }
+        Self {
+            config,
+            particles,
+            weights: vec![1.0 / n as f64; n],
+            rng,
+        }
+    }
+
+    pub fn particles(&self) -> &[[f64; 4]] {
+        &self.particles
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Move every particle by `dt` seconds with its own random acceleration
+    pub fn predict(&mut self, dt: f64) {
+        let Ok(acceleration) = Normal::new(0.0, self.config.process_noise.max(0.0)) else {
+            return;
+        };
+        for particle in &mut self.particles {
+            let (ax, ay) = (
+                acceleration.sample(&mut self.rng),
+                acceleration.sample(&mut self.rng),
+            );
+            particle[0] += particle[2] * dt + 0.5 * ax * dt * dt;
+            particle[1] += particle[3] * dt + 0.5 * ay * dt * dt;
+            particle[2] += ax * dt;
+            particle[3] += ay * dt;
+        }
+    }
+
+    /// Weighted circular mean of the particles' azimuths and linear means of
+    /// their range and radial velocity, with the covariance about them, all
+    /// over the observed components only
+    fn predicted_measurement(&self) -> (Vec<f64>, Array2<f64>) {
+        let observed = self.config.measurement.components();
+        let predicted: Vec<[f64; 3]> = self.particles.iter().map(particle_measurement).collect();
+        let (mut sum, mut sin, mut cos) = ([0.0; 3], 0.0, 0.0);
+        for (z, w) in predicted.iter().zip(&self.weights) {
+            for k in [0, 2] {
+                sum[k] += w * z[k];
+            }
+            sin += w * z[1].sin();
+            cos += w * z[1].cos();
+        }
+        let mean = [sum[0], sin.atan2(cos), sum[2]];
+
+        let k = observed.len();
+        let mut covariance = Array2::zeros((k, k));
+        for (z, w) in predicted.iter().zip(&self.weights) {
+            let offset: Vec<f64> = observed
+                .iter()
+                .map(|&c| match c {
+                    1 => wrap_angle(z[1] - mean[1]),
+                    _ => z[c] - mean[c],
+                })
+                .collect();
+            for i in 0..k {
+                for j in 0..k {
+                    covariance[[i, j]] += w * offset[i] * offset[j];
+                }
+            }
+        }
+        (observed.iter().map(|&c| mean[c]).collect(), covariance)
+    }
+
+    /// Observed-component residuals of a measurement against a predicted one
+    fn residual(&self, measurement: &PolarMeasurement, predicted: &[f64]) -> Vec<f64> {
+        let z = [
+            measurement.ground_range_m(),
+            measurement.azimuth_deg.to_radians(),
+            measurement.radial_velocity_m_s,
+        ];
+        self.config
+            .measurement
+            .components()
+            .iter()
+            .zip(predicted)
+            .map(|(&c, p)| match c {
+                1 => wrap_angle(z[1] - p),
+                _ => z[c] - p,
+            })
+            .collect()
+    }
+
+    /// Measurement noise variances of the observed components
+    fn noise_variances(&self, noise: &MeasurementNoise) -> Vec<f64> {
+        let all = [
+            noise.range_std_m.powi(2),
+            noise.azimuth_std_deg.to_radians().powi(2),
+            noise.radial_velocity_std_m_s.powi(2),
+        ];
+        self.config
+            .measurement
+            .components()
+            .iter()
+            .map(|&c| all[c])
+            .collect()
+    }
+
+    /// Gating statistics of a measurement, from a Gaussian fitted to the
+    /// particles' predicted measurements plus the measurement noise
+    pub fn score(
+        &self,
+        measurement: &PolarMeasurement,
+        noise: &MeasurementNoise,
+    ) -> Option<PairScore> {
+        let (predicted, mut s) = self.predicted_measurement();
+        for (i, variance) in self.noise_variances(noise).into_iter().enumerate() {
+            s[[i, i]] += variance;
+        }
+        let nu = Array1::from(self.residual(measurement, &predicted));
+        let d2 = nu.dot(&invert(&s)?.dot(&nu));
+        Some(PairScore {
+            d2,
+            log_likelihood: log_likelihood(d2, &s),
+        })
+    }
+
+    /// Reweight by measurements weighted by their association probabilities.
+    ///
+    /// The posterior is the mixture of the prior (with the probability that
+    /// none of the measurements is the target's) and the posterior under each
+    /// measurement, so each particle's weight is scaled by
+    /// `1 - Σβ + Σ β_j L_j / ℓ_j`, where `ℓ_j` is the weighted mean likelihood.
+    pub fn update(&mut self, measurements: &[(&PolarMeasurement, f64)], noise: &MeasurementNoise) {
+        let detected: f64 = measurements.iter().map(|(_, w)| w).sum();
+        if detected <= 0.0 {
+            return;
+        }
+        let variances = self.noise_variances(noise);
+        let observed = self.config.measurement.components();
+        let mut factors = vec![(1.0 - detected).max(0.0); self.particles.len()];
+        for (measurement, beta) in measurements {
+            let likelihoods: Vec<f64> = self
+                .particles
+                .iter()
+                .map(|particle| {
+                    let z = particle_measurement(particle);
+                    let predicted: Vec<f64> = observed.iter().map(|&c| z[c]).collect();
+                    let d2: f64 = self
+                        .residual(measurement, &predicted)
+                        .iter()
+                        .zip(&variances)
+                        .map(|(nu, v)| nu * nu / v.max(f64::MIN_POSITIVE))
+                        .sum();
+                    (-0.5 * d2).exp()
+                })
+                .collect();
+            let mean: f64 = likelihoods
+                .iter()
+                .zip(&self.weights)
+                .map(|(l, w)| l * w)
+                .sum();
+            if mean <= 0.0 {
+                // No particle explains this measurement
+                continue;
+            }
+            for (factor, l) in factors.iter_mut().zip(&likelihoods) {
+                *factor += beta * l / mean;
+            }
+        }
+
+        let mut total = 0.0;
+        for (weight, factor) in self.weights.iter_mut().zip(&factors) {
+            *weight *= factor;
+            total += *weight;
+        }
+        if total <= 0.0 || !total.is_finite() {
+            // Every particle ruled out: keep the prior
+            let n = self.weights.len() as f64;
+            self.weights.fill(1.0 / n);
+            return;
+        }
+        self.weights.iter_mut().for_each(|w| *w /= total);
+
+        let effective = 1.0 / self.weights.iter().map(|w| w * w).sum::<f64>();
+        if effective < self.config.resample_threshold * self.particles.len() as f64 {
+            self.resample();
+        }
+    }
+
+    /// Systematic resampling: one uniform offset, `n` evenly spaced pointers
+    /// into the cumulative weights
+    fn resample(&mut self) {
+        let n = self.particles.len();
+        let step = 1.0 / n as f64;
+        let mut pointer = self.rng.gen_range(0.0..step);
+        let mut cumulative = self.weights[0];
+        let mut source = 0;
+        let mut resampled = Vec::with_capacity(n);
+        for _ in 0..n {
+            while pointer > cumulative && source + 1 < n {
+                source += 1;
+                cumulative += self.weights[source];
+            }
+            resampled.push(self.particles[source]);
+            pointer += step;
+        }
+        self.particles = resampled;
+        self.weights.fill(step);
+    }
+
+    /// Weighted mean and covariance of the particles
+    pub fn estimate(&self) -> (Array1<f64>, Array2<f64>) {
+        let mut mean = Array1::zeros(4);
+        for (particle, w) in self.particles.iter().zip(&self.weights) {
+            mean.scaled_add(*w, &Array1::from(particle.to_vec()));
+        }
+        let mut covariance = Array2::zeros((4, 4));
+        for (particle, w) in self.particles.iter().zip(&self.weights) {
+            for i in 0..4 {
+                for j in 0..4 {
+                    covariance[[i, j]] += w * (particle[i] - mean[i]) * (particle[j] - mean[j]);
+                }
+            }
+        }
+        (mean, covariance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracking::{measure, ExtendedKalmanFilter, MotionModel};
+    use crate::types::TargetPosition;
+
+    /// Target at (2 km, 6 km) flying (12, -8) m/s, as seen `t` seconds in
+    fn truth(t: f64) -> ([f64; 4], TargetPosition) {
+        let state = [2_000.0 + 12.0 * t, 6_000.0 - 8.0 * t, 12.0, -8.0];
+        let z = particle_measurement(&state);
+        let target = TargetPosition {
+            range_m: z[0],
+            azimuth_deg: z[1].to_degrees(),
+            vel_m_s: z[2],
+            rcs: 0.1,
+            swerling: Default::default(),
+            ..TargetPosition::default()
+        };
+        (state, target)
+    }
+
+    #[test]
+    fn test_particle_filter_matches_ekf_with_polar_measurements() {
+        let noise = MeasurementNoise::default();
+        let mut rng = StdRng::seed_from_u64(20);
+        let (_, first) = truth(0.0);
+        let z = measure(&first, &noise, &mut rng);
+        let config = ParticleFilterConfig::default();
+        let mut pf = ParticleFilter::initialize(config, &z, &noise, 100.0);
+        let mut ekf =
+            ExtendedKalmanFilter::initialize(MotionModel::ConstantVelocity, &z, &noise, 5.0, 100.0);
+
+        let (mut pf_error, mut ekf_error) = (0.0, 0.0);
+        for step in 1..=60 {
+            let (state, target) = truth(step as f64);
+            let z = measure(&target, &noise, &mut rng);
+            pf.predict(1.0);
+            pf.update(&[(&z, 1.0)], &noise);
+            ekf.predict(1.0);
+            ekf.update(&z, &noise);
+            if step > 30 {
+                let (mean, _) = pf.estimate();
+                pf_error += (mean[0] - state[0]).hypot(mean[1] - state[1]).powi(2);
+                ekf_error += (ekf.state[0] - state[0])
+                    .hypot(ekf.state[1] - state[1])
+                    .powi(2);
+            }
+        }
+        let (pf_rms, ekf_rms) = ((pf_error / 30.0).sqrt(), (ekf_error / 30.0).sqrt());
+        assert!(pf_rms < 40.0, "particle filter RMS {pf_rms}");
+        assert!(pf_rms < 2.0 * ekf_rms, "pf {pf_rms} ekf {ekf_rms}");
+    }
+
+    #[test]
+    fn test_bearing_only_posterior_spans_unobserved_range() {
+        // From a fixed sensor, range is unobservable from bearings of a
+        // constant-velocity target: the posterior must stay spread along the
+        // bearing line and keep the true range inside it, rather than collapse
+        let noise = MeasurementNoise::default();
+        let mut rng = StdRng::seed_from_u64(22);
+        let config = ParticleFilterConfig {
+            measurement: MeasurementKind::BearingOnly,
+            process_noise: 0.5,
+            ..ParticleFilterConfig::default()
+        };
+        let (_, first) = truth(0.0);
+        let mut pf =
+            ParticleFilter::initialize(config, &measure(&first, &noise, &mut rng), &noise, 50.0);
+        let mut target = first;
+        for step in 1..=30 {
+            target = truth(step as f64).1;
+            pf.predict(1.0);
+            pf.update(&[(&measure(&target, &noise, &mut rng), 1.0)], &noise);
+        }
+
+        let mut ranges: Vec<(f64, f64)> = pf
+            .particles()
+            .iter()
+            .zip(pf.weights())
+            .map(|(p, &w)| (particle_measurement(p)[0], w))
+            .collect();
+        ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let quantile = |q: f64| {
+            let mut cumulative = 0.0;
+            ranges
+                .iter()
+                .find(|(_, w)| {
+                    cumulative += w;
+                    cumulative >= q
+                })
+                .map_or(f64::MAX, |r| r.0)
+        };
+        let (low, high) = (quantile(0.05), quantile(0.95));
+        assert!(
+            low < target.range_m && target.range_m < high,
+            "{low}..{high} vs {}",
+            target.range_m
+        );
+        assert!(high - low > 1_000.0, "collapsed to {low}..{high}");
+
+        // The bearing itself is pinned down
+        let (mean, _) = pf.estimate();
+        let azimuth = mean[0].atan2(mean[1]).to_degrees();
+        assert!(
+            (azimuth - target.azimuth_deg).abs() < 1.0,
+            "{azimuth} vs {}",
+            target.azimuth_deg
+        );
+        let score = pf
+            .score(&measure(&target, &noise, &mut rng), &noise)
+            .unwrap();
+        assert!(score.d2 < GATE_CHI2_1DOF_99 * 2.0);
+    }
+}
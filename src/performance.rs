@@ -0,0 +1,237 @@
+use crate::link_budget::snr_db;
+use crate::swerling::{detection_threshold, probability_of_detection, SwerlingModel};
+use crate::types::{RangePerformance, RangeSweep, RocCurve, RocPoint, RocRequest, RocResponse};
+use num_complex::Complex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, StandardNormal};
+use std::f64::consts::PI;
+
+/// False-alarm probabilities used for ROC curves: 1e-8 to 1e-1, two points per decade
+pub fn roc_pfa_grid() -> Vec<f64> {
+    (0..=14)
+        .map(|i| 10f64.powf(-8.0 + i as f64 * 0.5))
+        .collect()
+}
+
+/// Largest Monte Carlo workload, in [`monte_carlo_work`] units, a single
+/// request may ask for
+pub const MAX_MONTE_CARLO_WORK: usize = 50_000_000;
+
+/// Monte Carlo workload of a request: pulses drawn for every point of the Pd
+/// vs range curve and for every ROC range, whose draws serve all of its
+/// false-alarm probabilities
+pub fn monte_carlo_work(request: &RocRequest) -> usize {
+    let points = request
+        .range_sweep
+        .num_points
+        .saturating_add(request.roc_ranges_m.len());
+    points
+        .saturating_mul(request.monte_carlo_trials)
+        .saturating_mul(request.num_pulses.max(1))
+}
+
+/// Evenly spaced ranges of a sweep
+pub fn sweep_ranges(sweep: &RangeSweep) -> Vec<f64> {
+    match sweep.num_points {
+        0 => Vec::new(),
+        1 => vec![sweep.start_m],
+        n => {
+            let step = (sweep.stop_m - sweep.start_m) / (n - 1) as f64;
+            (0..n).map(|i| sweep.start_m + i as f64 * step).collect()
+        }
+    }
+}
+
+/// Draw `trials` detection statistics (sum of square-law samples over the dwell)
+/// for a Swerling target with average single-pulse SNR `snr_linear` in unit-power
+/// complex Gaussian noise.
+pub fn monte_carlo_statistics<R: Rng + ?Sized>(
+    model: SwerlingModel,
+    snr_linear: f64,
+    num_pulses: usize,
+    trials: usize,
+    rng: &mut R,
+) -> Vec<f64> {
+    let noise_scale = 0.5f64.sqrt();
+    (0..trials)
+        .map(|_| {
+            model
+                .dwell_rcs(1.0, num_pulses.max(1), rng)
+                .into_iter()
+                .map(|rcs| {
+                    let phase = rng.gen_range(0.0..2.0 * PI);
+                    let signal = Complex::from_polar((snr_linear * rcs).sqrt(), phase);
+                    let re: f64 = StandardNormal.sample(rng);
+                    let im: f64 = StandardNormal.sample(rng);
+                    (signal + Complex::new(re, im) * noise_scale).norm_sqr()
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Fraction of statistics exceeding `threshold`
+pub fn exceedance_fraction(statistics: &[f64], threshold: f64) -> f64 {
+    if statistics.is_empty() {
+        return 0.0;
+    }
+    statistics.iter().filter(|&&s| s > threshold).count() as f64 / statistics.len() as f64
+}
+
+/// Pd vs range and ROC curves, analytically and by Monte Carlo
+pub fn evaluate_roc(request: &RocRequest) -> RocResponse {
+    let mut rng = StdRng::seed_from_u64(request.seed);
+    let n = request.num_pulses.max(1);
+    let threshold = detection_threshold(request.pfa, n);
+
+    let pd_vs_range = sweep_ranges(&request.range_sweep)
+        .into_iter()
+        .map(|range_m| {
+            let snr = snr_db(&request.radar, range_m, request.rcs);
+            let stats = monte_carlo_statistics(
+                request.swerling,
+                10f64.powf(snr / 10.0),
+                n,
+                request.monte_carlo_trials,
+                &mut rng,
+            );
+            RangePerformance {
+                range_m,
+                snr_db: snr,
+                pd_analytic: probability_of_detection(request.swerling, snr, request.pfa, n),
+                pd_monte_carlo: exceedance_fraction(&stats, threshold),
+            }
+        })
+        .collect();
+
+    let pfas = roc_pfa_grid();
+    let thresholds: Vec<f64> = pfas
+        .iter()
+        .map(|&pfa| detection_threshold(pfa, n))
+        .collect();
+    let roc_curves = request
+        .roc_ranges_m
+        .iter()
+        .map(|&range_m| {
+            let snr = snr_db(&request.radar, range_m, request.rcs);
+            let stats = monte_carlo_statistics(
+                request.swerling,
+                10f64.powf(snr / 10.0),
+                n,
+                request.monte_carlo_trials,
+                &mut rng,
+            );
+            let points = pfas
+                .iter()
+                .zip(&thresholds)
+                .map(|(&pfa, &threshold)| RocPoint {
+                    pfa,
+                    pd_analytic: probability_of_detection(request.swerling, snr, pfa, n),
+                    pd_monte_carlo: exceedance_fraction(&stats, threshold),
+                })
+                .collect();
+            RocCurve {
+                range_m,
+                snr_db: snr,
+                points,
+            }
+        })
+        .collect();
+
+    RocResponse {
+        pd_vs_range,
+        roc_curves,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RadarConfig;
+
+    fn request(swerling: SwerlingModel, num_pulses: usize) -> RocRequest {
+        RocRequest {
+            radar: RadarConfig::default(),
+            rcs: 0.1,
+            swerling,
+            range_sweep: RangeSweep {
+                start_m: 2_000.0,
+                stop_m: 40_000.0,
+                num_points: 8,
+            },
+            pfa: 1e-3,
+            roc_ranges_m: vec![15_000.0],
+            num_pulses,
+            monte_carlo_trials: 4_000,
+            seed: 17,
+        }
+    }
+
+    #[test]
+    fn test_sweep_ranges_inclusive() {
+        let ranges = sweep_ranges(&RangeSweep {
+            start_m: 1_000.0,
+            stop_m: 5_000.0,
+            num_points: 5,
+        });
+        assert_eq!(ranges, vec![1_000.0, 2_000.0, 3_000.0, 4_000.0, 5_000.0]);
+    }
+
+    #[test]
+    fn test_monte_carlo_noise_only_matches_pfa() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let stats = monte_carlo_statistics(SwerlingModel::Zero, 0.0, 4, 100_000, &mut rng);
+        let pfa = 1e-2;
+        let measured = exceedance_fraction(&stats, detection_threshold(pfa, 4));
+        assert!((measured - pfa).abs() < 0.002, "measured Pfa {measured}");
+    }
+
+    #[test]
+    fn test_monte_carlo_agrees_with_analytic() {
+        for (model, n) in [
+            (SwerlingModel::Zero, 1),
+            (SwerlingModel::One, 1),
+            (SwerlingModel::Two, 4),
+            (SwerlingModel::Three, 4),
+            (SwerlingModel::Four, 4),
+        ] {
+            let response = evaluate_roc(&request(model, n));
+            for point in &response.pd_vs_range {
+                // Binomial standard deviation is at most 0.008 for 4000 trials
+                assert!(
+                    (point.pd_analytic - point.pd_monte_carlo).abs() < 0.04,
+                    "{model:?} at {} m: {} vs {}",
+                    point.range_m,
+                    point.pd_analytic,
+                    point.pd_monte_carlo
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_pd_decreases_with_range() {
+        let response = evaluate_roc(&request(SwerlingModel::One, 1));
+        assert!(response
+            .pd_vs_range
+            .windows(2)
+            .all(|w| w[1].pd_analytic <= w[0].pd_analytic));
+        assert!(response.pd_vs_range[0].pd_analytic > 0.99);
+        assert!(response.pd_vs_range.last().unwrap().pd_analytic < 0.5);
+    }
+
+    #[test]
+    fn test_roc_curve_increases_with_pfa() {
+        let response = evaluate_roc(&request(SwerlingModel::Zero, 1));
+        assert_eq!(response.roc_curves.len(), 1);
+        let points = &response.roc_curves[0].points;
+        assert_eq!(points.len(), roc_pfa_grid().len());
+        assert!(points
+            .windows(2)
+            .all(|w| w[1].pd_analytic >= w[0].pd_analytic));
+        assert!(points
+            .windows(2)
+            .all(|w| w[1].pd_monte_carlo >= w[0].pd_monte_carlo));
+    }
+}
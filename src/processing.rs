@@ -0,0 +1,428 @@
+use crate::fft::{fft_in_place, fftshift};
+use crate::signal::FmcwParams;
+use ndarray::{Array2, Array3, Axis};
+use num_complex::Complex;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use utoipa::ToSchema;
+
+/// Taper applied before each FFT to trade mainlobe width for sidelobe level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WindowFunction {
+    Rectangular,
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+    /// Dolph-Chebyshev window with equiripple sidelobes at `sidelobe_db` below the peak
+    Chebyshev {
+        sidelobe_db: f64,
+    },
+}
+
+impl WindowFunction {
+    /// Window coefficients for a sequence of `len` samples
+    pub fn coefficients(&self, len: usize) -> Vec<f64> {
+        if len <= 1 {
+            return vec![1.0; len];
+        }
+        let denom = (len - 1) as f64;
+        let cosine_sum = |a: &[f64]| -> Vec<f64> {
+            (0..len)
+                .map(|n| {
+                    let x = 2.0 * PI * n as f64 / denom;
+                    a.iter()
+                        .enumerate()
+                        .map(|(k, ak)| {
+                            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                            sign * ak * (k as f64 * x).cos()
+                        })
+                        .sum()
+                })
+                .collect()
+        };
+
+        match self {
+            WindowFunction::Rectangular => vec![1.0; len],
+            WindowFunction::Hann => cosine_sum(&[0.5, 0.5]),
+            WindowFunction::Hamming => cosine_sum(&[0.54, 0.46]),
+            WindowFunction::Blackman => cosine_sum(&[0.42, 0.5, 0.08]),
+            WindowFunction::Chebyshev { sidelobe_db } => chebyshev_window(len, *sidelobe_db),
+        }
+    }
+}
+
+/// Dolph-Chebyshev window computed from its frequency-domain definition
+fn chebyshev_window(len: usize, sidelobe_db: f64) -> Vec<f64> {
+    let order = (len - 1) as f64;
+    let beta = ((10f64.powf(sidelobe_db.abs() / 20.0)).acosh() / order).cosh();
+
+    let spectrum: Vec<Complex<f64>> = (0..len)
+        .map(|k| {
+            let x = beta * (PI * k as f64 / len as f64).cos();
+            let value = if x > 1.0 {
+                (order * x.acosh()).cosh()
+            } else if x < -1.0 {
+                let sign = if len % 2 == 1 { 1.0 } else { -1.0 };
+                sign * (order * (-x).acosh()).cosh()
+            } else {
+                (order * x.acos()).cos()
+            };
+            if len % 2 == 1 {
+                Complex::new(value, 0.0)
+            } else {
+                // Half-sample shift keeps even-length windows symmetric
+                Complex::from_polar(value, PI * k as f64 / len as f64)
+            }
+        })
+        .collect();
+
+    // Direct DFT: window lengths are arbitrary and this runs once per frame setup
+    let time: Vec<f64> = (0..len)
+        .map(|n| {
+            spectrum
+                .iter()
+                .enumerate()
+                .map(|(k, s)| {
+                    (s * Complex::from_polar(1.0, -2.0 * PI * (k * n) as f64 / len as f64)).re
+                })
+                .sum()
+        })
+        .collect();
+
+    let mut window = Vec::with_capacity(len);
+    if len % 2 == 1 {
+        let half = len.div_ceil(2);
+        window.extend(time[1..half].iter().rev());
+        window.extend(&time[..half]);
+    } else {
+        let half = len / 2 + 1;
+        window.extend(time[1..half].iter().rev());
+        window.extend(&time[1..half]);
+    }
+    let peak = window.iter().cloned().fold(f64::MIN, f64::max);
+    window.iter().map(|w| w / peak).collect()
+}
+
+/// Range-Doppler processing options
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProcessingConfig {
+    /// Window applied along fast time before the range FFT
+    pub range_window: WindowFunction,
+    /// Window applied along slow time before the Doppler FFT
+    pub doppler_window: WindowFunction,
+    /// Zero-padding factor for the range FFT (1 = none)
+    pub range_padding: usize,
+    /// Zero-padding factor for the Doppler FFT (1 = none)
+    pub doppler_padding: usize,
+}
+
+impl Default for ProcessingConfig {
+    fn default() -> Self {
+        Self {
+            range_window: WindowFunction::Hann,
+            doppler_window: WindowFunction::Hann,
+            range_padding: 1,
+            doppler_padding: 1,
+        }
+    }
+}
+
+/// A single resolution cell of a range-Doppler map in physical units
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RangeDopplerCell {
+    /// Range bin index
+    pub range_bin: usize,
+    /// Doppler bin index (zero velocity at `num_doppler_bins / 2`)
+    pub doppler_bin: usize,
+    /// Range in meters
+    pub range_m: f64,
+    /// Radial velocity in meters per second (positive = moving away)
+    pub velocity_m_s: f64,
+    /// Map magnitude at this cell
+    pub magnitude: f64,
+}
+
+/// Range-Doppler magnitude map with the scaling needed to convert bins to physical units
+#[derive(Debug, Clone)]
+pub struct RangeDopplerMap {
+    /// Magnitude indexed as (range bin, Doppler bin), non-coherently integrated over channels
+    pub magnitude: Array2<f64>,
+    /// Meters spanned by one range bin
+    pub range_bin_m: f64,
+    /// Meters per second spanned by one Doppler bin
+    pub velocity_bin_m_s: f64,
+}
+
+impl RangeDopplerMap {
+    pub fn num_range_bins(&self) -> usize {
+        self.magnitude.nrows()
+    }
+
+    pub fn num_doppler_bins(&self) -> usize {
+        self.magnitude.ncols()
+    }
+
+    /// Range in meters at the centre of a range bin
+    pub fn bin_to_range_m(&self, range_bin: f64) -> f64 {
+        range_bin * self.range_bin_m
+    }
+
+    /// Radial velocity in meters per second at a (shifted) Doppler bin
+    pub fn bin_to_velocity_m_s(&self, doppler_bin: f64) -> f64 {
+        (doppler_bin - (self.num_doppler_bins() / 2) as f64) * self.velocity_bin_m_s
+    }
+
+    /// Power (magnitude squared) map, as consumed by square-law detectors
+    pub fn power(&self) -> Array2<f64> {
+        self.magnitude.mapv(|m| m * m)
+    }
+
+    /// Physical description of one cell
+    pub fn cell(&self, range_bin: usize, doppler_bin: usize) -> RangeDopplerCell {
+        RangeDopplerCell {
+            range_bin,
+            doppler_bin,
+            range_m: self.bin_to_range_m(range_bin as f64),
+            velocity_m_s: self.bin_to_velocity_m_s(doppler_bin as f64),
+            magnitude: self.magnitude[[range_bin, doppler_bin]],
+        }
+    }
+
+    /// Strongest cell in the map
+    pub fn peak(&self) -> RangeDopplerCell {
+        let mut best = ((0, 0), f64::MIN);
+        for (index, &m) in self.magnitude.indexed_iter() {
+            if m > best.1 {
+                best = (index, m);
+            }
+        }
+        let (index, _) = best;
+        self.cell(index.0, index.1)
+    }
+}
+
+/// Complex range-Doppler spectrum of every channel, indexed as (range bin,
+/// Doppler bin, channel).
+///
+/// Each channel is windowed and transformed along fast time then slow time,
+/// with zero-padding up to the next power of two of the padded length. The
+/// Doppler axis is shifted so zero velocity sits in the middle column. Values
+/// are normalised by the window coherent gains so a unit-amplitude return
+/// peaks near 1, and channel-to-channel phase is preserved for angle estimation.
+pub fn range_doppler_cube(
+    cube: &Array3<Complex<f64>>,
+    config: &ProcessingConfig,
+) -> Array3<Complex<f64>> {
+    let (num_samples, num_chirps, num_channels) = cube.dim();
+    let range_fft_len = (num_samples * config.range_padding.max(1)).next_power_of_two();
+    let doppler_fft_len = (num_chirps * config.doppler_padding.max(1)).next_power_of_two();
+
+    let range_window = config.range_window.coefficients(num_samples);
+    let doppler_window = config.doppler_window.coefficients(num_chirps);
+    let gain = range_window.iter().sum::<f64>() * doppler_window.iter().sum::<f64>();
+
+    let mut output = Array3::<Complex<f64>>::zeros((range_fft_len, doppler_fft_len, num_channels));
+    let mut spectrum = Array2::<Complex<f64>>::zeros((range_fft_len, num_chirps));
+    let mut column = vec![Complex::new(0.0, 0.0); range_fft_len];
+    let mut row = vec![Complex::new(0.0, 0.0); doppler_fft_len];
+
+    for (k, channel) in cube.axis_iter(Axis(2)).enumerate() {
+        // Range FFT per chirp
+        for (m, chirp) in channel.axis_iter(Axis(1)).enumerate() {
+            column.iter_mut().for_each(|c| *c = Complex::new(0.0, 0.0));
+            for (n, sample) in chirp.iter().enumerate() {
+                column[n] = sample * range_window[n];
+            }
+            fft_in_place(&mut column);
+            for (r, value) in column.iter().enumerate() {
+                spectrum[[r, m]] = *value;
+            }
+        }
+
+        // Doppler FFT per range bin
+        for r in 0..range_fft_len {
+            row.iter_mut().for_each(|c| *c = Complex::new(0.0, 0.0));
+            for m in 0..num_chirps {
+                row[m] = spectrum[[r, m]] * doppler_window[m];
+            }
+            fft_in_place(&mut row);
+            fftshift(&mut row);
+            for (d, value) in row.iter().enumerate() {
+                output[[r, d, k]] = value / gain;
+            }
+        }
+    }
+
+    output
+}
+
+/// Turn a raw IQ cube (fast time × slow time × channel) into a range-Doppler magnitude map.
+///
+/// Channels of [`range_doppler_cube`] are combined non-coherently, so a
+/// unit-amplitude return peaks near 1 per channel.
+pub fn range_doppler_map(
+    cube: &Array3<Complex<f64>>,
+    params: &FmcwParams,
+    config: &ProcessingConfig,
+) -> RangeDopplerMap {
+    let spectra = range_doppler_cube(cube, config);
+    let (range_fft_len, doppler_fft_len, num_channels) = spectra.dim();
+    let channels = num_channels.max(1) as f64;
+    let magnitude = spectra.map_axis(Axis(2), |cells| {
+        (cells.iter().map(|c| c.norm_sqr()).sum::<f64>() / channels).sqrt()
+    });
+
+    RangeDopplerMap {
+        magnitude,
+        range_bin_m: params.sample_rate_hz / range_fft_len as f64 * crate::constants::C
+            / (2.0 * params.chirp_slope()),
+        velocity_bin_m_s: params.wavelength_m()
+            / (2.0 * doppler_fft_len as f64 * params.chirp_duration_s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fft::fft_padded;
+    use crate::signal::simulate_iq_cube;
+    use crate::types::TargetPosition;
+
+    fn params() -> FmcwParams {
+        FmcwParams {
+            carrier_freq_hz: 9.5e9,
+            bandwidth_hz: 5e6,
+            chirp_duration_s: 100e-6,
+            chirps_per_frame: 32,
+            sample_rate_hz: 2.56e6,
+            num_rx: 2,
+        }
+    }
+
+    fn target(range_m: f64, vel_m_s: f64) -> TargetPosition {
+        TargetPosition {
+            range_m,
+            vel_m_s,
+            rcs: 1.0,
+            ..TargetPosition::default()
+        }
+    }
+
+    /// Highest sidelobe (dB below peak) of a window's heavily zero-padded spectrum
+    fn peak_sidelobe_db(window: &[f64]) -> f64 {
+        let input: Vec<Complex<f64>> = window.iter().map(|&w| Complex::new(w, 0.0)).collect();
+        let spectrum: Vec<f64> = fft_padded(&input, 4096).iter().map(|c| c.norm()).collect();
+        let peak = spectrum[0];
+        // Walk down the mainlobe to its first null, then take the largest remaining value
+        let mut k = 1;
+        while k < spectrum.len() / 2 && spectrum[k + 1] < spectrum[k] {
+            k += 1;
+        }
+        let sidelobe = spectrum[k..spectrum.len() / 2]
+            .iter()
+            .cloned()
+            .fold(0.0, f64::max);
+        20.0 * (sidelobe / peak).log10()
+    }
+
+    #[test]
+    fn test_window_shapes() {
+        let hann = WindowFunction::Hann.coefficients(9);
+        assert!(hann[0].abs() < 1e-12 && (hann[4] - 1.0).abs() < 1e-12);
+
+        let hamming = WindowFunction::Hamming.coefficients(9);
+        assert!((hamming[0] - 0.08).abs() < 1e-12);
+
+        let blackman = WindowFunction::Blackman.coefficients(9);
+        assert!(blackman[0].abs() < 1e-12 && (blackman[4] - 1.0).abs() < 1e-12);
+
+        for len in [16, 17] {
+            let cheb = WindowFunction::Chebyshev { sidelobe_db: 60.0 }.coefficients(len);
+            assert_eq!(cheb.len(), len);
+            for i in 0..len {
+                assert!((cheb[i] - cheb[len - 1 - i]).abs() < 1e-9, "not symmetric");
+            }
+            assert!((cheb.iter().cloned().fold(f64::MIN, f64::max) - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_window_sidelobe_levels() {
+        assert!(peak_sidelobe_db(&WindowFunction::Rectangular.coefficients(64)) > -14.0);
+        assert!(peak_sidelobe_db(&WindowFunction::Hann.coefficients(64)) < -31.0);
+        assert!(peak_sidelobe_db(&WindowFunction::Hamming.coefficients(64)) < -41.0);
+        assert!(peak_sidelobe_db(&WindowFunction::Blackman.coefficients(64)) < -57.0);
+
+        let cheb = WindowFunction::Chebyshev { sidelobe_db: 80.0 }.coefficients(64);
+        let level = peak_sidelobe_db(&cheb);
+        assert!(
+            (level + 80.0).abs() < 1.0,
+            "Chebyshev sidelobes at {level} dB"
+        );
+    }
+
+    #[test]
+    fn test_peak_matches_target_range_and_velocity() {
+        let params = params();
+        let cube = simulate_iq_cube(&params, &[target(4_500.0, 14.79)]);
+        let map = range_doppler_map(&cube, &params, &ProcessingConfig::default());
+
+        assert_eq!(map.num_range_bins(), 256);
+        assert_eq!(map.num_doppler_bins(), 32);
+
+        let peak = map.peak();
+        assert!((peak.range_m - 4_500.0).abs() <= map.range_bin_m);
+        assert!((peak.velocity_m_s - 14.79).abs() <= map.velocity_bin_m_s);
+        assert!(peak.magnitude > 0.8 && peak.magnitude <= 1.05);
+    }
+
+    #[test]
+    fn test_approaching_target_has_negative_velocity() {
+        let params = params();
+        let cube = simulate_iq_cube(&params, &[target(3_000.0, -20.0)]);
+        let map = range_doppler_map(&cube, &params, &ProcessingConfig::default());
+        let peak = map.peak();
+        assert!((peak.velocity_m_s + 20.0).abs() <= map.velocity_bin_m_s);
+    }
+
+    #[test]
+    fn test_zero_padding_refines_bins() {
+        let params = params();
+        let cube = simulate_iq_cube(&params, &[target(4_321.0, 7.3)]);
+        let config = ProcessingConfig {
+            range_padding: 4,
+            doppler_padding: 4,
+            ..ProcessingConfig::default()
+        };
+        let map = range_doppler_map(&cube, &params, &config);
+
+        assert_eq!(map.num_range_bins(), 1024);
+        assert_eq!(map.num_doppler_bins(), 128);
+        assert!((map.range_bin_m - params.range_resolution_m() / 4.0).abs() < 0.5);
+
+        let peak = map.peak();
+        assert!((peak.range_m - 4_321.0).abs() <= map.range_bin_m);
+        assert!((peak.velocity_m_s - 7.3).abs() <= map.velocity_bin_m_s);
+    }
+
+    #[test]
+    fn test_two_targets_resolved() {
+        let params = params();
+        let cube = simulate_iq_cube(&params, &[target(2_000.0, 10.0), target(5_000.0, -15.0)]);
+        let map = range_doppler_map(&cube, &params, &ProcessingConfig::default());
+
+        let near = map.cell(
+            (2_000.0 / map.range_bin_m).round() as usize,
+            (10.0 / map.velocity_bin_m_s).round() as usize + map.num_doppler_bins() / 2,
+        );
+        let far = map.cell(
+            (5_000.0 / map.range_bin_m).round() as usize,
+            map.num_doppler_bins() / 2 - (15.0 / map.velocity_bin_m_s).round() as usize,
+        );
+        let empty = map.cell(100, 0);
+        assert!(near.magnitude > 100.0 * empty.magnitude.max(1e-12));
+        assert!(far.magnitude > 100.0 * empty.magnitude.max(1e-12));
+    }
+}
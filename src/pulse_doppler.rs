@@ -0,0 +1,634 @@
+use crate::cfar::Detection;
+use crate::constants::C;
+use crate::fft::{fft_in_place, fftshift};
+use crate::processing::{ProcessingConfig, RangeDopplerMap};
+use crate::types::TargetPosition;
+use crate::waveform::Waveform;
+use ndarray::Array2;
+use num_complex::Complex;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use utoipa::ToSchema;
+
+/// Pulse-Doppler radar waveform and receiver parameters
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PulseDopplerParams {
+    /// Carrier frequency in Hz
+    pub carrier_freq_hz: f64,
+    /// Transmitted pulse length in seconds
+    pub pulse_width_s: f64,
+    /// Linear-FM sweep within each pulse in Hz (sets the compressed range resolution)
+    pub bandwidth_hz: f64,
+    /// Complex (IQ) ADC sample rate in Hz
+    pub sample_rate_hz: f64,
+    /// PRFs cycled from one coherent processing interval (CPI) to the next, in Hz
+    pub prf_set_hz: Vec<f64>,
+    /// Pulses per CPI
+    pub pulses_per_cpi: usize,
+}
+
+impl PulseDopplerParams {
+    /// Carrier wavelength in meters
+    pub fn wavelength_m(&self) -> f64 {
+        C / self.carrier_freq_hz
+    }
+
+    /// Samples per pulse repetition interval; the PRI is rounded to a whole sample
+    pub fn samples_per_pri(&self, prf_hz: f64) -> usize {
+        (self.sample_rate_hz / prf_hz).round().max(1.0) as usize
+    }
+
+    /// Samples in one transmitted pulse
+    pub fn samples_per_pulse(&self) -> usize {
+        (self.pulse_width_s * self.sample_rate_hz).round().max(1.0) as usize
+    }
+
+    /// Meters spanned by one fast-time sample
+    pub fn range_bin_m(&self) -> f64 {
+        C / (2.0 * self.sample_rate_hz)
+    }
+
+    /// Range resolution after pulse compression in meters
+    pub fn range_resolution_m(&self) -> f64 {
+        C / (2.0 * self.bandwidth_hz)
+    }
+
+    /// Pulse-compression gain (time-bandwidth product)
+    pub fn compression_gain(&self) -> f64 {
+        self.pulse_width_s * self.bandwidth_hz
+    }
+
+    /// Unambiguous range at a PRF in meters: echoes beyond it fold back
+    pub fn unambiguous_range_m(&self, prf_hz: f64) -> f64 {
+        self.samples_per_pri(prf_hz) as f64 * self.range_bin_m()
+    }
+
+    /// Unambiguous radial velocity at a PRF: apparent velocities lie in `[-v, v)`
+    pub fn unambiguous_velocity_m_s(&self, prf_hz: f64) -> f64 {
+        self.wavelength_m() * prf_hz / 4.0
+    }
+
+    /// Baseband linear-FM pulse, sweeping `bandwidth_hz` about zero over the pulse
+    pub fn transmit_pulse(&self) -> Vec<Complex<f64>> {
+        Waveform::Lfm {
+            bandwidth_hz: self.bandwidth_hz,
+            pulse_width_s: self.pulse_width_s,
+        }
+        .samples(self.sample_rate_hz)
+        .unwrap_or_default()
+    }
+}
+
+impl Default for PulseDopplerParams {
+    /// X-band medium-PRF radar with a 3-PRF set (PRIs of 1000, 1250 and 800 samples)
+    fn default() -> Self {
+        Self {
+            carrier_freq_hz: 9.5e9,
+            pulse_width_s: 10e-6,
+            bandwidth_hz: 5e6,
+            sample_rate_hz: 10e6,
+            prf_set_hz: vec![10_000.0, 8_000.0, 12_500.0],
+            pulses_per_cpi: 64,
+        }
+    }
+}
+
+/// Received fast-time × pulse samples of one CPI at `prf_hz`.
+///
+/// The pulse train is taken to have been running before the CPI, so echoes
+/// from beyond the unambiguous range fold into every PRI. Each target is
+/// stop-and-hop: its delay is fixed during a pulse and its pulse-to-pulse
+/// phase follows its radial motion with the same sign as the FMCW simulator
+/// (positive `vel_m_s` moves away). Transmitter eclipsing is not modelled.
+pub fn simulate_cpi(
+    params: &PulseDopplerParams,
+    prf_hz: f64,
+    targets: &[TargetPosition],
+) -> Array2<Complex<f64>> {
+    let num_samples = params.samples_per_pri(prf_hz);
+    let num_pulses = params.pulses_per_cpi;
+    let pri_s = num_samples as f64 / params.sample_rate_hz;
+    let pulse = params.transmit_pulse();
+    let fc = params.carrier_freq_hz;
+    let mut data = Array2::<Complex<f64>>::zeros((num_samples, num_pulses));
+
+    for target in targets {
+        let amplitude = target.rcs.max(0.0).sqrt();
+        for m in 0..num_pulses {
+            for n in 0..num_samples {
+                let t = m as f64 * pri_s + n as f64 / params.sample_rate_hz;
+                let tau0 = 2.0 * target.range_m / C;
+                // Pulse whose echo is arriving now (may precede the CPI)
+                let source = ((t - tau0) / pri_s).floor();
+                let tau = 2.0 * (target.range_m + target.vel_m_s * source * pri_s) / C;
+                let offset = t - source * pri_s - tau;
+                let k = (offset * params.sample_rate_hz).round();
+                if k < 0.0 || k >= pulse.len() as f64 {
+                    continue;
+                }
+                let doppler = Complex::from_polar(amplitude, 2.0 * PI * fc * tau);
+                data[[n, m]] += pulse[k as usize] * doppler;
+            }
+        }
+    }
+    data
+}
+
+/// Pulse-compress and Doppler-process one CPI into a range-Doppler map.
+///
+/// Each PRI is correlated circularly with the transmit pulse, tapered by
+/// `config.range_window` to lower range sidelobes; circular correlation
+/// matches the steady-state pulse train. The Doppler FFT uses
+/// `config.doppler_window` and `config.doppler_padding`. A unit-amplitude
+/// echo peaks near 1, and map cells cover apparent (folded) range and velocity.
+pub fn process_cpi(
+    params: &PulseDopplerParams,
+    prf_hz: f64,
+    data: &Array2<Complex<f64>>,
+    config: &ProcessingConfig,
+) -> RangeDopplerMap {
+    let (num_samples, num_pulses) = data.dim();
+    let taper = config.range_window.coefficients(params.samples_per_pulse());
+    let replica: Vec<Complex<f64>> = params
+        .transmit_pulse()
+        .iter()
+        .zip(&taper)
+        .map(|(s, w)| s.conj() * *w)
+        .collect();
+    let doppler_window = config.doppler_window.coefficients(num_pulses);
+    let doppler_fft_len = (num_pulses * config.doppler_padding.max(1)).next_power_of_two();
+    let gain = taper.iter().sum::<f64>() * doppler_window.iter().sum::<f64>();
+
+    let mut magnitude = Array2::<f64>::zeros((num_samples, doppler_fft_len));
+    let mut compressed = Array2::<Complex<f64>>::zeros((num_samples, num_pulses));
+    for (m, pulse) in data.columns().into_iter().enumerate() {
+        for n in 0..num_samples {
+            compressed[[n, m]] = replica
+                .iter()
+                .enumerate()
+                .map(|(k, r)| pulse[(n + k) % num_samples] * r)
+                .sum();
+        }
+    }
+
+    let mut row = vec![Complex::new(0.0, 0.0); doppler_fft_len];
+    for n in 0..num_samples {
+        row.iter_mut().for_each(|c| *c = Complex::new(0.0, 0.0));
+        for m in 0..num_pulses {
+            row[m] = compressed[[n, m]] * doppler_window[m];
+        }
+        fft_in_place(&mut row);
+        fftshift(&mut row);
+        for (d, value) in row.iter().enumerate() {
+            magnitude[[n, d]] = value.norm() / gain;
+        }
+    }
+
+    RangeDopplerMap {
+        magnitude,
+        range_bin_m: params.range_bin_m(),
+        velocity_bin_m_s: params.wavelength_m() * prf_hz / (2.0 * doppler_fft_len as f64),
+    }
+}
+
+/// Detections from one CPI, with the PRF they were measured at
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PrfDwell {
+    pub prf_hz: f64,
+    /// Detections in apparent (folded) range and velocity
+    pub detections: Vec<Detection>,
+}
+
+/// Search limits and matching tolerances for ambiguity resolution
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResolverConfig {
+    /// Largest true range considered, in meters
+    pub max_range_m: f64,
+    /// Largest true speed considered, in meters per second
+    pub max_velocity_m_s: f64,
+    /// Largest disagreement in unfolded range between PRFs
+    pub range_tolerance_m: f64,
+    /// Largest disagreement in unfolded velocity between PRFs
+    pub velocity_tolerance_m_s: f64,
+    /// Number of PRFs that must agree (M in an M-of-N PRF scheme)
+    pub min_prfs: usize,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            max_range_m: 60_000.0,
+            max_velocity_m_s: 150.0,
+            range_tolerance_m: 40.0,
+            velocity_tolerance_m_s: 4.0,
+            min_prfs: 3,
+        }
+    }
+}
+
+/// Target with unfolded range and velocity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ResolvedTarget {
+    /// True range in meters
+    pub range_m: f64,
+    /// True radial velocity in meters per second (positive = moving away)
+    pub velocity_m_s: f64,
+    /// Number of PRFs whose detections agreed
+    pub num_prfs: usize,
+    /// Mean detection SNR over the agreeing PRFs, in dB
+    pub snr_db: f64,
+}
+
+/// Candidate unfolding of one detection: (range, velocity, dwell, detection)
+type Candidate = (f64, f64, usize, usize);
+
+/// Detections that are the strongest within ±2 range and Doppler bins of
+/// their dwell, so each target's spread of threshold crossings counts once
+fn local_peaks(detections: &[Detection]) -> Vec<Detection> {
+    detections
+        .iter()
+        .filter(|d| {
+            !detections.iter().any(|other| {
+                other.range_bin.abs_diff(d.range_bin) <= 2
+                    && other.doppler_bin.abs_diff(d.doppler_bin) <= 2
+                    && other.snr_db > d.snr_db
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Unfold ambiguous detections from several PRFs by clustering.
+///
+/// Each dwell is first reduced to its local peaks. Every peak is expanded into all its (range, velocity) aliases within
+/// the search limits. Each alias seeds a cluster that takes, from every other
+/// PRF, the detection whose nearest alias agrees within tolerance. Clusters
+/// spanning at least `min_prfs` PRFs are accepted best first, each detection
+/// used once; near-duplicates from neighbouring cells of the same target are
+/// merged.
+pub fn resolve_ambiguities(
+    params: &PulseDopplerParams,
+    dwells: &[PrfDwell],
+    config: &ResolverConfig,
+) -> Vec<ResolvedTarget> {
+    let dwells: Vec<PrfDwell> = dwells
+        .iter()
+        .map(|dwell| PrfDwell {
+            prf_hz: dwell.prf_hz,
+            detections: local_peaks(&dwell.detections),
+        })
+        .collect();
+    let intervals: Vec<(f64, f64)> = dwells
+        .iter()
+        .map(|dwell| {
+            (
+                params.unambiguous_range_m(dwell.prf_hz),
+                2.0 * params.unambiguous_velocity_m_s(dwell.prf_hz),
+            )
+        })
+        .collect();
+
+    // Nearest alias of a detection to a point, and its normalised distance
+    let nearest = |dwell: usize, detection: &Detection, range_m: f64, velocity_m_s: f64| {
+        let (range_period, velocity_period) = intervals[dwell];
+        let r = detection.range_m
+            + ((range_m - detection.range_m) / range_period).round() * range_period;
+        let v = detection.velocity_m_s
+            + ((velocity_m_s - detection.velocity_m_s) / velocity_period).round() * velocity_period;
+        let distance = ((r - range_m) / config.range_tolerance_m)
+            .abs()
+            .max(((v - velocity_m_s) / config.velocity_tolerance_m_s).abs());
+        (r, v, distance)
+    };
+
+    let mut clusters: Vec<(Vec<Candidate>, f64)> = Vec::new();
+    for (i, dwell) in dwells.iter().enumerate() {
+        let (range_period, velocity_period) = intervals[i];
+        for (j, detection) in dwell.detections.iter().enumerate() {
+            let range_folds = (config.max_range_m / range_period).ceil() as i64;
+            let velocity_folds = (config.max_velocity_m_s / velocity_period).ceil() as i64 + 1;
+            for a in 0..range_folds {
+                let range_m = detection.range_m + a as f64 * range_period;
+                if range_m > config.max_range_m {
+                    continue;
+                }
+                for b in -velocity_folds..=velocity_folds {
+                    let velocity_m_s = detection.velocity_m_s + b as f64 * velocity_period;
+                    if velocity_m_s.abs() > config.max_velocity_m_s {
+                        continue;
+                    }
+                    let mut members = vec![(range_m, velocity_m_s, i, j)];
+                    let mut cost = 0.0;
+                    for (k, other) in dwells.iter().enumerate().filter(|&(k, _)| k != i) {
+                        let best = other
+                            .detections
+                            .iter()
+                            .enumerate()
+                            .map(|(l, d)| (l, nearest(k, d, range_m, velocity_m_s)))
+                            .filter(|(_, (_, _, distance))| *distance <= 1.0)
+                            .min_by(|x, y| x.1 .2.total_cmp(&y.1 .2));
+                        if let Some((l, (r, v, distance))) = best {
+                            members.push((r, v, k, l));
+                            cost += distance;
+                        }
+                    }
+                    if members.len() >= config.min_prfs.max(1) {
+                        clusters.push((members, cost));
+                    }
+                }
+            }
+        }
+    }
+
+    // Most PRFs first, then tightest agreement, then strongest
+    let snr = |members: &[Candidate]| {
+        members
+            .iter()
+            .map(|&(_, _, k, l)| dwells[k].detections[l].snr_db)
+            .sum::<f64>()
+            / members.len() as f64
+    };
+    clusters.sort_by(|(a, cost_a), (b, cost_b)| {
+        b.len()
+            .cmp(&a.len())
+            .then(cost_a.total_cmp(cost_b))
+            .then(snr(b).total_cmp(&snr(a)))
+    });
+
+    let mut used = std::collections::HashSet::new();
+    let mut resolved: Vec<ResolvedTarget> = Vec::new();
+    for (members, _) in clusters {
+        if members.iter().any(|&(_, _, k, l)| used.contains(&(k, l))) {
+            continue;
+        }
+        members.iter().for_each(|&(_, _, k, l)| {
+            used.insert((k, l));
+        });
+        let n = members.len() as f64;
+        let target = ResolvedTarget {
+            range_m: members.iter().map(|m| m.0).sum::<f64>() / n,
+            velocity_m_s: members.iter().map(|m| m.1).sum::<f64>() / n,
+            num_prfs: members.len(),
+            snr_db: snr(&members),
+        };
+        let duplicate = resolved.iter().any(|r| {
+            (r.range_m - target.range_m).abs() <= 2.0 * config.range_tolerance_m
+                && (r.velocity_m_s - target.velocity_m_s).abs()
+                    <= 2.0 * config.velocity_tolerance_m_s
+        });
+        if !duplicate {
+            resolved.push(target);
+        }
+    }
+    resolved
+}
+
+/// Smallest non-negative `x` with `x ≡ residues[i] (mod moduli[i])` for all
+/// `i`, together with the lcm of the moduli; `None` if the congruences are
+/// inconsistent (possible when moduli share factors)
+pub fn chinese_remainder(residues: &[u64], moduli: &[u64]) -> Option<(u64, u64)> {
+    fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+        if b == 0 {
+            (a, 1, 0)
+        } else {
+            let (g, x, y) = extended_gcd(b, a % b);
+            (g, y, x - (a / b) * y)
+        }
+    }
+
+    let (mut x, mut modulus) = (0i128, 1i128);
+    for (&r, &m) in residues.iter().zip(moduli) {
+        let (r, m) = (r as i128 % m as i128, m as i128);
+        if m == 0 {
+            return None;
+        }
+        let (g, p, _) = extended_gcd(modulus, m);
+        if (r - x) % g != 0 {
+            return None;
+        }
+        let step = m / g;
+        let k = ((r - x) / g % step * p % step + step) % step;
+        x += modulus * k;
+        modulus *= step;
+        x = x.rem_euclid(modulus);
+    }
+    Some((x as u64, modulus as u64))
+}
+
+/// Most PRFs [`resolve_range_crt`] unfolds at once
+pub const MAX_CRT_PRFS: usize = 8;
+
+/// Unfold a single target's range from its apparent ranges at several PRFs
+/// with the Chinese remainder theorem on range-sample indices.
+///
+/// Measured indices may be off by one sample, so residues are also tried with
+/// ±1 corrections, fewest corrections first; the first solution within
+/// `max_range_m` is returned. `None` for more than [`MAX_CRT_PRFS`] PRFs or a
+/// PRF that is not positive or exceeds the sample rate.
+pub fn resolve_range_crt(
+    params: &PulseDopplerParams,
+    apparent: &[(f64, f64)],
+    max_range_m: f64,
+) -> Option<f64> {
+    // A PRI shorter than a sample leaves no residue to unfold, and the offset
+    // search below grows as 3^n
+    if apparent.len() > MAX_CRT_PRFS
+        || apparent
+            .iter()
+            .any(|&(prf, _)| !(prf > 0.0 && prf <= params.sample_rate_hz))
+    {
+        return None;
+    }
+
+    let bin = params.range_bin_m();
+    let moduli: Vec<u64> = apparent
+        .iter()
+        .map(|&(prf, _)| params.samples_per_pri(prf) as u64)
+        .collect();
+    let measured: Vec<i64> = apparent
+        .iter()
+        .map(|&(_, range_m)| (range_m / bin).round() as i64)
+        .collect();
+
+    let combinations = 3usize.pow(apparent.len() as u32);
+    let mut offsets: Vec<Vec<i64>> = (0..combinations)
+        .map(|mut c| {
+            (0..apparent.len())
+                .map(|_| {
+                    let digit = (c % 3) as i64 - 1;
+                    c /= 3;
+                    digit
+                })
+                .collect()
+        })
+        .collect();
+    offsets.sort_by_key(|o| o.iter().map(|d| d.abs()).sum::<i64>());
+
+    offsets.iter().find_map(|offset| {
+        let residues: Vec<u64> = measured
+            .iter()
+            .zip(offset)
+            .zip(&moduli)
+            .map(|((m, o), n)| (m + o).rem_euclid(*n as i64) as u64)
+            .collect();
+        let (x, _) = chinese_remainder(&residues, &moduli)?;
+        let range_m = x as f64 * bin;
+        (range_m <= max_range_m).then_some(range_m)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfar::{cfar_2d, CfarConfig};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, Normal};
+
+    fn target(range_m: f64, vel_m_s: f64) -> TargetPosition {
+        TargetPosition {
+            range_m,
+            vel_m_s,
+            rcs: 1.0,
+            ..TargetPosition::default()
+        }
+    }
+
+    #[test]
+    fn test_unambiguous_intervals() {
+        let params = PulseDopplerParams::default();
+        assert_eq!(params.samples_per_pri(10_000.0), 1000);
+        assert!((params.unambiguous_range_m(10_000.0) - C / 20_000.0).abs() < 1e-6);
+        let lambda = params.wavelength_m();
+        assert!((params.unambiguous_velocity_m_s(8_000.0) - lambda * 2_000.0).abs() < 1e-9);
+        assert!((params.compression_gain() - 50.0).abs() < 1e-9);
+        assert!((params.range_resolution_m() - 29.98).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_pulse_compression_peak() {
+        let params = PulseDopplerParams::default();
+        let prf = 10_000.0;
+        let data = simulate_cpi(&params, prf, &[target(6_000.0, 30.0)]);
+        let map = process_cpi(&params, prf, &data, &ProcessingConfig::default());
+        let peak = map.peak();
+        assert!((peak.range_m - 6_000.0).abs() <= map.range_bin_m);
+        assert!((peak.velocity_m_s - 30.0).abs() <= map.velocity_bin_m_s);
+        assert!(
+            (peak.magnitude - 1.0).abs() < 0.2,
+            "peak {}",
+            peak.magnitude
+        );
+        // Compressed (Hann-tapered) mainlobe is a few samples wide, not the 100-sample pulse
+        let row = map.magnitude.column(peak.doppler_bin);
+        let wide = row.iter().filter(|&&m| m > 0.5 * peak.magnitude).count();
+        assert!(wide <= 6, "{wide} samples above half peak");
+    }
+
+    #[test]
+    fn test_far_fast_target_folds() {
+        let params = PulseDopplerParams::default();
+        let prf = 10_000.0;
+        let ru = params.unambiguous_range_m(prf);
+        let vu = params.unambiguous_velocity_m_s(prf);
+        let data = simulate_cpi(&params, prf, &[target(ru + 4_000.0, vu + 20.0)]);
+        let peak = process_cpi(&params, prf, &data, &ProcessingConfig::default()).peak();
+        assert!((peak.range_m - 4_000.0).abs() <= 15.0, "{peak:?}");
+        assert!((peak.velocity_m_s - (20.0 - vu)).abs() <= 2.5, "{peak:?}");
+    }
+
+    #[test]
+    fn test_chinese_remainder() {
+        assert_eq!(chinese_remainder(&[2, 3, 2], &[3, 5, 7]), Some((23, 105)));
+        // Shared factors: consistent and inconsistent systems
+        assert_eq!(chinese_remainder(&[3, 9], &[6, 10]), Some((9, 30)));
+        assert_eq!(chinese_remainder(&[1, 2], &[4, 6]), None);
+    }
+
+    #[test]
+    fn test_crt_unfolds_range_beyond_every_prf() {
+        let params = PulseDopplerParams::default();
+        let truth = 41_000.0;
+        let bin = params.range_bin_m();
+        let apparent: Vec<(f64, f64)> = params
+            .prf_set_hz
+            .iter()
+            .enumerate()
+            .map(|(i, &prf)| {
+                // One PRF measured a sample late
+                let jitter = if i == 1 { bin } else { 0.0 };
+                (prf, truth % params.unambiguous_range_m(prf) + jitter)
+            })
+            .collect();
+        let unfolded = resolve_range_crt(&params, &apparent, 60_000.0).unwrap();
+        assert!((unfolded - truth).abs() <= 2.0 * bin, "unfolded {unfolded}");
+    }
+
+    #[test]
+    fn test_crt_rejects_degenerate_prf_sets() {
+        let params = PulseDopplerParams::default();
+        let fs = params.sample_rate_hz;
+        for prf in [0.0, -10_000.0, 2.0 * fs, f64::NAN] {
+            let apparent = [(prf, 1_000.0), (10_000.0, 1_000.0)];
+            assert_eq!(
+                resolve_range_crt(&params, &apparent, 60_000.0),
+                None,
+                "{prf}"
+            );
+        }
+
+        // One PRF past the cap instead of 3^9 offset combinations
+        let apparent: Vec<(f64, f64)> = (0..=MAX_CRT_PRFS)
+            .map(|i| (8_000.0 + 500.0 * i as f64, 1_000.0))
+            .collect();
+        assert_eq!(resolve_range_crt(&params, &apparent, 60_000.0), None);
+        assert!(resolve_range_crt(&params, &apparent[..MAX_CRT_PRFS], 60_000.0).is_some());
+    }
+
+    #[test]
+    fn test_multi_prf_resolves_simulated_targets() {
+        let params = PulseDopplerParams::default();
+        // Beyond every unambiguous range/velocity, and one inside all of them
+        let targets = [target(25_000.0, 120.0), target(8_000.0, -30.0)];
+        let mut rng = StdRng::seed_from_u64(31);
+        // Unit noise per component: about 28 dB after compression and Doppler gain
+        let noise = Normal::new(0.0, 1.0).unwrap();
+        let dwells: Vec<PrfDwell> = params
+            .prf_set_hz
+            .iter()
+            .map(|&prf| {
+                let mut data = simulate_cpi(&params, prf, &targets);
+                data.mapv_inplace(|c| {
+                    c + Complex::new(noise.sample(&mut rng), noise.sample(&mut rng))
+                });
+                let map = process_cpi(&params, prf, &data, &ProcessingConfig::default());
+                PrfDwell {
+                    prf_hz: prf,
+                    detections: cfar_2d(&map, &CfarConfig::default()),
+                }
+            })
+            .collect();
+        assert!(dwells.iter().all(|d| !d.detections.is_empty()));
+
+        let resolved = resolve_ambiguities(&params, &dwells, &ResolverConfig::default());
+        for truth in &targets {
+            assert!(
+                resolved
+                    .iter()
+                    .any(|r| (r.range_m - truth.range_m).abs() < 40.0
+                        && (r.velocity_m_s - truth.vel_m_s).abs() < 4.0),
+                "{truth:?} not in {resolved:?}"
+            );
+        }
+        assert_eq!(resolved.len(), targets.len(), "ghosts: {resolved:?}");
+
+        // Detections seen at a single PRF only become ghosts when agreement
+        // across PRFs is not required
+        let single = ResolverConfig {
+            min_prfs: 1,
+            ..ResolverConfig::default()
+        };
+        let unfiltered = resolve_ambiguities(&params, &dwells, &single);
+        assert!(unfiltered.len() > targets.len(), "{unfiltered:?}");
+    }
+}
@@ -1,14 +1,84 @@
-use crate::handlers::{analyze_handler, websocket_handler};
+use crate::evaluation::EvaluationStore;
+use crate::geo::RadarSite;
+use crate::handlers::{
+    ambiguity_handler, analyze_handler, evaluate_run_handler, evaluation_handler, horizon_handler,
+    link_budget_handler, roc_handler, smoothed_track_handler, websocket_handler,
+};
 use crate::observability::{health_handler, metrics_handler, AppMetrics};
+use crate::smoothing::TrackHistoryStore;
+use axum::extract::FromRef;
 use axum::routing::{get, post};
 use axum::Router;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 
-pub fn create_router(metrics: Arc<AppMetrics>) -> Router {
+/// State shared by the handlers
+#[derive(Debug, Clone)]
+pub struct AppState {
+    pub metrics: Arc<AppMetrics>,
+    /// Track histories recorded by the WebSocket tracking loop
+    pub track_history: Arc<RwLock<TrackHistoryStore>>,
+    /// Scores of the WebSocket tracking loop against ground truth
+    pub evaluation: Arc<RwLock<EvaluationStore>>,
+    /// Radar site for geodetic coordinates; radar-relative only when absent
+    pub site: Option<RadarSite>,
+}
+
+impl AppState {
+    pub fn new(metrics: Arc<AppMetrics>) -> Self {
+        Self {
+            metrics,
+            track_history: Arc::new(RwLock::new(TrackHistoryStore::new())),
+            evaluation: Arc::new(RwLock::new(EvaluationStore::new())),
+            site: None,
+        }
+    }
+
+    /// Place the radar at a geodetic site
+    pub fn with_site(mut self, site: RadarSite) -> Self {
+        self.site = Some(site);
+        self
+    }
+}
+
+impl FromRef<AppState> for Arc<AppMetrics> {
+    fn from_ref(state: &AppState) -> Self {
+        state.metrics.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<RwLock<TrackHistoryStore>> {
+    fn from_ref(state: &AppState) -> Self {
+        state.track_history.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<RwLock<EvaluationStore>> {
+    fn from_ref(state: &AppState) -> Self {
+        state.evaluation.clone()
+    }
+}
+
+impl FromRef<AppState> for Option<RadarSite> {
+    fn from_ref(state: &AppState) -> Self {
+        state.site
+    }
+}
+
+pub fn create_router(state: AppState) -> Router {
     Router::new()
         .route("/api/analyze", post(analyze_handler))
+        .route("/api/link-budget", post(link_budget_handler))
+        .route("/api/performance/roc", post(roc_handler))
+        .route("/api/waveforms/ambiguity", post(ambiguity_handler))
+        .route("/api/coverage/horizon", get(horizon_handler))
+        .route("/api/tracks/{id}/smoothed", get(smoothed_track_handler))
+        .route(
+            "/api/evaluation",
+            get(evaluation_handler).post(evaluate_run_handler),
+        )
         .route("/ws", get(websocket_handler))
         .route("/health", get(health_handler))
         .route("/metrics", get(metrics_handler))
-        .with_state(metrics)
+        .with_state(state)
 }
@@ -0,0 +1,541 @@
+use crate::aoa::{estimate_angles, simulate_snapshots, AngleGrid, ArrayConfig, ArraySource};
+use crate::cfar::{cfar_2d, CfarConfig, Detection};
+use crate::clutter::{clutter_map, ClutterConfig};
+use crate::constants::C;
+use crate::error::{AppError, AppResult};
+use crate::horizon::{default_horizon, RadarHorizon};
+use crate::link_budget::snr_db;
+use crate::processing::RangeDopplerMap;
+use crate::signal::FmcwParams;
+use crate::tbd::{TbdConfig, TrackBeforeDetect};
+use crate::types::{RadarConfig, TargetPosition};
+use ndarray::Array2;
+use num_complex::Complex;
+use rand::Rng;
+use rand_distr::{Distribution, StandardNormal};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Environment and detector settings for a simulated scene
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ScenarioConfig {
+    /// Radar used to scale target and clutter returns against thermal noise
+    #[serde(default)]
+    pub radar: RadarConfig,
+    /// Background clutter
+    #[serde(default)]
+    pub clutter: ClutterConfig,
+    /// Detector run over each simulated range-Doppler map
+    #[serde(default)]
+    pub cfar: CfarConfig,
+    /// Receive array used to estimate each detection's direction of arrival
+    #[serde(default)]
+    pub array: Option<ArrayConfig>,
+    /// Declare targets by track-before-detect over a sliding window of
+    /// frames instead of single-frame CFAR; such detections carry no angle
+    #[serde(default)]
+    pub tbd: Option<TbdConfig>,
+    /// Earth curvature and refraction hiding low, distant targets; also
+    /// applied to the session's tracker measurements. A flat earth when `null`.
+    #[serde(default = "default_horizon")]
+    pub horizon: Option<RadarHorizon>,
+}
+
+impl Default for ScenarioConfig {
+    fn default() -> Self {
+        Self {
+            radar: RadarConfig::default(),
+            clutter: ClutterConfig::default(),
+            cfar: CfarConfig::default(),
+            array: None,
+            tbd: None,
+            horizon: default_horizon(),
+        }
+    }
+}
+
+impl ScenarioConfig {
+    /// Reject a scene that cannot be simulated on the range-Doppler grid of
+    /// `params`, or would be too costly to simulate on every dwell of a session
+    pub fn validate(&self, params: &FmcwParams) -> AppResult<()> {
+        self.radar.validate()?;
+        self.cfar.validate()?;
+        let (num_range, num_doppler, _, _) = bin_sizes(params);
+        let fits = self
+            .cfar
+            .window_span()
+            .is_some_and(|(range, doppler)| range < num_range && doppler <= num_doppler);
+        if !fits {
+            return Err(AppError::InvalidInput(format!(
+                "CFAR window larger than the {}x{} range-Doppler map",
+                num_range, num_doppler
+            )));
+        }
+        self.clutter.validate()?;
+        if let Some(array) = &self.array {
+            array.validate()?;
+        }
+        if let Some(horizon) = &self.horizon {
+            horizon.validate()?;
+        }
+        if let Some(tbd) = &self.tbd {
+            tbd.validate(num_range.saturating_mul(num_doppler))?;
+        }
+        Ok(())
+    }
+
+    /// Whether a target is above the radar horizon
+    pub fn is_visible(&self, target: &TargetPosition) -> bool {
+        self.horizon
+            .is_none_or(|horizon| horizon.is_visible(target))
+    }
+}
+
+/// Range and velocity bin sizes of the map produced for `params`
+fn bin_sizes(params: &FmcwParams) -> (usize, usize, f64, f64) {
+    let num_range = params.samples_per_chirp().next_power_of_two();
+    let num_doppler = params.chirps_per_frame.next_power_of_two();
+    let range_bin_m = params.sample_rate_hz / num_range as f64 * C / (2.0 * params.chirp_slope());
+    let velocity_bin_m_s =
+        params.wavelength_m() / (2.0 * num_doppler as f64 * params.chirp_duration_s);
+    (num_range, num_doppler, range_bin_m, velocity_bin_m_s)
+}
+
+/// Nearest (range, Doppler) cell of a target; `None` beyond the unambiguous range
+fn target_cell(params: &FmcwParams, target: &TargetPosition) -> Option<(usize, usize)> {
+    let (num_range, num_doppler, range_bin_m, velocity_bin_m_s) = bin_sizes(params);
+    let r = (target.range_m / range_bin_m).round();
+    if r < 1.0 || r >= num_range as f64 {
+        return None;
+    }
+    let d = ((num_doppler / 2) as f64 + (target.vel_m_s / velocity_bin_m_s).round())
+        .rem_euclid(num_doppler as f64) as usize;
+    Some((r as usize, d))
+}
+
+/// Detection-level range-Doppler map of a scene, in units of the noise power.
+///
+/// Instead of synthesizing IQ, each target is placed in its nearest cell with
+/// its post-integration SNR from the radar range equation, clutter cells are
+/// scaled the same way, and unit-power complex Gaussian noise fills every cell.
+/// Targets outside the unambiguous range or below the radar horizon are
+/// dropped; velocities fold.
+pub fn detection_level_map<R: Rng + ?Sized>(
+    params: &FmcwParams,
+    scenario: &ScenarioConfig,
+    targets: &[TargetPosition],
+    rng: &mut R,
+) -> RangeDopplerMap {
+    let (num_range, num_doppler, range_bin_m, velocity_bin_m_s) = bin_sizes(params);
+    // Linear SNR of a 1 m² return at each range bin
+    let snr_per_m2 = |range_m: f64| 10f64.powf(snr_db(&scenario.radar, range_m, 1.0) / 10.0);
+
+    let mut cells: Array2<Complex<f64>> = clutter_map(
+        &scenario.clutter,
+        num_range,
+        num_doppler,
+        range_bin_m,
+        velocity_bin_m_s,
+        params.wavelength_m(),
+        rng,
+    );
+    for (r, mut row) in cells.outer_iter_mut().enumerate().skip(1) {
+        let scale = snr_per_m2(r as f64 * range_bin_m).sqrt();
+        row.mapv_inplace(|c| c * scale);
+    }
+
+    for target in targets.iter().filter(|target| scenario.is_visible(target)) {
+        let Some((r, d)) = target_cell(params, target) else {
+            continue;
+        };
+        let rcs = target.swerling.sample_rcs(target.rcs, rng);
+        let phase = rng.gen_range(0.0..2.0 * std::f64::consts::PI);
+        cells[[r, d]] += Complex::from_polar((rcs * snr_per_m2(target.range_m)).sqrt(), phase);
+    }
+
+    let noise_scale = 0.5f64.sqrt();
+    let magnitude = cells.mapv(|c| {
+        let re: f64 = StandardNormal.sample(rng);
+        let im: f64 = StandardNormal.sample(rng);
+        (c + Complex::new(re, im) * noise_scale).norm()
+    });
+
+    RangeDopplerMap {
+        magnitude,
+        range_bin_m,
+        velocity_bin_m_s,
+    }
+}
+
+/// CFAR detections from one detection-level dwell of a scene, each located
+/// in angle when the scenario has a receive array
+pub fn simulate_detections<R: Rng + ?Sized>(
+    params: &FmcwParams,
+    scenario: &ScenarioConfig,
+    targets: &[TargetPosition],
+    rng: &mut R,
+) -> Vec<Detection> {
+    let map = detection_level_map(params, scenario, targets, rng);
+    let mut detections = cfar_2d(&map, &scenario.cfar);
+    if let Some(array) = &scenario.array {
+        locate_detections(params, scenario, array, targets, &mut detections, rng);
+    }
+    detections
+}
+
+/// Detector a session runs over its scene every dwell: single-frame CFAR,
+/// or track-before-detect when the scenario has a `tbd` configuration
+pub struct SceneDetector {
+    scenario: ScenarioConfig,
+    tbd: Option<TrackBeforeDetect>,
+}
+
+impl SceneDetector {
+    pub fn new(scenario: ScenarioConfig) -> Self {
+        let tbd = scenario.tbd.clone().map(TrackBeforeDetect::new);
+        Self { scenario, tbd }
+    }
+
+    /// Detections of one dwell of `targets`; track-before-detect integrates
+    /// it with the previous dwells and calibrates its threshold on the first
+    /// full window, which makes that call slow
+    pub fn detect<R: Rng + ?Sized>(
+        &mut self,
+        params: &FmcwParams,
+        targets: &[TargetPosition],
+        rng: &mut R,
+    ) -> Vec<Detection> {
+        match &mut self.tbd {
+            Some(tbd) => {
+                tbd.push(&detection_level_map(params, &self.scenario, targets, rng));
+                tbd.detect().into_iter().map(|d| d.detection).collect()
+            }
+            None => simulate_detections(params, &self.scenario, targets, rng),
+        }
+    }
+}
+
+/// Estimate each detection's direction from array snapshots of the targets in
+/// (or adjacent to) its cell.
+///
+/// A target's integrated SNR from the radar equation is shared evenly across
+/// the array elements; detections with no target nearby see noise only.
+fn locate_detections<R: Rng + ?Sized>(
+    params: &FmcwParams,
+    scenario: &ScenarioConfig,
+    array: &ArrayConfig,
+    targets: &[TargetPosition],
+    detections: &mut [Detection],
+    rng: &mut R,
+) {
+    let (_, num_doppler, _, _) = bin_sizes(params);
+    let element_loss_db = 10.0 * (array.geometry.num_elements().max(1) as f64).log10();
+    let cells: Vec<Option<(usize, usize)>> = targets
+        .iter()
+        .map(|t| {
+            if scenario.is_visible(t) {
+                target_cell(params, t)
+            } else {
+                None
+            }
+        })
+        .collect();
+    let grid = AngleGrid::default();
+
+    for detection in detections.iter_mut() {
+        let sources: Vec<ArraySource> = targets
+            .iter()
+            .zip(&cells)
+            .filter_map(|(target, cell)| {
+                let (r, d) = (*cell)?;
+                let doppler_offset = (d + num_doppler - detection.doppler_bin) % num_doppler;
+                let adjacent = r.abs_diff(detection.range_bin) <= 1
+                    && (doppler_offset <= 1 || doppler_offset == num_doppler - 1);
+                adjacent.then(|| ArraySource {
+                    azimuth_deg: target.azimuth_deg,
+                    elevation_deg: 0.0,
+                    snr_db: snr_db(&scenario.radar, target.range_m, target.rcs) - element_loss_db,
+                })
+            })
+            .collect();
+        let snapshots = simulate_snapshots(&array.geometry, &sources, array.num_snapshots, rng);
+        detection.angle = estimate_angles(
+            &array.geometry,
+            &snapshots,
+            array.method,
+            sources.len().max(1),
+            &grid,
+        )
+        .into_iter()
+        .next();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfar::CfarKind;
+    use crate::clutter::{GroundClutter, RainClutter};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn params() -> FmcwParams {
+        FmcwParams {
+            chirps_per_frame: 32,
+            sample_rate_hz: 2.56e6,
+            ..FmcwParams::default()
+        }
+    }
+
+    fn drone(range_m: f64, vel_m_s: f64) -> TargetPosition {
+        TargetPosition {
+            range_m,
+            vel_m_s,
+            rcs: 0.5,
+            ..TargetPosition::default()
+        }
+    }
+
+    #[test]
+    fn test_clean_scene_detects_target() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let scenario = ScenarioConfig::default();
+        let detections =
+            simulate_detections(&params(), &scenario, &[drone(3_000.0, 10.0)], &mut rng);
+        assert!(detections
+            .iter()
+            .any(|d| (d.range_m - 3_000.0).abs() < 60.0 && (d.velocity_m_s - 10.0).abs() < 1.5));
+        // Noise alone at Pfa 1e-6 over ~8k cells rarely crosses
+        assert!(detections.len() <= 3);
+    }
+
+    #[test]
+    fn test_clutter_scene_scales_with_radar_equation() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let scenario = ScenarioConfig {
+            clutter: ClutterConfig {
+                ground: Some(GroundClutter::default()),
+                ..ClutterConfig::default()
+            },
+            ..ScenarioConfig::default()
+        };
+        let map = detection_level_map(&params(), &scenario, &[], &mut rng);
+        let power = map.power();
+        let zero = map.num_doppler_bins() / 2;
+        // Clutter-to-noise ratio at zero Doppler far above the unit noise floor up close
+        assert!(power[[20, zero]] > 100.0);
+        // Away from zero Doppler only noise remains
+        let noise: f64 = power.column(2).iter().sum::<f64>() / power.nrows() as f64;
+        assert!((noise - 1.0).abs() < 0.15, "noise {noise}");
+    }
+
+    #[test]
+    fn test_rain_raises_false_alarms() {
+        // Rain is a strong, spread background; greatest-of CFAR keeps the
+        // false alarms at the rain cell's edges no higher than cell averaging
+        let scenario = |kind: CfarKind| ScenarioConfig {
+            clutter: ClutterConfig {
+                rain: Some(RainClutter::default()),
+                ..ClutterConfig::default()
+            },
+            cfar: CfarConfig {
+                kind,
+                ..CfarConfig::default()
+            },
+            ..ScenarioConfig::default()
+        };
+        let count = |kind: CfarKind| {
+            let mut rng = StdRng::seed_from_u64(9);
+            (0..4)
+                .map(|_| simulate_detections(&params(), &scenario(kind), &[], &mut rng).len())
+                .sum::<usize>()
+        };
+        let clean = {
+            let mut rng = StdRng::seed_from_u64(9);
+            simulate_detections(&params(), &ScenarioConfig::default(), &[], &mut rng).len()
+        };
+        assert!(count(CfarKind::CellAveraging) > clean);
+        assert!(count(CfarKind::GreatestOf) <= count(CfarKind::CellAveraging));
+    }
+
+    #[test]
+    fn test_array_scenario_locates_detections() {
+        use crate::aoa::AoaMethod;
+
+        let mut rng = StdRng::seed_from_u64(10);
+        let mut target = drone(3_000.0, 10.0);
+        target.azimuth_deg = 35.0;
+        let scenario = ScenarioConfig {
+            array: Some(ArrayConfig {
+                method: AoaMethod::Music,
+                ..ArrayConfig::default()
+            }),
+            ..ScenarioConfig::default()
+        };
+        let detections = simulate_detections(
+            &params(),
+            &scenario,
+            std::slice::from_ref(&target),
+            &mut rng,
+        );
+        let hit = detections
+            .iter()
+            .find(|d| (d.range_m - 3_000.0).abs() < 60.0)
+            .expect("target detected");
+        let angle = hit.angle.expect("angle estimated");
+        assert!((angle.azimuth_deg - 35.0).abs() < 1.0, "{angle:?}");
+        assert!(angle.azimuth_std_deg < 1.0);
+
+        // Without an array, detections carry no angle
+        let plain = simulate_detections(&params(), &ScenarioConfig::default(), &[target], &mut rng);
+        assert!(plain.iter().all(|d| d.angle.is_none()));
+    }
+
+    #[test]
+    fn test_validate_rejects_unbounded_scenarios() {
+        use crate::aoa::ArrayGeometry;
+        use crate::clutter::{SeaAmplitude, SeaClutter};
+
+        assert!(ScenarioConfig::default().validate(&params()).is_ok());
+        let invalid = [
+            ScenarioConfig {
+                radar: RadarConfig {
+                    bandwidth_hz: 0.0,
+                    ..RadarConfig::default()
+                },
+                ..ScenarioConfig::default()
+            },
+            ScenarioConfig {
+                cfar: CfarConfig {
+                    pfa: 0.0,
+                    ..CfarConfig::default()
+                },
+                ..ScenarioConfig::default()
+            },
+            ScenarioConfig {
+                cfar: CfarConfig {
+                    training_range: usize::MAX,
+                    ..CfarConfig::default()
+                },
+                ..ScenarioConfig::default()
+            },
+            ScenarioConfig {
+                cfar: CfarConfig {
+                    kind: CfarKind::OrderedStatistic { rank: 10_000 },
+                    ..CfarConfig::default()
+                },
+                ..ScenarioConfig::default()
+            },
+            // Within the window limit, but wider than the 32 Doppler bins
+            ScenarioConfig {
+                cfar: CfarConfig {
+                    guard_range: 0,
+                    training_range: 1,
+                    training_doppler: 20,
+                    ..CfarConfig::default()
+                },
+                ..ScenarioConfig::default()
+            },
+            ScenarioConfig {
+                array: Some(ArrayConfig {
+                    geometry: ArrayGeometry::Planar {
+                        num_columns: usize::MAX,
+                        num_rows: 2,
+                        spacing_wavelengths: 0.5,
+                    },
+                    ..ArrayConfig::default()
+                }),
+                ..ScenarioConfig::default()
+            },
+            ScenarioConfig {
+                array: Some(ArrayConfig {
+                    num_snapshots: 1_000_000,
+                    ..ArrayConfig::default()
+                }),
+                ..ScenarioConfig::default()
+            },
+            ScenarioConfig {
+                clutter: ClutterConfig {
+                    sea: Some(SeaClutter {
+                        amplitude: SeaAmplitude::Weibull { shape: 0.0 },
+                        ..SeaClutter::default()
+                    }),
+                    ..ClutterConfig::default()
+                },
+                ..ScenarioConfig::default()
+            },
+            ScenarioConfig {
+                horizon: Some(RadarHorizon {
+                    antenna_height_m: -5.0,
+                    ..RadarHorizon::default()
+                }),
+                ..ScenarioConfig::default()
+            },
+            ScenarioConfig {
+                tbd: Some(TbdConfig {
+                    num_frames: 1_000_000,
+                    ..TbdConfig::default()
+                }),
+                ..ScenarioConfig::default()
+            },
+            ScenarioConfig {
+                tbd: Some(TbdConfig {
+                    range_tolerance_bins: 100,
+                    ..TbdConfig::default()
+                }),
+                ..ScenarioConfig::default()
+            },
+        ];
+        for scenario in invalid {
+            assert!(scenario.validate(&params()).is_err(), "{scenario:?}");
+        }
+
+        // Track-before-detect on a map of more cells than it may integrate
+        let scenario = ScenarioConfig {
+            tbd: Some(TbdConfig::default()),
+            ..ScenarioConfig::default()
+        };
+        assert!(scenario.validate(&params()).is_ok());
+        let large = FmcwParams {
+            chirps_per_frame: 512,
+            ..FmcwParams::default()
+        };
+        assert!(scenario.validate(&large).is_err());
+    }
+
+    #[test]
+    fn test_start_tracking_scenario_is_optional() {
+        use crate::types::WebSocketMessage;
+
+        let plain: WebSocketMessage = serde_json::from_str(r#"{"type":"start_tracking"}"#).unwrap();
+        assert!(matches!(
+            plain,
+            WebSocketMessage::StartTracking {
+                scenario: None,
+                mht: None,
+                tracker: None,
+                site: None,
+                geodetic: false
+            }
+        ));
+
+        let json = r#"{"type":"start_tracking","scenario":{"clutter":{"sea":{
+            "reflectivity_db":-30,"max_range_m":10000,
+            "amplitude":{"type":"weibull","shape":1.2},
+            "mean_velocity_m_s":0.5,"spectral_spread_m_s":0.8}}}}"#;
+        match serde_json::from_str::<WebSocketMessage>(json).unwrap() {
+            WebSocketMessage::StartTracking {
+                scenario: Some(scenario),
+                mht: None,
+                tracker: None,
+                site: None,
+                geodetic: false,
+            } => {
+                assert!(scenario.clutter.sea.is_some());
+                assert!(scenario.clutter.ground.is_none());
+                assert_eq!(scenario.clutter.azimuth_beamwidth_deg, 2.0);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+}
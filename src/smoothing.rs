@@ -0,0 +1,336 @@
+use crate::linalg::invert;
+use crate::tracking::{polar_measurement, MotionModel, TrackEstimate, TrackerConfig};
+use ndarray::{s, Array1, Array2};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use utoipa::ToSchema;
+
+/// Estimates kept per track: ten minutes of the 10 Hz WebSocket loop
+const MAX_ENTRIES_PER_TRACK: usize = 6_000;
+/// Tracks kept per session; the one updated longest ago is dropped first
+const MAX_TRACKS: usize = 256;
+
+/// Motion model the smoother assumes between recorded estimates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmootherModel {
+    pub model: MotionModel,
+    /// Acceleration (CV, m/s²) or jerk (CA, m/s³) noise standard deviation
+    pub process_noise: f64,
+}
+
+impl SmootherModel {
+    /// The model a tracker's filters predict with: an EKF's own model, or
+    /// constant velocity with a particle filter's acceleration noise. IMM
+    /// tracks are smoothed on their position and velocity with the largest
+    /// of the modes' acceleration noises.
+    pub fn for_tracker(config: &TrackerConfig) -> Self {
+        match (config.particle, config.imm) {
+            (Some(particle), _) => Self {
+                model: MotionModel::ConstantVelocity,
+                process_noise: particle.process_noise,
+            },
+            (None, Some(imm)) => Self {
+                model: MotionModel::ConstantVelocity,
+                process_noise: imm
+                    .cruise_noise_m_s2
+                    .max(imm.turn_noise_m_s2)
+                    .max(imm.hover_noise_m_s2),
+            },
+            (None, None) => Self {
+                model: config.model,
+                process_noise: config.process_noise,
+            },
+        }
+    }
+}
+
+/// State estimate and covariance at a point in time
+#[derive(Debug, Clone)]
+pub struct TimedEstimate {
+    pub time_s: f64,
+    pub state: Array1<f64>,
+    pub covariance: Array2<f64>,
+}
+
+/// Rauch–Tung–Striebel fixed-interval smoothing of filtered estimates,
+/// oldest first.
+///
+/// Each estimate is predicted to the next one's time with `model`; the
+/// backward pass then corrects it by the smoothed next estimate through the
+/// gain `C = P Fᵀ P_pred⁻¹`. The last estimate is already smoothed; a
+/// singular predicted covariance leaves an estimate as filtered.
+pub fn rts_smooth(model: SmootherModel, filtered: &[TimedEstimate]) -> Vec<TimedEstimate> {
+    let mut smoothed = filtered.to_vec();
+    for k in (0..filtered.len().saturating_sub(1)).rev() {
+        let current = &filtered[k];
+        let dt = filtered[k + 1].time_s - current.time_s;
+        let f = model.model.transition(dt);
+        let predicted_state = f.dot(&current.state);
+        let predicted_covariance = f.dot(&current.covariance).dot(&f.t())
+            + model.model.process_noise(dt, model.process_noise);
+        let Some(predicted_inv) = invert(&predicted_covariance) else {
+            continue;
+        };
+        let gain = current.covariance.dot(&f.t()).dot(&predicted_inv);
+        let next = &smoothed[k + 1];
+        let state = &current.state + &gain.dot(&(&next.state - &predicted_state));
+        let covariance = &current.covariance
+            + &gain
+                .dot(&(&next.covariance - &predicted_covariance))
+                .dot(&gain.t());
+        smoothed[k] = TimedEstimate {
+            time_s: current.time_s,
+            state,
+            covariance,
+        };
+    }
+    smoothed
+}
+
+/// Recorded filtered estimates of one track
+#[derive(Debug, Clone)]
+pub struct TrackHistory {
+    pub model: SmootherModel,
+    estimates: VecDeque<TimedEstimate>,
+}
+
+impl TrackHistory {
+    pub fn estimates(&self) -> impl Iterator<Item = &TimedEstimate> {
+        self.estimates.iter()
+    }
+
+    /// Smoothed trajectory alongside the real-time one
+    pub fn smooth(&self, id: u32) -> SmoothedTrack {
+        let filtered: Vec<TimedEstimate> = self.estimates.iter().cloned().collect();
+        let smoothed = rts_smooth(self.model, &filtered);
+        let position_std = |p: &Array2<f64>| (p[[0, 0]] + p[[1, 1]]).max(0.0).sqrt();
+        let points = smoothed
+            .iter()
+            .zip(&filtered)
+            .map(|(smoothed, filtered)| {
+                let z = polar_measurement(&smoothed.state);
+                SmoothedPoint {
+                    time_s: smoothed.time_s,
+                    range_m: z[0],
+                    azimuth_deg: z[1].to_degrees().rem_euclid(360.0),
+                    vel_m_s: z[2],
+                    state: smoothed.state.to_vec(),
+                    covariance: smoothed
+                        .covariance
+                        .outer_iter()
+                        .map(|row| row.to_vec())
+                        .collect(),
+                    position_std_m: position_std(&smoothed.covariance),
+                    filtered_position_std_m: position_std(&filtered.covariance),
+                }
+            })
+            .collect();
+        SmoothedTrack { id, points }
+    }
+}
+
+/// Smoothed estimate of a track at one scan
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SmoothedPoint {
+    /// Seconds since the tracking session started
+    pub time_s: f64,
+    pub range_m: f64,
+    pub azimuth_deg: f64,
+    /// Smoothed radial velocity (positive = moving away)
+    pub vel_m_s: f64,
+    /// Smoothed Cartesian state: `[x, y, vx, vy]`, plus `[ax, ay]` for constant acceleration
+    pub state: Vec<f64>,
+    /// Smoothed state covariance, row-major
+    pub covariance: Vec<Vec<f64>>,
+    /// Smoothed position uncertainty, `sqrt(var_x + var_y)`
+    pub position_std_m: f64,
+    /// Position uncertainty of the real-time estimate at the same scan
+    pub filtered_position_std_m: f64,
+}
+
+/// Best-estimate trajectory of a track for post-incident review
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SmoothedTrack {
+    pub id: u32,
+    /// Oldest first
+    pub points: Vec<SmoothedPoint>,
+}
+
+/// Track histories of the latest tracking session.
+///
+/// Starting a session drops the previous one's histories, and a superseded
+/// session's late estimates are ignored, so track ids stay unambiguous.
+/// Histories are bounded per track and in number of tracks.
+#[derive(Debug, Default)]
+pub struct TrackHistoryStore {
+    session: u64,
+    tracks: HashMap<u32, TrackHistory>,
+}
+
+impl TrackHistoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start recording a new session; returns its number
+    pub fn start_session(&mut self) -> u64 {
+        self.session += 1;
+        self.tracks.clear();
+        self.session
+    }
+
+    /// Record one scan's estimates of `session` at `time_s`
+    pub fn record(
+        &mut self,
+        session: u64,
+        time_s: f64,
+        model: SmootherModel,
+        estimates: &[TrackEstimate],
+    ) {
+        if session != self.session {
+            return;
+        }
+        let n = model.model.state_len();
+        for estimate in estimates {
+            if estimate.state.len() < n || estimate.covariance.len() < n {
+                continue;
+            }
+            let covariance = Array2::from_shape_fn((n, n), |(i, j)| estimate.covariance[i][j]);
+            let history = self
+                .tracks
+                .entry(estimate.id)
+                .or_insert_with(|| TrackHistory {
+                    model,
+                    estimates: VecDeque::new(),
+                });
+            history.estimates.push_back(TimedEstimate {
+                time_s,
+                state: Array1::from(estimate.state.clone())
+                    .slice(s![..n])
+                    .to_owned(),
+                covariance,
+            });
+            if history.estimates.len() > MAX_ENTRIES_PER_TRACK {
+                history.estimates.pop_front();
+            }
+        }
+
+        while self.tracks.len() > MAX_TRACKS {
+            let stalest = self
+                .tracks
+                .iter()
+                .min_by(|a, b| {
+                    let last = |h: &TrackHistory| h.estimates.back().map_or(0.0, |e| e.time_s);
+                    last(a.1).total_cmp(&last(b.1))
+                })
+                .map(|(&id, _)| id);
+            match stalest {
+                Some(id) => self.tracks.remove(&id),
+                None => break,
+            };
+        }
+    }
+
+    pub fn history(&self, id: u32) -> Option<&TrackHistory> {
+        self.tracks.get(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracking::{measure, MeasurementNoise, Tracker};
+    use crate::types::TargetPosition;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Target at (3 km, 4 km) flying (-10, 5) m/s, as seen `t` seconds in
+    fn truth(t: f64) -> ((f64, f64), TargetPosition) {
+        let (x, y) = (3_000.0 - 10.0 * t, 4_000.0 + 5.0 * t);
+        let r = x.hypot(y);
+        let target = TargetPosition {
+            range_m: r,
+            azimuth_deg: x.atan2(y).to_degrees(),
+            vel_m_s: (-10.0 * x + 5.0 * y) / r,
+            rcs: 0.1,
+            swerling: Default::default(),
+            ..TargetPosition::default()
+        };
+        ((x, y), target)
+    }
+
+    #[test]
+    fn test_rts_reduces_error_and_covariance() {
+        let config = TrackerConfig {
+            process_noise: 1.0,
+            measurement_noise: MeasurementNoise {
+                range_std_m: 20.0,
+                azimuth_std_deg: 1.0,
+                radial_velocity_std_m_s: 1.0,
+                elevation_std_deg: 1.0,
+            },
+            ..TrackerConfig::default()
+        };
+        let noise = config.measurement_noise;
+        let model = SmootherModel::for_tracker(&config);
+        let mut tracker = Tracker::new(config);
+        let mut store = TrackHistoryStore::new();
+        let session = store.start_session();
+        let mut rng = StdRng::seed_from_u64(21);
+        let dt = 0.5;
+        for step in 0..80 {
+            let t = step as f64 * dt;
+            tracker.step(&[measure(&truth(t).1, &noise, &mut rng)], dt);
+            store.record(session, t, model, &tracker.estimates());
+        }
+
+        let history = store.history(0).expect("track recorded");
+        let smoothed = history.smooth(0);
+        assert_eq!(smoothed.points.len(), 80);
+        let (mut filtered_error, mut smoothed_error) = (0.0, 0.0);
+        for (point, filtered) in smoothed.points.iter().zip(history.estimates()).skip(10) {
+            let ((x, y), _) = truth(point.time_s);
+            smoothed_error += (point.state[0] - x).hypot(point.state[1] - y).powi(2);
+            filtered_error += (filtered.state[0] - x).hypot(filtered.state[1] - y).powi(2);
+            assert!(point.position_std_m <= point.filtered_position_std_m + 1e-9);
+        }
+        assert!(
+            smoothed_error < 0.5 * filtered_error,
+            "smoothed {smoothed_error} filtered {filtered_error}"
+        );
+        // The newest estimate has no future measurements to learn from
+        let last = smoothed.points.last().unwrap();
+        assert!((last.position_std_m - last.filtered_position_std_m).abs() < 1e-9);
+        assert!(
+            smoothed.points[40].position_std_m < 0.8 * smoothed.points[40].filtered_position_std_m
+        );
+    }
+
+    #[test]
+    fn test_store_keeps_only_latest_session() {
+        let config = TrackerConfig::default();
+        let model = SmootherModel::for_tracker(&config);
+        let mut tracker = Tracker::new(config);
+        let mut rng = StdRng::seed_from_u64(22);
+        tracker.step(
+            &[measure(
+                &truth(0.0).1,
+                &MeasurementNoise::default(),
+                &mut rng,
+            )],
+            1.0,
+        );
+
+        let mut store = TrackHistoryStore::new();
+        let old = store.start_session();
+        store.record(old, 0.0, model, &tracker.estimates());
+        assert!(store.history(0).is_some());
+
+        let new = store.start_session();
+        assert!(store.history(0).is_none());
+        store.record(old, 1.0, model, &tracker.estimates());
+        assert!(store.history(0).is_none(), "superseded session ignored");
+        store.record(new, 1.0, model, &tracker.estimates());
+        assert_eq!(store.history(0).unwrap().estimates().count(), 1);
+    }
+}
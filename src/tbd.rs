@@ -0,0 +1,535 @@
+use crate::cfar::Detection;
+use crate::error::{AppError, AppResult};
+use crate::processing::RangeDopplerMap;
+use ndarray::Array2;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Exp1};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use utoipa::ToSchema;
+
+/// Noise-only integrated scores drawn to calibrate the threshold
+const CALIBRATION_SAMPLES: usize = 200_000;
+/// Fewest exceedances an empirical quantile is read from before the tail is extrapolated
+const CALIBRATION_EXCEEDANCES: f64 = 20.0;
+const CALIBRATION_SEED: u64 = 0x7bd;
+
+/// Dynamic-programming track-before-detect settings
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TbdConfig {
+    /// Frames integrated along each candidate trajectory
+    pub num_frames: usize,
+    /// Time between frames in seconds
+    pub frame_interval_s: f64,
+    /// Range bins a trajectory may move per frame beyond the motion its Doppler implies
+    pub range_tolerance_bins: usize,
+    /// Doppler bins a trajectory may change per frame
+    pub doppler_tolerance_bins: usize,
+    /// Probability that a noise-only cell's integrated score crosses the threshold
+    pub pfa: f64,
+}
+
+impl Default for TbdConfig {
+    fn default() -> Self {
+        Self {
+            num_frames: 8,
+            frame_interval_s: 0.1,
+            range_tolerance_bins: 1,
+            doppler_tolerance_bins: 1,
+            pfa: 1e-6,
+        }
+    }
+}
+
+/// Longest window of frames a trajectory may be integrated over
+pub const MAX_TBD_FRAMES: usize = 16;
+/// Widest per-frame range or Doppler tolerance, in bins
+pub const MAX_TBD_TOLERANCE_BINS: usize = 2;
+/// Largest range-Doppler map, in cells, integrated every frame
+pub const MAX_TBD_CELLS: usize = 1 << 16;
+
+impl TbdConfig {
+    /// Reject a window whose threshold cannot be calibrated, or whose
+    /// integration over maps of `num_cells` cells would be too slow to run
+    /// every frame. Calibration and each integration cost the number of
+    /// frames times the cells times the transitions allowed per cell.
+    pub fn validate(&self, num_cells: usize) -> AppResult<()> {
+        if !(self.pfa > 0.0 && self.pfa < 1.0) {
+            return Err(AppError::InvalidInput(format!(
+                "TBD false-alarm probability must lie between 0 and 1: {}",
+                self.pfa
+            )));
+        }
+        if !(self.frame_interval_s > 0.0 && self.frame_interval_s.is_finite()) {
+            return Err(AppError::InvalidInput(format!(
+                "TBD frame interval must be positive: {}",
+                self.frame_interval_s
+            )));
+        }
+        let bounded = (1..=MAX_TBD_FRAMES).contains(&self.num_frames)
+            && self.range_tolerance_bins <= MAX_TBD_TOLERANCE_BINS
+            && self.doppler_tolerance_bins <= MAX_TBD_TOLERANCE_BINS
+            && num_cells <= MAX_TBD_CELLS;
+        if !bounded {
+            return Err(AppError::InvalidInput(format!(
+                "TBD needs 1-{} frames, tolerances of at most {} bins and maps of at most {} cells: {} frames, {}/{} bins, {} cells",
+                MAX_TBD_FRAMES,
+                MAX_TBD_TOLERANCE_BINS,
+                MAX_TBD_CELLS,
+                self.num_frames,
+                self.range_tolerance_bins,
+                self.doppler_tolerance_bins,
+                num_cells
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Target declared by track-before-detect
+#[derive(Debug, Clone)]
+pub struct TbdDetection {
+    /// Cell in the newest frame; `snr_db` is the mean normalised power along the path
+    pub detection: Detection,
+    /// Normalised power integrated along the path
+    pub score: f64,
+    /// (range bin, Doppler bin) in every frame of the window, oldest first
+    pub path: Vec<(usize, usize)>,
+}
+
+/// Map dimensions and bin sizes, which fix the allowed transitions
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Grid {
+    num_range: usize,
+    num_doppler: usize,
+    range_bin_m: f64,
+    velocity_bin_m_s: f64,
+}
+
+impl Grid {
+    fn of(map: &RangeDopplerMap) -> Self {
+        Self {
+            num_range: map.num_range_bins(),
+            num_doppler: map.num_doppler_bins(),
+            range_bin_m: map.range_bin_m,
+            velocity_bin_m_s: map.velocity_bin_m_s,
+        }
+    }
+
+    /// Range bins travelled in one frame at the velocity of a Doppler bin
+    fn range_step(&self, doppler_bin: usize, frame_interval_s: f64) -> isize {
+        let velocity = (doppler_bin as f64 - (self.num_doppler / 2) as f64) * self.velocity_bin_m_s;
+        (velocity * frame_interval_s / self.range_bin_m).round() as isize
+    }
+}
+
+/// Integrated score of every cell in the newest frame and, per later frame,
+/// the flat index of each cell's best predecessor
+struct Integration {
+    scores: Array2<f64>,
+    backpointers: Vec<Vec<usize>>,
+}
+
+/// Best-path integration of normalised power over `frames` (oldest first).
+///
+/// A cell's score is its own power plus the best score among the cells of
+/// the previous frame that could have moved into it: within the Doppler
+/// tolerance, and within the range tolerance of where their radial velocity
+/// would carry them. The Doppler axis wraps; range does not.
+fn integrate<'a>(
+    frames: impl IntoIterator<Item = &'a Array2<f64>>,
+    grid: Grid,
+    config: &TbdConfig,
+) -> Integration {
+    let (nr, nd) = (grid.num_range, grid.num_doppler);
+    let range_tol = config.range_tolerance_bins as isize;
+    let doppler_tol = config.doppler_tolerance_bins.min(nd / 2) as isize;
+    let steps: Vec<isize> = (0..nd)
+        .map(|d| grid.range_step(d, config.frame_interval_s))
+        .collect();
+
+    let mut frames = frames.into_iter();
+    let mut scores = frames
+        .next()
+        .cloned()
+        .unwrap_or_else(|| Array2::zeros((nr, nd)));
+    let mut backpointers = Vec::new();
+    for frame in frames {
+        let mut next = Array2::zeros((nr, nd));
+        let mut pointers = vec![0; nr * nd];
+        for r in 0..nr {
+            for d in 0..nd {
+                let mut best = (f64::MIN, 0);
+                for dd in -doppler_tol..=doppler_tol {
+                    let dp = (d as isize + dd).rem_euclid(nd as isize) as usize;
+                    let origin = r as isize - steps[dp];
+                    let lo = (origin - range_tol).max(0);
+                    let hi = (origin + range_tol).min(nr as isize - 1);
+                    for rp in lo..=hi {
+                        let score = scores[[rp as usize, dp]];
+                        if score > best.0 {
+                            best = (score, rp as usize * nd + dp);
+                        }
+                    }
+                }
+                if best.0 > f64::MIN {
+                    next[[r, d]] = frame[[r, d]] + best.0;
+                    pointers[r * nd + d] = best.1;
+                } else {
+                    // Nothing could have reached this cell; start afresh
+                    next[[r, d]] = frame[[r, d]];
+                    pointers[r * nd + d] = r * nd + d;
+                }
+            }
+        }
+        scores = next;
+        backpointers.push(pointers);
+    }
+    Integration {
+        scores,
+        backpointers,
+    }
+}
+
+/// Power of a map in units of its noise floor, estimated from the median
+/// cell (the median of unit-mean exponential noise power is ln 2)
+fn normalised_power(map: &RangeDopplerMap) -> Array2<f64> {
+    let power = map.power();
+    let mut sorted: Vec<f64> = power.iter().copied().collect();
+    if sorted.is_empty() {
+        return power;
+    }
+    let mid = sorted.len() / 2;
+    let (_, median, _) = sorted.select_nth_unstable_by(mid, f64::total_cmp);
+    let noise = (*median / std::f64::consts::LN_2).max(f64::MIN_POSITIVE);
+    power.mapv(|p| p / noise)
+}
+
+/// Integrated-score threshold giving `config.pfa` per cell on a noise-only grid.
+///
+/// Scores of noise-only frames are drawn and integrated. When `pfa` is too
+/// small to read off the samples directly, the tail beyond the smallest
+/// well-sampled quantile is extrapolated as exponential, fitted over the
+/// decade of probability above it.
+fn calibrate_threshold(grid: Grid, config: &TbdConfig) -> f64 {
+    let cells = (grid.num_range * grid.num_doppler).max(1);
+    let trials = CALIBRATION_SAMPLES.div_ceil(cells);
+    let mut rng = StdRng::seed_from_u64(CALIBRATION_SEED);
+    let mut samples = Vec::with_capacity(trials * cells);
+    for _ in 0..trials {
+        let frames: Vec<Array2<f64>> = (0..config.num_frames.max(1))
+            .map(|_| {
+                Array2::from_shape_simple_fn((grid.num_range, grid.num_doppler), || {
+                    Exp1.sample(&mut rng)
+                })
+            })
+            .collect();
+        samples.extend(integrate(&frames, grid, config).scores);
+    }
+    samples.sort_unstable_by(f64::total_cmp);
+
+    let n = samples.len() as f64;
+    let quantile = |p: f64| samples[((1.0 - p) * n).floor().clamp(0.0, n - 1.0) as usize];
+    let resolvable = CALIBRATION_EXCEEDANCES / n;
+    if config.pfa >= resolvable {
+        return quantile(config.pfa);
+    }
+    let (q, q_decade) = (quantile(resolvable), quantile(10.0 * resolvable));
+    let scale = (q - q_decade) / std::f64::consts::LN_10;
+    q + scale * (resolvable / config.pfa).ln()
+}
+
+/// Sliding-window dynamic-programming track-before-detect.
+///
+/// Range-Doppler maps are pushed one frame at a time. Once the window holds
+/// `num_frames` frames, `detect` integrates normalised power along the best
+/// trajectory ending in every cell of the newest frame and declares the
+/// local maxima whose score crosses a threshold calibrated for `pfa`. Weak
+/// targets below a single-frame CFAR threshold accumulate energy along their
+/// path, while noise does not line up from frame to frame.
+pub struct TrackBeforeDetect {
+    config: TbdConfig,
+    frames: VecDeque<Array2<f64>>,
+    grid: Option<Grid>,
+    /// Threshold for the grid it was calibrated on
+    threshold: Option<(Grid, f64)>,
+}
+
+impl TrackBeforeDetect {
+    pub fn new(config: TbdConfig) -> Self {
+        Self {
+            config,
+            frames: VecDeque::new(),
+            grid: None,
+            threshold: None,
+        }
+    }
+
+    pub fn config(&self) -> &TbdConfig {
+        &self.config
+    }
+
+    /// Empty the window, keeping the calibrated threshold
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Add the newest frame, dropping the oldest beyond the window; a frame
+    /// with a different grid restarts the window
+    pub fn push(&mut self, map: &RangeDopplerMap) {
+        let grid = Grid::of(map);
+        if self.grid != Some(grid) {
+            self.frames.clear();
+            self.grid = Some(grid);
+        }
+        self.frames.push_back(normalised_power(map));
+        while self.frames.len() > self.config.num_frames.max(1) {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Integrated-score threshold for the current grid, calibrated on first use
+    pub fn threshold(&mut self) -> Option<f64> {
+        let grid = self.grid?;
+        match self.threshold {
+            Some((calibrated, threshold)) if calibrated == grid => Some(threshold),
+            _ => {
+                let threshold = calibrate_threshold(grid, &self.config);
+                self.threshold = Some((grid, threshold));
+                Some(threshold)
+            }
+        }
+    }
+
+    /// Targets whose best trajectory over a full window crosses the threshold;
+    /// empty until the window is full
+    pub fn detect(&mut self) -> Vec<TbdDetection> {
+        let num_frames = self.config.num_frames.max(1);
+        let (Some(grid), Some(threshold)) = (self.grid, self.threshold()) else {
+            return Vec::new();
+        };
+        if self.frames.len() < num_frames {
+            return Vec::new();
+        }
+        let integration = integrate(&self.frames, grid, &self.config);
+        let scores = &integration.scores;
+        let (nr, nd) = (grid.num_range, grid.num_doppler);
+        let map = RangeDopplerMap {
+            magnitude: Array2::zeros((0, nd)),
+            range_bin_m: grid.range_bin_m,
+            velocity_bin_m_s: grid.velocity_bin_m_s,
+        };
+
+        let mut detections = Vec::new();
+        for ((r, d), &score) in scores.indexed_iter() {
+            if score <= threshold {
+                continue;
+            }
+            let is_peak = (-1isize..=1).all(|dr| {
+                (-1isize..=1).all(|dd| {
+                    let rr = r as isize + dr;
+                    if (dr == 0 && dd == 0) || rr < 0 || rr >= nr as isize {
+                        return true;
+                    }
+                    let dc = (d as isize + dd).rem_euclid(nd as isize) as usize;
+                    scores[[rr as usize, dc]] <= score
+                })
+            });
+            if !is_peak {
+                continue;
+            }
+
+            let mut path = vec![(r, d)];
+            let mut index = r * nd + d;
+            for pointers in integration.backpointers.iter().rev() {
+                index = pointers[index];
+                path.push((index / nd, index % nd));
+            }
+            path.reverse();
+
+            detections.push(TbdDetection {
+                detection: Detection {
+                    range_bin: r,
+                    doppler_bin: d,
+                    range_m: map.bin_to_range_m(r as f64),
+                    velocity_m_s: map.bin_to_velocity_m_s(d as f64),
+                    snr_db: 10.0 * (score / num_frames as f64).log10(),
+                    angle: None,
+                },
+                score,
+                path,
+            });
+        }
+        detections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cfar::{cfar_2d, CfarConfig};
+    use crate::scenario::{detection_level_map, ScenarioConfig};
+    use crate::signal::FmcwParams;
+    use crate::swerling::SwerlingModel;
+    use crate::tracking::{PolarMeasurement, TrackStatus, Tracker, TrackerConfig};
+    use crate::types::{RadarConfig, TargetPosition};
+
+    fn params() -> FmcwParams {
+        FmcwParams {
+            chirps_per_frame: 16,
+            sample_rate_hz: 2.56e6,
+            ..FmcwParams::default()
+        }
+    }
+
+    /// Low-power radar that puts a 0.01 m² drone's single-frame detection
+    /// range inside the 7.5 km unambiguous range of `params`
+    fn scenario() -> ScenarioConfig {
+        ScenarioConfig {
+            radar: RadarConfig {
+                transmit_power_w: 50.0,
+                ..RadarConfig::default()
+            },
+            cfar: CfarConfig {
+                guard_range: 1,
+                training_range: 4,
+                guard_doppler: 1,
+                training_doppler: 2,
+                ..CfarConfig::default()
+            },
+            ..ScenarioConfig::default()
+        }
+    }
+
+    fn micro_drone(range_m: f64) -> TargetPosition {
+        TargetPosition {
+            range_m,
+            vel_m_s: -12.0,
+            rcs: 0.01,
+            swerling: SwerlingModel::One,
+            ..TargetPosition::default()
+        }
+    }
+
+    /// Whether CFAR followed by a tracker and TBD each hold the drone after
+    /// one window of frames starting at `range_m`
+    fn detect_both(tbd: &mut TrackBeforeDetect, range_m: f64, seed: u64) -> (bool, bool) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let config = tbd.config().clone();
+        let dt = config.frame_interval_s;
+        tbd.clear();
+        let mut tracker = Tracker::new(TrackerConfig::default());
+        let mut target = micro_drone(range_m);
+        let tolerance_m = 2.0 * params().range_resolution_m();
+
+        for frame in 0..config.num_frames {
+            if frame > 0 {
+                target.range_m += target.vel_m_s * dt;
+            }
+            let map = detection_level_map(
+                &params(),
+                &scenario(),
+                std::slice::from_ref(&target),
+                &mut rng,
+            );
+            let measurements: Vec<PolarMeasurement> = cfar_2d(&map, &scenario().cfar)
+                .iter()
+                .map(|d| PolarMeasurement {
+                    range_m: d.range_m,
+                    azimuth_deg: 0.0,
+                    elevation_deg: 0.0,
+                    radial_velocity_m_s: d.velocity_m_s,
+                    rcs: 0.01,
+                })
+                .collect();
+            tracker.step(&measurements, dt);
+            tbd.push(&map);
+        }
+        let tracked = tracker.tracks().iter().any(|track| {
+            track.status == TrackStatus::Confirmed
+                && (track.estimate().range_m - target.range_m).abs() < tolerance_m
+        });
+        let found = tbd
+            .detect()
+            .iter()
+            .any(|d| (d.detection.range_m - target.range_m).abs() < tolerance_m);
+        (tracked, found)
+    }
+
+    #[test]
+    fn test_tbd_extends_detection_range_over_cfar_chain() {
+        let ranges = [3_000.0, 5_000.0];
+        let trials = 4;
+        let mut tbd = TrackBeforeDetect::new(TbdConfig::default());
+        let mut pd = Vec::new();
+        for &range_m in &ranges {
+            let (mut tracked, mut found) = (0, 0);
+            for seed in 0..trials {
+                let (t, f) = detect_both(&mut tbd, range_m, seed);
+                tracked += t as usize;
+                found += f as usize;
+            }
+            pd.push((tracked as f64 / trials as f64, found as f64 / trials as f64));
+        }
+        // Farthest range at which each chain holds the drone at least half the time
+        let detection_range = |pick: fn(&(f64, f64)) -> f64| {
+            ranges
+                .iter()
+                .zip(&pd)
+                .filter(|(_, p)| pick(p) >= 0.5)
+                .map(|(&r, _)| r)
+                .fold(0.0, f64::max)
+        };
+        let cfar_range = detection_range(|p| p.0);
+        let tbd_range = detection_range(|p| p.1);
+        assert!(cfar_range >= ranges[0], "{pd:?}");
+        assert!(
+            tbd_range > cfar_range,
+            "tbd {tbd_range} cfar {cfar_range}: {pd:?}"
+        );
+    }
+
+    #[test]
+    fn test_tbd_noise_only_false_alarms_and_path() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut tbd = TrackBeforeDetect::new(TbdConfig::default());
+        let mut false_alarms = 0;
+        for frame in 0..24 {
+            let map = detection_level_map(&params(), &scenario(), &[], &mut rng);
+            tbd.push(&map);
+            let detections = tbd.detect();
+            if frame + 1 < tbd.config().num_frames {
+                assert!(detections.is_empty());
+            }
+            false_alarms += detections.len();
+        }
+        // ~4k cells at Pfa 1e-6 over 17 full windows
+        assert!(false_alarms <= 1, "{false_alarms}");
+
+        // A strong approaching target is declared with a path that follows it
+        let mut target = micro_drone(2_000.0);
+        target.rcs = 1.0;
+        target.vel_m_s = -60.0;
+        for _ in 0..tbd.config().num_frames {
+            let map = detection_level_map(
+                &params(),
+                &scenario(),
+                std::slice::from_ref(&target),
+                &mut rng,
+            );
+            tbd.push(&map);
+            target.range_m += target.vel_m_s * tbd.config().frame_interval_s;
+        }
+        let detections = tbd.detect();
+        let hit = detections
+            .iter()
+            .max_by(|a, b| a.score.total_cmp(&b.score))
+            .expect("target declared");
+        let (first, last) = (hit.path[0], *hit.path.last().unwrap());
+        assert_eq!(hit.path.len(), tbd.config().num_frames);
+        // 42 m inbound over the window: at least one 30 m range bin
+        assert!(first.0 > last.0, "{:?}", hit.path);
+        assert!((hit.detection.velocity_m_s + 60.0).abs() < 5.0, "{hit:?}");
+    }
+}
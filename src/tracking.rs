@@ -0,0 +1,1649 @@
+use crate::association::{AssociationMethod, PairScore};
+use crate::error::{AppError, AppResult};
+use crate::geo::{GeodeticPosition, RadarSite};
+use crate::horizon::{default_horizon, RadarHorizon};
+use crate::imm::{ImmConfig, ImmFilter, ModeProbability};
+use crate::linalg::{determinant, invert};
+use crate::link_budget::snr_db;
+use crate::particle::{ParticleFilter, ParticleFilterConfig};
+use crate::swerling::{probability_of_detection, SwerlingModel};
+use crate::types::{RadarConfig, TargetPosition, Velocity3};
+use ndarray::{Array1, Array2};
+use rand::Rng;
+use rand_distr::{Distribution, Normal, Poisson};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use utoipa::ToSchema;
+
+/// Chi-square 99% gate for the three-dimensional polar innovation
+pub const GATE_CHI2_3DOF_99: f64 = 11.345;
+
+/// Kinematic model of a track's state in the horizontal plane.
+///
+/// Positions are `x` (east, `range * sin(azimuth)`) and `y` (north,
+/// `range * cos(azimuth)`), so azimuth is measured clockwise from north.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MotionModel {
+    /// State `[x, y, vx, vy]` driven by white acceleration noise
+    #[default]
+    ConstantVelocity,
+    /// State `[x, y, vx, vy, ax, ay]` driven by white jerk noise
+    ConstantAcceleration,
+}
+
+impl MotionModel {
+    pub fn state_len(&self) -> usize {
+        match self {
+            MotionModel::ConstantVelocity => 4,
+            MotionModel::ConstantAcceleration => 6,
+        }
+    }
+
+    /// State transition matrix over `dt` seconds
+    pub fn transition(&self, dt: f64) -> Array2<f64> {
+        let n = self.state_len();
+        let mut f = Array2::eye(n);
+        for axis in 0..2 {
+            f[[axis, 2 + axis]] = dt;
+            if n == 6 {
+                f[[axis, 4 + axis]] = 0.5 * dt * dt;
+                f[[2 + axis, 4 + axis]] = dt;
+            }
+        }
+        f
+    }
+
+    /// Discrete process noise for an `intensity` of acceleration (CV, m/s²)
+    /// or jerk (CA, m/s³) held constant over each step
+    pub fn process_noise(&self, dt: f64, intensity: f64) -> Array2<f64> {
+        let n = self.state_len();
+        let gain: Vec<f64> = match self {
+            MotionModel::ConstantVelocity => vec![0.5 * dt * dt, dt],
+            MotionModel::ConstantAcceleration => vec![dt.powi(3) / 6.0, 0.5 * dt * dt, dt],
+        };
+        let mut q = Array2::zeros((n, n));
+        for axis in 0..2 {
+            for (i, gi) in gain.iter().enumerate() {
+                for (j, gj) in gain.iter().enumerate() {
+                    q[[axis + 2 * i, axis + 2 * j]] = gi * gj * intensity * intensity;
+                }
+            }
+        }
+        q
+    }
+}
+
+/// Range, azimuth, elevation and radial-velocity measurement of one detection
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PolarMeasurement {
+    /// Slant range in meters
+    pub range_m: f64,
+    /// Azimuth in degrees, clockwise from north
+    pub azimuth_deg: f64,
+    /// Elevation in degrees above the radar's horizontal plane
+    #[serde(default)]
+    pub elevation_deg: f64,
+    /// Radial velocity in meters per second (positive = moving away)
+    pub radial_velocity_m_s: f64,
+    /// Observed RCS, carried to the track for display and classification
+    pub rcs: f64,
+}
+
+impl PolarMeasurement {
+    /// Range projected onto the horizontal plane the track filters work in
+    pub(crate) fn ground_range_m(&self) -> f64 {
+        self.range_m * self.elevation_deg.to_radians().cos()
+    }
+
+    /// Height above the radar
+    pub(crate) fn height_m(&self) -> f64 {
+        self.range_m * self.elevation_deg.to_radians().sin()
+    }
+
+    /// Horizontal measurement `[ground range, azimuth (rad), radial
+    /// velocity]`; at drone elevation angles the radial velocity is taken as
+    /// horizontal
+    fn as_vector(&self) -> Array1<f64> {
+        Array1::from(vec![
+            self.ground_range_m(),
+            self.azimuth_deg.to_radians(),
+            self.radial_velocity_m_s,
+        ])
+    }
+}
+
+/// Standard deviations of the polar measurement errors
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MeasurementNoise {
+    pub range_std_m: f64,
+    pub azimuth_std_deg: f64,
+    pub radial_velocity_std_m_s: f64,
+    #[serde(default = "default_elevation_std_deg")]
+    pub elevation_std_deg: f64,
+}
+
+fn default_elevation_std_deg() -> f64 {
+    1.0
+}
+
+impl Default for MeasurementNoise {
+    fn default() -> Self {
+        Self {
+            range_std_m: 10.0,
+            azimuth_std_deg: 0.5,
+            radial_velocity_std_m_s: 0.5,
+            elevation_std_deg: default_elevation_std_deg(),
+        }
+    }
+}
+
+impl MeasurementNoise {
+    pub(crate) fn covariance(&self) -> Array2<f64> {
+        Array2::from_diag(&Array1::from(vec![
+            self.range_std_m.powi(2),
+            self.azimuth_std_deg.to_radians().powi(2),
+            self.radial_velocity_std_m_s.powi(2),
+        ]))
+    }
+}
+
+/// Noisy polar measurement of a target's true position and radial velocity
+pub fn measure<R: Rng + ?Sized>(
+    target: &TargetPosition,
+    noise: &MeasurementNoise,
+    rng: &mut R,
+) -> PolarMeasurement {
+    let mut draw = |std: f64| {
+        Normal::new(0.0, std.max(0.0))
+            .map(|n| n.sample(rng))
+            .unwrap_or(0.0)
+    };
+    PolarMeasurement {
+        range_m: target.range_m + draw(noise.range_std_m),
+        azimuth_deg: target.azimuth_deg + draw(noise.azimuth_std_deg),
+        radial_velocity_m_s: target.vel_m_s + draw(noise.radial_velocity_std_m_s),
+        rcs: target.rcs,
+        elevation_deg: target.elevation_deg + draw(noise.elevation_std_deg),
+    }
+}
+
+/// RCS reported with a false alarm, about that of a clutter spike
+const FALSE_ALARM_RCS_M2: f64 = 0.01;
+
+/// Scan-level sensor model: missed detections and false alarms on top of the
+/// measurement noise
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+pub struct DetectionModel {
+    /// Probability that a target is detected in a scan, without a `radar`
+    pub detection_probability: f64,
+    /// Radar whose link budget gives each target's detection probability from
+    /// its range and RCS; the fixed `detection_probability` when `null`
+    #[serde(default)]
+    pub radar: Option<RadarConfig>,
+    /// False-alarm probability of the detector, with a `radar`
+    #[serde(default = "default_pfa")]
+    pub pfa: f64,
+    /// Pulses non-coherently integrated per dwell, with a `radar`
+    #[serde(default = "default_num_pulses")]
+    pub num_pulses: usize,
+    /// Mean number of false alarms per scan, spread uniformly over the
+    /// surveillance area
+    pub false_alarms_per_scan: f64,
+    /// Radius of the surveillance area
+    pub max_range_m: f64,
+    /// Largest radial speed of a false alarm
+    pub max_speed_m_s: f64,
+    /// Earth curvature and refraction; targets below the radar horizon are
+    /// never detected. A flat earth when `null`.
+    #[serde(default = "default_horizon")]
+    pub horizon: Option<RadarHorizon>,
+}
+
+fn default_pfa() -> f64 {
+    1e-6
+}
+
+fn default_num_pulses() -> usize {
+    1
+}
+
+impl Default for DetectionModel {
+    fn default() -> Self {
+        Self {
+            detection_probability: 0.9,
+            radar: None,
+            pfa: default_pfa(),
+            num_pulses: default_num_pulses(),
+            false_alarms_per_scan: 0.5,
+            max_range_m: 20_000.0,
+            max_speed_m_s: 50.0,
+            horizon: default_horizon(),
+        }
+    }
+}
+
+impl DetectionModel {
+    /// Probability of detecting a target in a scan. With a radar, the
+    /// target's RCS is the one drawn for this dwell from its Swerling model,
+    /// so it is detected like a steady target of that RCS.
+    pub fn probability_of_detection(&self, target: &TargetPosition) -> f64 {
+        let pd = match &self.radar {
+            Some(radar) => probability_of_detection(
+                SwerlingModel::Zero,
+                snr_db(radar, target.range_m, target.rcs),
+                self.pfa,
+                self.num_pulses,
+            ),
+            None => self.detection_probability,
+        };
+        if pd.is_nan() {
+            0.0
+        } else {
+            pd.clamp(0.0, 1.0)
+        }
+    }
+
+    /// One scan: a noisy measurement of every detected target above the
+    /// horizon followed by a Poisson number of false alarms
+    pub fn scan<R: Rng + ?Sized>(
+        &self,
+        targets: &[TargetPosition],
+        noise: &MeasurementNoise,
+        rng: &mut R,
+    ) -> Vec<PolarMeasurement> {
+        let mut measurements = Vec::with_capacity(targets.len());
+        for target in targets {
+            if !self
+                .horizon
+                .is_none_or(|horizon| horizon.is_visible(target))
+            {
+                continue;
+            }
+            if rng.gen_bool(self.probability_of_detection(target)) {
+                measurements.push(measure(target, noise, rng));
+            }
+        }
+        let false_alarms = Poisson::new(self.false_alarms_per_scan)
+            .map(|poisson| poisson.sample(rng) as usize)
+            .unwrap_or(0);
+        for _ in 0..false_alarms {
+            measurements.push(PolarMeasurement {
+                // Uniform over the disc
+                range_m: self.max_range_m * rng.gen::<f64>().sqrt(),
+                azimuth_deg: rng.gen_range(0.0..360.0),
+                // Clutter spikes come from the ground
+                elevation_deg: 0.0,
+                radial_velocity_m_s: self.max_speed_m_s * rng.gen_range(-1.0..=1.0),
+                rcs: FALSE_ALARM_RCS_M2,
+            });
+        }
+        measurements
+    }
+}
+
+/// Wrap an angle in radians to `[-pi, pi)`
+pub(crate) fn wrap_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+/// Measurement `[range, azimuth (rad), radial velocity]` of a Cartesian
+/// state whose first four elements are `[x, y, vx, vy]`
+pub(crate) fn polar_measurement(state: &Array1<f64>) -> Array1<f64> {
+    let (x, y, vx, vy) = (state[0], state[1], state[2], state[3]);
+    let r = x.hypot(y).max(1e-6);
+    Array1::from(vec![r, x.atan2(y), (x * vx + y * vy) / r])
+}
+
+/// Jacobian of [`polar_measurement`]; columns past the velocity are zero
+pub(crate) fn polar_jacobian(state: &Array1<f64>) -> Array2<f64> {
+    let (x, y, vx, vy) = (state[0], state[1], state[2], state[3]);
+    let r = x.hypot(y).max(1e-6);
+    let rr = (x * vx + y * vy) / r;
+    let mut h = Array2::zeros((3, state.len()));
+    h[[0, 0]] = x / r;
+    h[[0, 1]] = y / r;
+    h[[1, 0]] = y / (r * r);
+    h[[1, 1]] = -x / (r * r);
+    h[[2, 0]] = (vx - x * rr / r) / r;
+    h[[2, 1]] = (vy - y * rr / r) / r;
+    h[[2, 2]] = x / r;
+    h[[2, 3]] = y / r;
+    h
+}
+
+/// Measurement minus its prediction, with the azimuth difference wrapped
+fn residual(measurement: &PolarMeasurement, predicted: &Array1<f64>) -> Array1<f64> {
+    let mut nu = measurement.as_vector() - predicted;
+    nu[1] = wrap_angle(nu[1]);
+    nu
+}
+
+/// Innovation, its covariance and the measurement Jacobian of a polar
+/// measurement against a state estimate
+pub(crate) fn polar_innovation(
+    state: &Array1<f64>,
+    covariance: &Array2<f64>,
+    measurement: &PolarMeasurement,
+    noise: &MeasurementNoise,
+) -> (Array1<f64>, Array2<f64>, Array2<f64>) {
+    let nu = residual(measurement, &polar_measurement(state));
+    let h = polar_jacobian(state);
+    let s = h.dot(covariance).dot(&h.t()) + noise.covariance();
+    (nu, s, h)
+}
+
+/// Kalman measurement update with the Joseph-form covariance, given the
+/// innovation, measurement Jacobian and inverse innovation covariance
+pub(crate) fn joseph_update(
+    state: &mut Array1<f64>,
+    covariance: &mut Array2<f64>,
+    nu: &Array1<f64>,
+    h: &Array2<f64>,
+    s_inv: &Array2<f64>,
+    noise: &MeasurementNoise,
+) {
+    let gain = covariance.dot(&h.t()).dot(s_inv);
+    *state = &*state + &gain.dot(nu);
+    let i_kh = Array2::<f64>::eye(state.len()) - gain.dot(h);
+    *covariance =
+        i_kh.dot(&*covariance).dot(&i_kh.t()) + gain.dot(&noise.covariance()).dot(&gain.t());
+}
+
+/// Log of the Gaussian density of an innovation with normalised squared
+/// distance `d2` and covariance `s`
+pub(crate) fn log_likelihood(d2: f64, s: &Array2<f64>) -> f64 {
+    let k = s.nrows() as i32;
+    -0.5 * (d2
+        + ((2.0 * PI).powi(k) * determinant(s))
+            .max(f64::MIN_POSITIVE)
+            .ln())
+}
+
+/// Gating statistics of a measurement against a state estimate; `None` if
+/// the innovation covariance is singular
+pub(crate) fn pair_score(
+    state: &Array1<f64>,
+    covariance: &Array2<f64>,
+    measurement: &PolarMeasurement,
+    noise: &MeasurementNoise,
+) -> Option<PairScore> {
+    let (nu, s, _) = polar_innovation(state, covariance, measurement, noise);
+    let d2 = nu.dot(&invert(&s)?.dot(&nu));
+    Some(PairScore {
+        d2,
+        log_likelihood: log_likelihood(d2, &s),
+    })
+}
+
+/// Probabilistic data association update with measurements weighted by their
+/// association probabilities, the remaining probability going to "none of
+/// them". A single measurement of weight one is a plain Joseph update.
+///
+/// Returns the log of the weight-averaged measurement likelihood, or `None`
+/// (leaving the estimate untouched) without weight or with a singular
+/// innovation covariance.
+pub(crate) fn pda_update(
+    state: &mut Array1<f64>,
+    covariance: &mut Array2<f64>,
+    measurements: &[(&PolarMeasurement, f64)],
+    noise: &MeasurementNoise,
+) -> Option<f64> {
+    let detected: f64 = measurements.iter().map(|(_, w)| w).sum();
+    if detected <= 0.0 {
+        return None;
+    }
+    // The innovation covariance and Jacobian depend on the prediction only
+    let (_, s, h) = polar_innovation(state, covariance, measurements.first()?.0, noise);
+    let s_inv = invert(&s)?;
+    let predicted = polar_measurement(state);
+    let mut combined = Array1::zeros(3);
+    let mut spread = Array2::zeros((3, 3));
+    let mut log_likelihoods = Vec::with_capacity(measurements.len());
+    for (measurement, weight) in measurements {
+        let nu = residual(measurement, &predicted);
+        let d2 = nu.dot(&s_inv.dot(&nu));
+        log_likelihoods.push(weight.ln() + log_likelihood(d2, &s));
+        combined.scaled_add(*weight, &nu);
+        let column = nu.insert_axis(ndarray::Axis(1));
+        spread.scaled_add(*weight, &column.dot(&column.t()));
+    }
+
+    let prior = covariance.clone();
+    joseph_update(state, covariance, &combined, &h, &s_inv, noise);
+    let miss = (1.0 - detected).max(0.0);
+    if miss > 0.0 || measurements.len() > 1 {
+        let gain = prior.dot(&h.t()).dot(&s_inv);
+        let combined = combined.insert_axis(ndarray::Axis(1));
+        spread -= &combined.dot(&combined.t());
+        *covariance =
+            &prior * miss + &*covariance * (1.0 - miss) + gain.dot(&spread).dot(&gain.t());
+    }
+
+    // Log-sum-exp of the weighted likelihoods, normalised by the total weight
+    let max = log_likelihoods
+        .iter()
+        .cloned()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let sum: f64 = log_likelihoods.iter().map(|l| (l - max).exp()).sum();
+    Some(max + sum.ln() - detected.ln())
+}
+
+/// Extended Kalman filter on a Cartesian state with polar measurements
+#[derive(Debug, Clone)]
+pub struct ExtendedKalmanFilter {
+    pub model: MotionModel,
+    pub state: Array1<f64>,
+    pub covariance: Array2<f64>,
+    /// Acceleration (CV) or jerk (CA) noise standard deviation
+    pub process_noise: f64,
+}
+
+impl ExtendedKalmanFilter {
+    /// Start a filter from a single measurement.
+    ///
+    /// Position comes from the polar-to-Cartesian conversion with its
+    /// linearised covariance. Only the radial velocity is observed, so the
+    /// tangential component starts at zero with `max_speed_m_s` uncertainty;
+    /// acceleration (CA) starts at zero with `process_noise` uncertainty per second.
+    pub fn initialize(
+        model: MotionModel,
+        measurement: &PolarMeasurement,
+        noise: &MeasurementNoise,
+        process_noise: f64,
+        max_speed_m_s: f64,
+    ) -> Self {
+        let n = model.state_len();
+        let (r, az) = (
+            measurement.ground_range_m(),
+            measurement.azimuth_deg.to_radians(),
+        );
+        let los = [az.sin(), az.cos()];
+        let across = [az.cos(), -az.sin()];
+
+        let mut state = Array1::zeros(n);
+        state[0] = r * los[0];
+        state[1] = r * los[1];
+        state[2] = measurement.radial_velocity_m_s * los[0];
+        state[3] = measurement.radial_velocity_m_s * los[1];
+
+        let mut covariance = Array2::zeros((n, n));
+        let (var_r, var_az) = (
+            noise.range_std_m.powi(2),
+            noise.azimuth_std_deg.to_radians().powi(2),
+        );
+        let var_v = noise.radial_velocity_std_m_s.powi(2);
+        let var_t = max_speed_m_s.powi(2);
+        for i in 0..2 {
+            for j in 0..2 {
+                covariance[[i, j]] =
+                    los[i] * los[j] * var_r + across[i] * across[j] * r * r * var_az;
+                covariance[[2 + i, 2 + j]] =
+                    los[i] * los[j] * var_v + across[i] * across[j] * var_t;
+            }
+        }
+        if n == 6 {
+            covariance[[4, 4]] = process_noise.powi(2);
+            covariance[[5, 5]] = process_noise.powi(2);
+        }
+        Self {
+            model,
+            state,
+            covariance,
+            process_noise,
+        }
+    }
+
+    pub fn predict(&mut self, dt: f64) {
+        let f = self.model.transition(dt);
+        self.state = f.dot(&self.state);
+        self.covariance =
+            f.dot(&self.covariance).dot(&f.t()) + self.model.process_noise(dt, self.process_noise);
+    }
+
+    /// Predicted measurement `[range, azimuth (rad), radial velocity]`
+    pub fn predicted_measurement(&self) -> Array1<f64> {
+        polar_measurement(&self.state)
+    }
+
+    /// Innovation, its covariance and Jacobian for a measurement
+    fn innovation(
+        &self,
+        measurement: &PolarMeasurement,
+        noise: &MeasurementNoise,
+    ) -> (Array1<f64>, Array2<f64>, Array2<f64>) {
+        polar_innovation(&self.state, &self.covariance, measurement, noise)
+    }
+
+    /// Normalised innovation squared (Mahalanobis distance²) of a measurement,
+    /// used for gating; infinite if the innovation covariance is singular
+    pub fn mahalanobis(&self, measurement: &PolarMeasurement, noise: &MeasurementNoise) -> f64 {
+        let (nu, s, _) = self.innovation(measurement, noise);
+        invert(&s).map_or(f64::INFINITY, |s_inv| nu.dot(&s_inv.dot(&nu)))
+    }
+
+    /// Measurement update with the Joseph-form covariance; returns the
+    /// normalised innovation squared
+    pub fn update(&mut self, measurement: &PolarMeasurement, noise: &MeasurementNoise) -> f64 {
+        let (nu, s, h) = self.innovation(measurement, noise);
+        let Some(s_inv) = invert(&s) else {
+            return f64::INFINITY;
+        };
+        joseph_update(
+            &mut self.state,
+            &mut self.covariance,
+            &nu,
+            &h,
+            &s_inv,
+            noise,
+        );
+        nu.dot(&s_inv.dot(&nu))
+    }
+
+    /// Measurement update with several measurements weighted by their
+    /// association probabilities, as produced by JPDA
+    pub fn update_weighted(
+        &mut self,
+        measurements: &[(&PolarMeasurement, f64)],
+        noise: &MeasurementNoise,
+    ) {
+        pda_update(&mut self.state, &mut self.covariance, measurements, noise);
+    }
+}
+
+/// Lifecycle state of a track
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackStatus {
+    /// Newly started, not yet confirmed; possibly a false alarm
+    Tentative,
+    /// Confirmed and updated by the last scan
+    Confirmed,
+    /// Confirmed but missed by the latest scans; predicted only
+    Coasting,
+    /// Dropped; reported for one scan, then removed
+    Deleted,
+}
+
+/// M-of-N confirmation: a track is confirmed once it has `hits` associated
+/// measurements within its first `window` scans, and deleted as soon as it
+/// can no longer reach them
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ConfirmationLogic {
+    pub hits: u32,
+    pub window: u32,
+}
+
+impl Default for ConfirmationLogic {
+    fn default() -> Self {
+        Self { hits: 3, window: 5 }
+    }
+}
+
+impl ConfirmationLogic {
+    /// Whether a track with `hits` after `scans` scans can still be confirmed
+    pub fn reachable(&self, hits: u32, scans: u32) -> bool {
+        hits + self.window.saturating_sub(scans) >= self.hits
+    }
+}
+
+/// Tracker settings
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrackerConfig {
+    pub model: MotionModel,
+    /// Acceleration (CV, m/s²) or jerk (CA, m/s³) noise standard deviation
+    pub process_noise: f64,
+    pub measurement_noise: MeasurementNoise,
+    /// Normalised-innovation-squared gate for association
+    pub gate: f64,
+    /// Largest speed a new target may have, sizing the initial tangential uncertainty
+    pub max_speed_m_s: f64,
+    /// Consecutive missed updates a confirmed track coasts through before
+    /// it is deleted
+    pub max_misses: u32,
+    /// When a tentative track becomes confirmed or is deleted
+    #[serde(default)]
+    pub confirmation: ConfirmationLogic,
+    /// Run an IMM estimator on every track instead of a single-model EKF,
+    /// in which case `model` and `process_noise` are unused
+    #[serde(default)]
+    pub imm: Option<ImmConfig>,
+    /// Run a particle filter on every track instead, taking precedence over
+    /// `imm`; suits bearing- or range-only measurements
+    #[serde(default)]
+    pub particle: Option<ParticleFilterConfig>,
+    /// How gated measurements are assigned to tracks
+    #[serde(default)]
+    pub association: AssociationMethod,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            model: MotionModel::ConstantVelocity,
+            process_noise: 5.0,
+            measurement_noise: MeasurementNoise::default(),
+            gate: GATE_CHI2_3DOF_99,
+            max_speed_m_s: 100.0,
+            max_misses: 3,
+            imm: None,
+            particle: None,
+            association: AssociationMethod::default(),
+            confirmation: ConfirmationLogic::default(),
+        }
+    }
+}
+
+impl TrackerConfig {
+    /// Reject settings that leave a filter without noise, could never
+    /// confirm a track or would allocate unbounded particle clouds
+    pub fn validate(&self) -> AppResult<()> {
+        let positive = |x: f64| x > 0.0 && x.is_finite();
+        let noise = &self.measurement_noise;
+        let stds = [
+            self.process_noise,
+            noise.range_std_m,
+            noise.azimuth_std_deg,
+            noise.radial_velocity_std_m_s,
+            noise.elevation_std_deg,
+        ];
+        if !stds.into_iter().all(positive) {
+            return Err(AppError::InvalidInput(format!(
+                "Process and measurement noise must be positive: {} {:?}",
+                self.process_noise, noise
+            )));
+        }
+        let speed_ok = self.max_speed_m_s >= 0.0 && self.max_speed_m_s.is_finite();
+        if !(positive(self.gate) && speed_ok) {
+            return Err(AppError::InvalidInput(format!(
+                "Gate must be positive and maximum speed non-negative: {} {}",
+                self.gate, self.max_speed_m_s
+            )));
+        }
+        let ConfirmationLogic { hits, window } = self.confirmation;
+        if hits == 0 || hits > window {
+            return Err(AppError::InvalidInput(format!(
+                "Confirmation needs between 1 and {} hits in its window: {}",
+                window, hits
+            )));
+        }
+        if let Some(imm) = &self.imm {
+            let settings = [
+                imm.cruise_noise_m_s2,
+                imm.turn_noise_m_s2,
+                imm.turn_rate_noise_deg_s2,
+                imm.hover_noise_m_s2,
+                imm.hover_damping_s,
+                imm.max_turn_rate_deg_s,
+                imm.mean_sojourn_s,
+            ];
+            if !settings.into_iter().all(positive) {
+                return Err(AppError::InvalidInput(format!(
+                    "IMM noises and times must be positive: {:?}",
+                    imm
+                )));
+            }
+        }
+        if let Some(particle) = &self.particle {
+            particle.validate()?;
+        }
+        if let AssociationMethod::Jpda {
+            detection_probability,
+            clutter_density,
+        } = self.association
+        {
+            if !(detection_probability > 0.0
+                && detection_probability <= 1.0
+                && positive(clutter_density))
+            {
+                return Err(AppError::InvalidInput(format!(
+                    "JPDA needs a detection probability in (0, 1] and a positive clutter density: {} {}",
+                    detection_probability, clutter_density
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// State estimator behind a track
+#[derive(Debug, Clone)]
+pub enum TrackFilter {
+    Ekf(ExtendedKalmanFilter),
+    Imm(ImmFilter),
+    Particle(Box<ParticleFilter>),
+}
+
+impl TrackFilter {
+    /// Start the filter `config` asks for (particle filter, IMM or
+    /// single-model EKF) from one measurement
+    pub fn initialize(config: &TrackerConfig, measurement: &PolarMeasurement) -> Self {
+        let noise = &config.measurement_noise;
+        match (config.particle, config.imm) {
+            (Some(particle), _) => TrackFilter::Particle(Box::new(ParticleFilter::initialize(
+                particle,
+                measurement,
+                noise,
+                config.max_speed_m_s,
+            ))),
+            (None, Some(imm)) => TrackFilter::Imm(ImmFilter::initialize(
+                imm,
+                measurement,
+                noise,
+                config.max_speed_m_s,
+            )),
+            (None, None) => TrackFilter::Ekf(ExtendedKalmanFilter::initialize(
+                config.model,
+                measurement,
+                noise,
+                config.process_noise,
+                config.max_speed_m_s,
+            )),
+        }
+    }
+
+    pub fn predict(&mut self, dt: f64) {
+        match self {
+            TrackFilter::Ekf(filter) => filter.predict(dt),
+            TrackFilter::Imm(filter) => filter.predict(dt),
+            TrackFilter::Particle(filter) => filter.predict(dt),
+        }
+    }
+
+    /// Gating statistics of a measurement against the predicted estimate
+    pub fn score(
+        &self,
+        measurement: &PolarMeasurement,
+        noise: &MeasurementNoise,
+    ) -> Option<PairScore> {
+        match self {
+            TrackFilter::Ekf(filter) => {
+                pair_score(&filter.state, &filter.covariance, measurement, noise)
+            }
+            TrackFilter::Imm(filter) => {
+                let (state, covariance) = filter.estimate();
+                pair_score(&state, &covariance, measurement, noise)
+            }
+            TrackFilter::Particle(filter) => filter.score(measurement, noise),
+        }
+    }
+
+    /// Update with measurements weighted by their association probabilities
+    pub fn update(&mut self, measurements: &[(&PolarMeasurement, f64)], noise: &MeasurementNoise) {
+        match self {
+            TrackFilter::Ekf(filter) => filter.update_weighted(measurements, noise),
+            TrackFilter::Imm(filter) => filter.update_weighted(measurements, noise),
+            TrackFilter::Particle(filter) => filter.update(measurements, noise),
+        }
+    }
+
+    /// State estimate and covariance; combined over the modes for an IMM,
+    /// and the particles' weighted moments for a particle filter
+    pub fn estimate(&self) -> (Array1<f64>, Array2<f64>) {
+        match self {
+            TrackFilter::Ekf(filter) => (filter.state.clone(), filter.covariance.clone()),
+            TrackFilter::Imm(filter) => filter.estimate(),
+            TrackFilter::Particle(filter) => filter.estimate(),
+        }
+    }
+}
+
+/// Vertical acceleration noise of the height filter; drones climb and
+/// descend gently compared with their horizontal maneuvers
+const HEIGHT_PROCESS_NOISE_M_S2: f64 = 0.5;
+/// Climb-rate uncertainty of a new track
+const INITIAL_CLIMB_RATE_STD_M_S: f64 = 5.0;
+
+/// Constant-velocity Kalman filter on a track's height above the radar,
+/// decoupled from the horizontal filter and updated from the measured
+/// elevation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeightFilter {
+    /// `[height, climb rate]`
+    pub state: [f64; 2],
+    pub covariance: [[f64; 2]; 2],
+}
+
+impl HeightFilter {
+    pub fn initialize(measurement: &PolarMeasurement, noise: &MeasurementNoise) -> Self {
+        Self {
+            state: [measurement.height_m(), 0.0],
+            covariance: [
+                [Self::measurement_variance(measurement, noise), 0.0],
+                [0.0, INITIAL_CLIMB_RATE_STD_M_S.powi(2)],
+            ],
+        }
+    }
+
+    /// Height error of a measurement: the elevation error across the slant range
+    fn measurement_variance(measurement: &PolarMeasurement, noise: &MeasurementNoise) -> f64 {
+        (measurement.ground_range_m() * noise.elevation_std_deg.to_radians()).powi(2)
+            + (measurement.elevation_deg.to_radians().sin() * noise.range_std_m).powi(2)
+    }
+
+    pub fn predict(&mut self, dt: f64) {
+        let [[p00, p01], [_, p11]] = self.covariance;
+        let q = HEIGHT_PROCESS_NOISE_M_S2.powi(2);
+        self.state[0] += self.state[1] * dt;
+        let p00 = p00 + 2.0 * dt * p01 + dt * dt * p11 + 0.25 * dt.powi(4) * q;
+        let p01 = p01 + dt * p11 + 0.5 * dt.powi(3) * q;
+        let p11 = p11 + dt * dt * q;
+        self.covariance = [[p00, p01], [p01, p11]];
+    }
+
+    pub fn update(&mut self, measurement: &PolarMeasurement, noise: &MeasurementNoise) {
+        let [[p00, p01], [_, p11]] = self.covariance;
+        let s = p00 + Self::measurement_variance(measurement, noise);
+        if s <= 0.0 {
+            return;
+        }
+        let gain = [p00 / s, p01 / s];
+        let innovation = measurement.height_m() - self.state[0];
+        self.state[0] += gain[0] * innovation;
+        self.state[1] += gain[1] * innovation;
+        let p01_new = (1.0 - gain[0]) * p01;
+        self.covariance = [
+            [(1.0 - gain[0]) * p00, p01_new],
+            [p01_new, p11 - gain[1] * p01],
+        ];
+    }
+}
+
+/// A track maintained by the [`Tracker`]
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub id: u32,
+    /// Horizontal state estimator
+    pub filter: TrackFilter,
+    pub height: HeightFilter,
+    /// Last RCS associated with the track
+    pub rcs: f64,
+    /// Updates with an associated measurement
+    pub hits: u32,
+    /// Consecutive updates without one
+    pub misses: u32,
+    /// Scans since the track was started, including the first
+    pub scans: u32,
+    /// Lifecycle state
+    pub status: TrackStatus,
+}
+
+/// Published estimate of a track.
+///
+/// The polar fields mirror [`TargetPosition`] so a track can be displayed and
+/// analysed like a target.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrackEstimate {
+    pub id: u32,
+    /// Slant range
+    pub range_m: f64,
+    pub azimuth_deg: f64,
+    pub elevation_deg: f64,
+    /// Height above the radar
+    pub altitude_m: f64,
+    /// Estimated radial velocity (positive = moving away)
+    pub vel_m_s: f64,
+    /// Horizontal velocity from the state, climb rate from the height filter;
+    /// in the radar's local frame, or east-north-up once `geodetic` is set
+    pub velocity: Velocity3,
+    /// Position on the WGS84 ellipsoid, when the session asks for geodetic tracks
+    #[serde(default)]
+    pub geodetic: Option<GeodeticPosition>,
+    pub rcs: f64,
+    /// Motion model of a single-model track; none for an IMM track
+    pub model: Option<MotionModel>,
+    /// IMM mode probabilities; empty for a single-model track
+    pub mode_probabilities: Vec<ModeProbability>,
+    /// Cartesian state: `[x, y, vx, vy]`, plus `[ax, ay]` for constant
+    /// acceleration or `[turn_rate]` for an IMM track
+    pub state: Vec<f64>,
+    /// State covariance, row-major
+    pub covariance: Vec<Vec<f64>>,
+    pub hits: u32,
+    pub misses: u32,
+    /// Lifecycle state
+    pub status: TrackStatus,
+}
+
+impl TrackEstimate {
+    /// Attach the track's geodetic position and turn its velocity to
+    /// east-north-up, the frame [`RadarSite::resolve`] reads a geodetic
+    /// target's velocity in. The Cartesian `state` stays in the radar frame.
+    pub fn georeference(&mut self, site: &RadarSite) {
+        self.geodetic =
+            Some(site.local_to_geodetic([self.state[0], self.state[1], self.altitude_m]));
+        let [vx_m_s, vy_m_s, vz_m_s] = site.local_to_enu([
+            self.velocity.vx_m_s,
+            self.velocity.vy_m_s,
+            self.velocity.vz_m_s,
+        ]);
+        self.velocity = Velocity3 {
+            vx_m_s,
+            vy_m_s,
+            vz_m_s,
+        };
+    }
+}
+
+impl Track {
+    /// Track started from one measurement; tentative unless a single hit confirms
+    pub fn start(id: u32, config: &TrackerConfig, measurement: &PolarMeasurement) -> Self {
+        Self {
+            id,
+            filter: TrackFilter::initialize(config, measurement),
+            height: HeightFilter::initialize(measurement, &config.measurement_noise),
+            rcs: measurement.rcs,
+            hits: 1,
+            misses: 0,
+            scans: 1,
+            status: if config.confirmation.hits <= 1 {
+                TrackStatus::Confirmed
+            } else {
+                TrackStatus::Tentative
+            },
+        }
+    }
+
+    /// Predict the horizontal and height filters `dt` seconds ahead
+    pub fn predict(&mut self, dt: f64) {
+        self.filter.predict(dt);
+        self.height.predict(dt);
+    }
+
+    /// Count a scan with `measurement` as the most likely origin and update
+    /// the height from it; the horizontal filter update is up to the caller
+    pub fn hit(&mut self, measurement: &PolarMeasurement, config: &TrackerConfig) {
+        self.rcs = measurement.rcs;
+        self.height.update(measurement, &config.measurement_noise);
+        self.hits += 1;
+        self.misses = 0;
+        self.scans += 1;
+        self.status = match self.status {
+            TrackStatus::Tentative if self.hits < config.confirmation.hits => {
+                TrackStatus::Tentative
+            }
+            TrackStatus::Deleted => TrackStatus::Deleted,
+            _ => TrackStatus::Confirmed,
+        };
+    }
+
+    /// Count a scan without an associated measurement
+    pub fn miss(&mut self, config: &TrackerConfig) {
+        self.misses += 1;
+        self.scans += 1;
+        self.status = match self.status {
+            TrackStatus::Tentative
+                if config.confirmation.reachable(self.hits, self.scans)
+                    && self.misses <= config.max_misses =>
+            {
+                TrackStatus::Tentative
+            }
+            TrackStatus::Confirmed | TrackStatus::Coasting if self.misses <= config.max_misses => {
+                TrackStatus::Coasting
+            }
+            _ => TrackStatus::Deleted,
+        };
+    }
+
+    pub fn estimate(&self) -> TrackEstimate {
+        let (state, covariance) = self.filter.estimate();
+        let z = polar_measurement(&state);
+        let (model, mode_probabilities) = match &self.filter {
+            TrackFilter::Ekf(filter) => (Some(filter.model), Vec::new()),
+            TrackFilter::Imm(filter) => (None, filter.mode_probabilities()),
+            TrackFilter::Particle(_) => (Some(MotionModel::ConstantVelocity), Vec::new()),
+        };
+        let [height, climb_rate] = self.height.state;
+        let velocity = Velocity3 {
+            vx_m_s: state[2],
+            vy_m_s: state[3],
+            vz_m_s: climb_rate,
+        };
+        TrackEstimate {
+            id: self.id,
+            range_m: z[0].hypot(height),
+            azimuth_deg: z[1].to_degrees().rem_euclid(360.0),
+            elevation_deg: height.atan2(z[0]).to_degrees(),
+            altitude_m: height,
+            vel_m_s: velocity.radial_m_s([state[0], state[1], height]),
+            velocity,
+            geodetic: None,
+            rcs: self.rcs,
+            model,
+            mode_probabilities,
+            state: state.to_vec(),
+            covariance: covariance.outer_iter().map(|row| row.to_vec()).collect(),
+            hits: self.hits,
+            misses: self.misses,
+            status: self.status,
+        }
+    }
+}
+
+/// Multi-target tracker: EKF or IMM per track, gated association by the
+/// configured [`AssociationMethod`], and a tentative track for every
+/// unassociated measurement. Tracks are confirmed by M-of-N logic, coast
+/// through misses and are deleted after `max_misses` consecutive ones.
+#[derive(Debug, Clone, Default)]
+pub struct Tracker {
+    pub config: TrackerConfig,
+    tracks: Vec<Track>,
+    next_id: u32,
+}
+
+impl Tracker {
+    pub fn new(config: TrackerConfig) -> Self {
+        Self {
+            config,
+            tracks: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    pub fn estimates(&self) -> Vec<TrackEstimate> {
+        self.tracks.iter().map(Track::estimate).collect()
+    }
+
+    /// Advance all tracks by `dt` seconds and process one scan of measurements
+    pub fn step(&mut self, measurements: &[PolarMeasurement], dt: f64) {
+        let noise = self.config.measurement_noise;
+        self.tracks
+            .retain(|track| track.status != TrackStatus::Deleted);
+        for track in &mut self.tracks {
+            track.predict(dt);
+        }
+
+        let gate = self.config.gate;
+        let scores: Vec<Vec<Option<PairScore>>> = self
+            .tracks
+            .iter()
+            .map(|track| {
+                measurements
+                    .iter()
+                    .map(|z| track.filter.score(z, &noise).filter(|s| s.d2 <= gate))
+                    .collect()
+            })
+            .collect();
+        let association = self
+            .config
+            .association
+            .associator()
+            .associate(&scores, measurements.len());
+
+        for (track, weights) in self.tracks.iter_mut().zip(&association.weights) {
+            let Some(&(strongest, _)) = weights.iter().max_by(|a, b| a.1.total_cmp(&b.1)) else {
+                track.miss(&self.config);
+                continue;
+            };
+            let weighted: Vec<(&PolarMeasurement, f64)> = weights
+                .iter()
+                .map(|&(m, w)| (&measurements[m], w))
+                .collect();
+            track.filter.update(&weighted, &noise);
+            track.hit(&measurements[strongest], &self.config);
+        }
+
+        for (z, used) in measurements.iter().zip(association.used) {
+            if !used {
+                self.tracks
+                    .push(Track::start(self.next_id, &self.config, z));
+                self.next_id += 1;
+            }
+        }
+    }
+}
+
+/// Multi-target tracking strategy fed one scan at a time, so callers can
+/// switch trackers by configuration
+pub trait MultiTargetTracker: Send {
+    /// Filter, gate and lifecycle settings of the tracks
+    fn tracker_config(&self) -> &TrackerConfig;
+
+    /// Advance all tracks by `dt` seconds and process one scan of measurements
+    fn step(&mut self, measurements: &[PolarMeasurement], dt: f64);
+
+    /// Estimates of the current tracks
+    fn estimates(&self) -> Vec<TrackEstimate>;
+}
+
+impl MultiTargetTracker for Tracker {
+    fn tracker_config(&self) -> &TrackerConfig {
+        &self.config
+    }
+
+    fn step(&mut self, measurements: &[PolarMeasurement], dt: f64) {
+        Tracker::step(self, measurements, dt)
+    }
+
+    fn estimates(&self) -> Vec<TrackEstimate> {
+        Tracker::estimates(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::imm::FlightBehavior;
+    use crate::trajectory::ManeuveringTarget;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Truth moving in a straight line; returns the target and its Cartesian velocity
+    fn straight_line(t: f64) -> (TargetPosition, (f64, f64)) {
+        let (x0, y0, vx, vy) = (2_000.0, 6_000.0, 12.0, -8.0);
+        let (x, y) = (x0 + vx * t, y0 + vy * t);
+        let r = x.hypot(y);
+        let target = TargetPosition {
+            range_m: r,
+            azimuth_deg: x.atan2(y).to_degrees(),
+            vel_m_s: (x * vx + y * vy) / r,
+            rcs: 0.1,
+            ..TargetPosition::default()
+        };
+        (target, (vx, vy))
+    }
+
+    #[test]
+    fn test_transition_and_noise_shapes() {
+        let cv = MotionModel::ConstantVelocity;
+        let ca = MotionModel::ConstantAcceleration;
+        assert_eq!(cv.transition(0.5)[[0, 2]], 0.5);
+        assert_eq!(ca.transition(2.0)[[1, 5]], 2.0);
+        let q = cv.process_noise(1.0, 2.0);
+        // Position variance (dt²/2)² σ², velocity variance dt² σ²
+        assert!((q[[0, 0]] - 1.0).abs() < 1e-12);
+        assert!((q[[2, 2]] - 4.0).abs() < 1e-12);
+        assert_eq!(q[[0, 1]], 0.0);
+    }
+
+    #[test]
+    fn test_jacobian_matches_finite_differences() {
+        let z = PolarMeasurement {
+            range_m: 5_000.0,
+            azimuth_deg: 30.0,
+            elevation_deg: 0.0,
+            radial_velocity_m_s: -10.0,
+            rcs: 1.0,
+        };
+        let mut filter = ExtendedKalmanFilter::initialize(
+            MotionModel::ConstantVelocity,
+            &z,
+            &MeasurementNoise::default(),
+            1.0,
+            50.0,
+        );
+        filter.state[2] += 7.0;
+        let h = polar_jacobian(&filter.state);
+        for j in 0..4 {
+            let step = 1e-4;
+            let mut plus = filter.clone();
+            plus.state[j] += step;
+            let mut minus = filter.clone();
+            minus.state[j] -= step;
+            let numeric =
+                (plus.predicted_measurement() - minus.predicted_measurement()) / (2.0 * step);
+            for i in 0..3 {
+                assert!((h[[i, j]] - numeric[i]).abs() < 1e-6, "H[{i},{j}]");
+            }
+        }
+    }
+
+    #[test]
+    fn test_filter_converges_on_straight_line() {
+        let noise = MeasurementNoise::default();
+        let mut rng = StdRng::seed_from_u64(41);
+        // Acceleration noise for CV in m/s², jerk noise for CA in m/s³
+        for (model, intensity) in [
+            (MotionModel::ConstantVelocity, 0.5),
+            (MotionModel::ConstantAcceleration, 0.05),
+        ] {
+            let (first, _) = straight_line(0.0);
+            let mut filter = ExtendedKalmanFilter::initialize(
+                model,
+                &measure(&first, &noise, &mut rng),
+                &noise,
+                intensity,
+                50.0,
+            );
+            let mut nis = Vec::new();
+            for step in 1..=200 {
+                let (truth, _) = straight_line(step as f64);
+                filter.predict(1.0);
+                nis.push(filter.update(&measure(&truth, &noise, &mut rng), &noise));
+            }
+            let (truth, (vx, vy)) = straight_line(200.0);
+            let pos_err = (filter.state[0] - truth.range_m * truth.azimuth_deg.to_radians().sin())
+                .hypot(filter.state[1] - truth.range_m * truth.azimuth_deg.to_radians().cos());
+            let vel_err = (filter.state[2] - vx).hypot(filter.state[3] - vy);
+            // Single measurements are ~52 m off in cross-range at this distance
+            assert!(pos_err < 50.0, "{model:?} position error {pos_err}");
+            let vel_std = (filter.covariance[[2, 2]] + filter.covariance[[3, 3]]).sqrt();
+            assert!(
+                vel_err < 3.0 && vel_err < 3.0 * vel_std,
+                "{model:?} velocity error {vel_err}, std {vel_std}"
+            );
+            // Consistency: mean NIS near the measurement dimension
+            let mean_nis = nis[20..].iter().sum::<f64>() / (nis.len() - 20) as f64;
+            assert!(
+                (1.5..5.0).contains(&mean_nis),
+                "{model:?} mean NIS {mean_nis}"
+            );
+            // Reported covariance covers the error
+            let std = filter.covariance[[0, 0]]
+                .sqrt()
+                .max(filter.covariance[[1, 1]].sqrt());
+            assert!(pos_err < 4.0 * std * 2f64.sqrt());
+        }
+    }
+
+    #[test]
+    fn test_tracker_keeps_ids_and_drops_lost_tracks() {
+        let config = TrackerConfig::default();
+        let noise = config.measurement_noise;
+        let mut tracker = Tracker::new(config);
+        let mut rng = StdRng::seed_from_u64(42);
+        let second = TargetPosition {
+            id: 1,
+            range_m: 9_000.0,
+            azimuth_deg: 200.0,
+            rcs: 1.0,
+            ..TargetPosition::default()
+        };
+        for step in 0..30 {
+            let (first, _) = straight_line(step as f64);
+            let scan = [
+                measure(&first, &noise, &mut rng),
+                measure(&second, &noise, &mut rng),
+            ];
+            tracker.step(&scan, 1.0);
+        }
+        let ids: Vec<u32> = tracker.tracks().iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![0, 1], "no spurious tracks");
+        let estimate = tracker.tracks()[1].estimate();
+        assert!((estimate.range_m - 9_000.0).abs() < 20.0);
+        assert!((estimate.azimuth_deg - 200.0).abs() < 0.5);
+        assert_eq!(estimate.covariance.len(), 4);
+
+        // The second target disappears: its track is dropped after max_misses
+        for step in 30..35 {
+            let (first, _) = straight_line(step as f64);
+            tracker.step(&[measure(&first, &noise, &mut rng)], 1.0);
+        }
+        let ids: Vec<u32> = tracker.tracks().iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![0]);
+    }
+
+    #[test]
+    fn test_m_of_n_confirmation_and_coasting() {
+        let config = TrackerConfig::default();
+        let noise = config.measurement_noise;
+        let mut tracker = Tracker::new(config);
+        let mut rng = StdRng::seed_from_u64(18);
+        let status = |tracker: &Tracker, id: u32| {
+            tracker
+                .tracks()
+                .iter()
+                .find(|t| t.id == id)
+                .map(|t| t.status)
+        };
+
+        // A lone false alarm stays tentative while 3 of 5 is within reach
+        let (first, _) = straight_line(0.0);
+        let false_alarm = PolarMeasurement {
+            range_m: 15_000.0,
+            azimuth_deg: 300.0,
+            elevation_deg: 0.0,
+            radial_velocity_m_s: 20.0,
+            rcs: 0.01,
+        };
+        tracker.step(&[measure(&first, &noise, &mut rng), false_alarm], 1.0);
+        assert_eq!(status(&tracker, 0), Some(TrackStatus::Tentative));
+        assert_eq!(status(&tracker, 1), Some(TrackStatus::Tentative));
+
+        // Hit, miss, hit: the target's third hit within five scans confirms it
+        let mut expected = vec![];
+        for (step, detected) in [(1, true), (2, false), (3, true)] {
+            let (truth, _) = straight_line(step as f64);
+            let scan: Vec<_> = detected
+                .then(|| measure(&truth, &noise, &mut rng))
+                .into_iter()
+                .collect();
+            tracker.step(&scan, 1.0);
+            expected.push(status(&tracker, 1));
+        }
+        assert_eq!(status(&tracker, 0), Some(TrackStatus::Confirmed));
+        // With one scan left, the false alarm can no longer reach three hits
+        assert_eq!(
+            expected,
+            vec![
+                Some(TrackStatus::Tentative),
+                Some(TrackStatus::Tentative),
+                Some(TrackStatus::Deleted)
+            ]
+        );
+
+        // A lost target coasts through max_misses scans, is reported deleted once, then dropped
+        let mut statuses = Vec::new();
+        for _ in 0..5 {
+            tracker.step(&[], 1.0);
+            statuses.push(status(&tracker, 0));
+        }
+        assert_eq!(
+            statuses,
+            vec![
+                Some(TrackStatus::Coasting),
+                Some(TrackStatus::Coasting),
+                Some(TrackStatus::Coasting),
+                Some(TrackStatus::Deleted),
+                None
+            ]
+        );
+        assert_eq!(tracker.estimates().len(), 0);
+    }
+
+    #[test]
+    fn test_detection_model_misses_and_false_alarms() {
+        let noise = MeasurementNoise::default();
+        let mut rng = StdRng::seed_from_u64(19);
+        let (target, _) = straight_line(0.0);
+        let targets = vec![target; 4];
+
+        let perfect = DetectionModel {
+            detection_probability: 1.0,
+            false_alarms_per_scan: 0.0,
+            ..DetectionModel::default()
+        };
+        assert_eq!(perfect.scan(&targets, &noise, &mut rng).len(), 4);
+
+        let clutter_only = DetectionModel {
+            detection_probability: 0.0,
+            false_alarms_per_scan: 2.0,
+            ..DetectionModel::default()
+        };
+        let scans: Vec<Vec<PolarMeasurement>> = (0..2_000)
+            .map(|_| clutter_only.scan(&targets, &noise, &mut rng))
+            .collect();
+        let mean = scans.iter().map(Vec::len).sum::<usize>() as f64 / scans.len() as f64;
+        assert!((mean - 2.0).abs() < 0.1, "mean false alarms {mean}");
+        assert!(scans
+            .iter()
+            .flatten()
+            .all(|z| z.range_m <= 20_000.0 && z.radial_velocity_m_s.abs() <= 50.0));
+
+        let mut rng = StdRng::seed_from_u64(20);
+        let detected: usize = (0..1_000)
+            .map(|_| {
+                DetectionModel::default()
+                    .scan(&targets[..1], &noise, &mut rng)
+                    .len()
+            })
+            .sum();
+        // 0.9 detections plus 0.5 false alarms per scan
+        assert!((1_300..1_500).contains(&detected), "{detected}");
+    }
+
+    #[test]
+    fn test_detection_probability_falls_with_range() {
+        let noise = MeasurementNoise::default();
+        let mut rng = StdRng::seed_from_u64(18);
+        let model = DetectionModel {
+            radar: Some(RadarConfig::default()),
+            false_alarms_per_scan: 0.0,
+            horizon: None,
+            ..DetectionModel::default()
+        };
+        let at = |range_m: f64, rcs: f64| TargetPosition {
+            range_m,
+            rcs,
+            ..straight_line(0.0).0
+        };
+
+        let pds: Vec<f64> = [5_000.0, 20_000.0, 40_000.0]
+            .iter()
+            .map(|&range_m| model.probability_of_detection(&at(range_m, 0.1)))
+            .collect();
+        assert!(pds[0] > 0.99, "{pds:?}");
+        assert!((0.2..0.8).contains(&pds[1]), "{pds:?}");
+        assert!(pds[2] < 1e-3, "{pds:?}");
+        // A larger RCS is seen farther out
+        assert!(model.probability_of_detection(&at(20_000.0, 1.0)) > 0.99);
+
+        // Detections follow the link budget scan by scan
+        let detected = |range_m: f64, rng: &mut StdRng| -> usize {
+            (0..500)
+                .map(|_| model.scan(&[at(range_m, 0.1)], &noise, rng).len())
+                .sum()
+        };
+        assert_eq!(detected(5_000.0, &mut rng), 500);
+        let expected = 500.0 * pds[1];
+        let mid = detected(20_000.0, &mut rng) as f64;
+        assert!(
+            (mid - expected).abs() < 4.0 * expected.sqrt(),
+            "{mid} vs {expected}"
+        );
+        assert_eq!(detected(80_000.0, &mut rng), 0);
+    }
+
+    #[test]
+    fn test_detection_model_respects_radar_horizon() {
+        let noise = MeasurementNoise::default();
+        let mut rng = StdRng::seed_from_u64(23);
+        // 40 km out and 100 m below the radar's horizontal: about 36 m above
+        // the ground, where the 4/3 earth hides anything under 43 m
+        let low = TargetPosition {
+            range_m: 40_000.0,
+            elevation_deg: (-100f64).atan2(40_000.0).to_degrees(),
+            altitude_m: Some(-100.0),
+            ..straight_line(0.0).0
+        };
+        let perfect = DetectionModel {
+            detection_probability: 1.0,
+            false_alarms_per_scan: 0.0,
+            ..DetectionModel::default()
+        };
+        assert!(perfect
+            .scan(std::slice::from_ref(&low), &noise, &mut rng)
+            .is_empty());
+
+        let flat = DetectionModel {
+            horizon: None,
+            ..perfect
+        };
+        assert_eq!(flat.scan(&[low], &noise, &mut rng).len(), 1);
+    }
+
+    #[test]
+    fn test_height_filter_tracks_altitude() {
+        let config = TrackerConfig::default();
+        let noise = config.measurement_noise;
+        let mut tracker = Tracker::new(config);
+        let mut rng = StdRng::seed_from_u64(23);
+        let mut target = ManeuveringTarget::new(
+            TargetPosition {
+                range_m: 2_000.0,
+                azimuth_deg: 135.0,
+                altitude_m: Some(150.0),
+                rcs: 0.2,
+                ..TargetPosition::default()
+            },
+            0.0,
+            15.0,
+            Vec::new(),
+        );
+        for _ in 0..60 {
+            target.step(0.5);
+            tracker.step(&[measure(&target.report(), &noise, &mut rng)], 0.5);
+        }
+        let truth = target.report();
+        let estimate = &tracker.estimates()[0];
+        // A single measurement's height error is about 35 m at this range
+        assert!((estimate.altitude_m - 150.0).abs() < 15.0, "{estimate:?}");
+        assert!(estimate.velocity.vz_m_s.abs() < 2.0, "{estimate:?}");
+        assert!((estimate.elevation_deg - truth.elevation_deg).abs() < 0.5);
+        assert!((estimate.range_m - truth.range_m).abs() < 20.0);
+        let heading = estimate.velocity.heading_deg().unwrap();
+        assert!(!(10.0..=350.0).contains(&heading), "heading {heading}");
+        assert!((estimate.vel_m_s - truth.vel_m_s).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_georeferenced_track_analyses_like_its_target() {
+        let site: RadarSite = "52.0,4.5,30,90".parse().unwrap();
+        let config = TrackerConfig::default();
+        let noise = config.measurement_noise;
+        let mut tracker = Tracker::new(config);
+        let mut rng = StdRng::seed_from_u64(24);
+        for step in 0..30 {
+            let (truth, _) = straight_line(step as f64);
+            tracker.step(&[measure(&truth, &noise, &mut rng)], 1.0);
+        }
+        let local = tracker.estimates().remove(0);
+        let mut track = local.clone();
+        track.georeference(&site);
+        // The radar's zero azimuth points east, so its azimuth 90° is true south
+        assert!((track.velocity.vx_m_s - local.velocity.vy_m_s).abs() < 1e-9);
+        assert!((track.velocity.vy_m_s + local.velocity.vx_m_s).abs() < 1e-9);
+
+        // Posted back as a target, the track resolves to its own radar-frame motion
+        let json = serde_json::to_string(&track).unwrap();
+        let mut target: TargetPosition = serde_json::from_str(&json).unwrap();
+        site.resolve(&mut target);
+        let velocity = target.velocity.unwrap();
+        assert!((velocity.vx_m_s - local.velocity.vx_m_s).abs() < 1e-6);
+        assert!((velocity.vy_m_s - local.velocity.vy_m_s).abs() < 1e-6);
+        assert!((target.vel_m_s - local.vel_m_s).abs() < 1e-3);
+        assert!((target.range_m - local.range_m).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_imm_tracker_publishes_mode_probabilities() {
+        let config = TrackerConfig {
+            imm: Some(ImmConfig::default()),
+            ..TrackerConfig::default()
+        };
+        let noise = config.measurement_noise;
+        let mut tracker = Tracker::new(config);
+        let mut rng = StdRng::seed_from_u64(4);
+        let hovering = TargetPosition {
+            range_m: 3_000.0,
+            azimuth_deg: 60.0,
+            rcs: 0.2,
+            ..TargetPosition::default()
+        };
+        for _ in 0..40 {
+            tracker.step(&[measure(&hovering, &noise, &mut rng)], 0.5);
+        }
+        let estimates = tracker.estimates();
+        assert_eq!(estimates.len(), 1);
+        let estimate = &estimates[0];
+        assert_eq!(estimate.model, None);
+        assert_eq!(estimate.state.len(), 5);
+        assert_eq!(
+            FlightBehavior::from_mode_probabilities(&estimate.mode_probabilities),
+            Some(FlightBehavior::Hovering)
+        );
+        assert!((estimate.range_m - 3_000.0).abs() < 20.0);
+    }
+
+    #[test]
+    fn test_tracker_backends_switch_by_config() {
+        use crate::mht::{MhtConfig, MhtTracker};
+        use crate::particle::{MeasurementKind, ParticleFilterConfig, GATE_CHI2_1DOF_99};
+
+        let particle = |measurement| TrackerConfig {
+            particle: Some(ParticleFilterConfig {
+                measurement,
+                process_noise: 1.0,
+                ..ParticleFilterConfig::default()
+            }),
+            ..TrackerConfig::default()
+        };
+        let backends: Vec<Box<dyn MultiTargetTracker>> = vec![
+            Box::new(Tracker::new(TrackerConfig::default())),
+            Box::new(Tracker::new(TrackerConfig {
+                imm: Some(ImmConfig::default()),
+                ..TrackerConfig::default()
+            })),
+            Box::new(Tracker::new(particle(MeasurementKind::Polar))),
+            Box::new(Tracker::new(TrackerConfig {
+                gate: GATE_CHI2_1DOF_99,
+                ..particle(MeasurementKind::BearingOnly)
+            })),
+            Box::new(MhtTracker::new(MhtConfig::default())),
+        ];
+        let second = TargetPosition {
+            id: 1,
+            range_m: 9_000.0,
+            azimuth_deg: 200.0,
+            rcs: 1.0,
+            ..TargetPosition::default()
+        };
+        for mut tracker in backends {
+            let noise = tracker.tracker_config().measurement_noise;
+            let mut rng = StdRng::seed_from_u64(20);
+            for step in 0..20 {
+                let (first, _) = straight_line(step as f64);
+                let scan = [
+                    measure(&first, &noise, &mut rng),
+                    measure(&second, &noise, &mut rng),
+                ];
+                tracker.step(&scan, 1.0);
+            }
+            let (first, _) = straight_line(19.0);
+            let estimates = tracker.estimates();
+            assert_eq!(estimates.len(), 2, "{:?}", tracker.tracker_config());
+            for (estimate, truth) in estimates.iter().zip([&first, &second]) {
+                assert_eq!(estimate.status, TrackStatus::Confirmed);
+                assert!((estimate.azimuth_deg - truth.azimuth_deg.rem_euclid(360.0)).abs() < 1.0);
+                if tracker.tracker_config().particle.map(|p| p.measurement)
+                    != Some(MeasurementKind::BearingOnly)
+                {
+                    assert!((estimate.range_m - truth.range_m).abs() < 50.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_unbounded_tracker() {
+        assert!(TrackerConfig::default().validate().is_ok());
+        let invalid = [
+            TrackerConfig {
+                process_noise: 0.0,
+                ..TrackerConfig::default()
+            },
+            TrackerConfig {
+                measurement_noise: MeasurementNoise {
+                    range_std_m: -10.0,
+                    ..MeasurementNoise::default()
+                },
+                ..TrackerConfig::default()
+            },
+            TrackerConfig {
+                confirmation: ConfirmationLogic { hits: 6, window: 5 },
+                ..TrackerConfig::default()
+            },
+            TrackerConfig {
+                particle: Some(ParticleFilterConfig {
+                    num_particles: 1_000_000_000_000,
+                    ..ParticleFilterConfig::default()
+                }),
+                ..TrackerConfig::default()
+            },
+            TrackerConfig {
+                association: AssociationMethod::Jpda {
+                    detection_probability: 0.9,
+                    clutter_density: 0.0,
+                },
+                ..TrackerConfig::default()
+            },
+        ];
+        for config in invalid {
+            assert!(config.validate().is_err(), "{config:?}");
+        }
+    }
+}
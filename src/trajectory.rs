@@ -0,0 +1,294 @@
+use crate::types::{TargetPosition, Velocity3};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Default limit on how fast a target changes speed, typical of small multirotors
+pub const DEFAULT_ACCELERATION_M_S2: f64 = 3.0;
+
+/// One leg of a scripted flight
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Maneuver {
+    /// Straight flight at the cruise speed
+    Cruise { duration_s: f64 },
+    /// Constant-speed turn; positive rates turn clockwise (right)
+    Turn {
+        duration_s: f64,
+        turn_rate_deg_s: f64,
+    },
+    /// Hold position
+    Hover { duration_s: f64 },
+}
+
+impl Maneuver {
+    pub fn duration_s(&self) -> f64 {
+        match *self {
+            Maneuver::Cruise { duration_s }
+            | Maneuver::Turn { duration_s, .. }
+            | Maneuver::Hover { duration_s } => duration_s,
+        }
+    }
+}
+
+/// Target flying a repeating sequence of maneuvers at constant altitude.
+///
+/// Positions use the tracking frame: `x` east, `y` north, `z` up, heading
+/// clockwise from north.
+#[derive(Debug, Clone)]
+pub struct ManeuveringTarget {
+    /// Identity, RCS and signature; the geometry and velocity are replaced
+    /// by the flight in every [`ManeuveringTarget::report`]
+    pub template: TargetPosition,
+    pub x_m: f64,
+    pub y_m: f64,
+    /// Height above the radar
+    pub z_m: f64,
+    pub heading_deg: f64,
+    /// Current speed
+    pub speed_m_s: f64,
+    /// Speed held whenever the target is not hovering
+    pub cruise_speed_m_s: f64,
+    /// Rate at which the speed moves towards zero on hovering and back to the
+    /// cruise speed afterwards
+    pub max_acceleration_m_s2: f64,
+    plan: Vec<Maneuver>,
+    leg: usize,
+    leg_elapsed_s: f64,
+}
+
+impl ManeuveringTarget {
+    /// Start at the template's slant range, azimuth and altitude, at rest if
+    /// the first leg is a hover and at the cruise speed otherwise. Legs
+    /// without a positive duration are dropped, and an empty plan cruises
+    /// forever.
+    pub fn new(
+        template: TargetPosition,
+        heading_deg: f64,
+        cruise_speed_m_s: f64,
+        plan: Vec<Maneuver>,
+    ) -> Self {
+        let azimuth = template.azimuth_deg.to_radians();
+        let plan: Vec<Maneuver> = plan.into_iter().filter(|m| m.duration_s() > 0.0).collect();
+        let speed_m_s = match plan.first() {
+            Some(Maneuver::Hover { .. }) => 0.0,
+            _ => cruise_speed_m_s,
+        };
+        let altitude_m = template.altitude_m();
+        let ground_m = (template.range_m.powi(2) - altitude_m.powi(2))
+            .max(0.0)
+            .sqrt();
+        Self {
+            x_m: ground_m * azimuth.sin(),
+            y_m: ground_m * azimuth.cos(),
+            z_m: altitude_m,
+            template,
+            heading_deg,
+            speed_m_s,
+            cruise_speed_m_s,
+            max_acceleration_m_s2: DEFAULT_ACCELERATION_M_S2,
+            plan,
+            leg: 0,
+            leg_elapsed_s: 0.0,
+        }
+    }
+
+    /// Maneuver currently being flown
+    pub fn current(&self) -> Maneuver {
+        self.plan
+            .get(self.leg)
+            .copied()
+            .unwrap_or(Maneuver::Cruise {
+                duration_s: f64::INFINITY,
+            })
+    }
+
+    /// Horizontal position `(x, y)`
+    pub fn position(&self) -> (f64, f64) {
+        (self.x_m, self.y_m)
+    }
+
+    /// Horizontal velocity `(vx, vy)`
+    pub fn velocity(&self) -> (f64, f64) {
+        let heading = self.heading_deg.to_radians();
+        (
+            self.speed_m_s * heading.sin(),
+            self.speed_m_s * heading.cos(),
+        )
+    }
+
+    /// Fly `dt` seconds, moving on through the plan as legs end
+    pub fn step(&mut self, dt: f64) {
+        let mut remaining = dt;
+        while remaining > 0.0 {
+            let maneuver = self.current();
+            let t = (maneuver.duration_s() - self.leg_elapsed_s).min(remaining);
+            self.fly(maneuver, t);
+            remaining -= t;
+            self.leg_elapsed_s += t;
+            if self.leg_elapsed_s >= maneuver.duration_s() {
+                self.leg = (self.leg + 1) % self.plan.len().max(1);
+                self.leg_elapsed_s = 0.0;
+            }
+        }
+    }
+
+    fn fly(&mut self, maneuver: Maneuver, t: f64) {
+        // Change speed at the acceleration limit, then hold it
+        let target = match maneuver {
+            Maneuver::Hover { .. } => 0.0,
+            _ => self.cruise_speed_m_s,
+        };
+        let change = target - self.speed_m_s;
+        let ramp = (change.abs() / self.max_acceleration_m_s2).min(t);
+        let reached = if ramp < t {
+            target
+        } else {
+            self.speed_m_s + change.signum() * self.max_acceleration_m_s2 * ramp
+        };
+        self.advance(maneuver, ramp, 0.5 * (self.speed_m_s + reached));
+        self.speed_m_s = reached;
+        self.advance(maneuver, t - ramp, reached);
+    }
+
+    /// Move for `t` seconds at `speed`, turning on a turn leg
+    fn advance(&mut self, maneuver: Maneuver, t: f64, speed: f64) {
+        if t <= 0.0 {
+            return;
+        }
+        let heading = self.heading_deg.to_radians();
+        match maneuver {
+            Maneuver::Turn {
+                turn_rate_deg_s, ..
+            } if turn_rate_deg_s != 0.0 => {
+                // Exact arc: integrate (sin h, cos h) with h advancing at the turn rate
+                let rate = turn_rate_deg_s.to_radians();
+                let end = heading + rate * t;
+                self.x_m += speed * (heading.cos() - end.cos()) / rate;
+                self.y_m += speed * (end.sin() - heading.sin()) / rate;
+                self.heading_deg = (self.heading_deg + turn_rate_deg_s * t).rem_euclid(360.0);
+            }
+            _ => {
+                self.x_m += speed * heading.sin() * t;
+                self.y_m += speed * heading.cos() * t;
+            }
+        }
+    }
+
+    /// Current position and velocity as seen by the radar
+    pub fn report(&self) -> TargetPosition {
+        let (x, y) = self.position();
+        let (vx_m_s, vy_m_s) = self.velocity();
+        let velocity = Velocity3 {
+            vx_m_s,
+            vy_m_s,
+            vz_m_s: 0.0,
+        };
+        let ground_m = x.hypot(y);
+        TargetPosition {
+            range_m: ground_m.hypot(self.z_m),
+            azimuth_deg: x.atan2(y).to_degrees().rem_euclid(360.0),
+            elevation_deg: self.z_m.atan2(ground_m).to_degrees(),
+            altitude_m: Some(self.z_m),
+            vel_m_s: velocity.radial_m_s([x, y, self.z_m]),
+            velocity: Some(velocity),
+            geodetic: None,
+            ..self.template.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(range_m: f64, azimuth_deg: f64) -> TargetPosition {
+        TargetPosition {
+            id: 3,
+            range_m,
+            azimuth_deg,
+            rcs: 0.5,
+            ..TargetPosition::default()
+        }
+    }
+
+    #[test]
+    fn test_square_loop_returns_to_start() {
+        // Four right-angle turns close a loop
+        let mut square = ManeuveringTarget::new(
+            template(5_000.0, 45.0),
+            0.0,
+            20.0,
+            vec![
+                Maneuver::Cruise { duration_s: 10.0 },
+                Maneuver::Turn {
+                    duration_s: 10.0,
+                    turn_rate_deg_s: 9.0,
+                },
+                Maneuver::Hover { duration_s: 5.0 },
+            ],
+        );
+        // Instant speed changes keep every leg exact
+        square.max_acceleration_m_s2 = f64::INFINITY;
+        let mut target = square;
+        let start = target.position();
+        for _ in 0..1_000 {
+            target.step(0.1);
+        }
+        let end = target.position();
+        assert!((end.0 - start.0).hypot(end.1 - start.1) < 1e-6);
+        assert!(target.heading_deg.abs() < 1e-6 || (target.heading_deg - 360.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_report_radial_velocity() {
+        // Northbound target due north of the radar moves straight away
+        let mut target = ManeuveringTarget::new(
+            template(1_000.0, 0.0),
+            0.0,
+            15.0,
+            vec![
+                Maneuver::Cruise { duration_s: 2.0 },
+                Maneuver::Hover { duration_s: 10.0 },
+            ],
+        );
+        target.step(1.0);
+        let report = target.report();
+        assert!((report.range_m - 1_015.0).abs() < 1e-9);
+        assert!(report.azimuth_deg.abs() < 1e-9);
+        assert!((report.vel_m_s - 15.0).abs() < 1e-9);
+        assert_eq!(report.id, 3);
+
+        // A step spanning the end of the leg: 1 s of cruise, then 0.5 s of
+        // braking at 3 m/s² down to 13.5 m/s
+        target.step(1.5);
+        let report = target.report();
+        assert!((report.range_m - (1_030.0 + 0.5 * 14.25)).abs() < 1e-9);
+        assert!((report.vel_m_s - 13.5).abs() < 1e-9);
+        assert!(matches!(target.current(), Maneuver::Hover { .. }));
+
+        // Stopped 5 s into the hover, and picking up speed again after it
+        target.step(5.0);
+        assert_eq!(target.report().vel_m_s, 0.0);
+        target.step(5.5);
+        assert!(matches!(target.current(), Maneuver::Cruise { .. }));
+        assert!((target.report().vel_m_s - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_report_geometry_at_altitude() {
+        // Eastbound at 300 m, due north of the radar: crossing, so no radial velocity
+        let mut high = template(5_000.0, 0.0);
+        high.altitude_m = Some(300.0);
+        let target = ManeuveringTarget::new(high, 90.0, 20.0, Vec::new());
+        let report = target.report();
+        assert!((report.range_m - 5_000.0).abs() < 1e-9);
+        assert!((report.elevation_deg - (300.0f64 / 5_000.0).asin().to_degrees()).abs() < 1e-9);
+        assert_eq!(report.altitude_m, Some(300.0));
+        assert!(report.vel_m_s.abs() < 1e-9);
+        let velocity = report.velocity.unwrap();
+        assert!((velocity.heading_deg().unwrap() - 90.0).abs() < 1e-9);
+        assert!((velocity.speed_m_s() - 20.0).abs() < 1e-9);
+        let [x, y, z] = report.position_m();
+        assert!(x.abs() < 1e-9 && (y - target.y_m).abs() < 1e-9 && (z - 300.0).abs() < 1e-9);
+    }
+}
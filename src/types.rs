@@ -1,18 +1,191 @@
+use crate::cfar::Detection;
+use crate::classifier::{ClassProbability, FeatureVector};
+use crate::error::{AppError, AppResult};
+use crate::evaluation::{EvaluationConfig, EvaluationScan};
+use crate::geo::{GeodeticPosition, RadarSite};
+use crate::horizon::STANDARD_K_FACTOR;
+use crate::imm::{FlightBehavior, ModeProbability};
+use crate::mht::MhtConfig;
+use crate::micro_doppler::RotorModel;
+use crate::scenario::ScenarioConfig;
+use crate::swerling::SwerlingModel;
+use crate::tracking::{TrackEstimate, TrackerConfig};
+use crate::waveform::Waveform;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
 pub struct TargetPosition {
     /// Target identifier
     pub id: usize,
-    /// Range in meters
+    /// Slant range in meters
     pub range_m: f64,
     /// Azimuth angle in degrees (0-360)
     pub azimuth_deg: f64,
-    /// Velocity in meters per second
+    /// Elevation angle above the radar's horizontal plane in degrees
+    #[serde(default)]
+    pub elevation_deg: f64,
+    /// Height above the radar in meters; derived from the slant range and
+    /// elevation when absent
+    #[serde(default)]
+    pub altitude_m: Option<f64>,
+    /// Radial velocity in meters per second (positive = moving away); the
+    /// line-of-sight component of `velocity` when that is known
+    #[serde(default)]
+    pub vel_m_s: f64,
+    /// Velocity vector, when known; east-north-up when the target is given by
+    /// its `geodetic` position
+    #[serde(default)]
+    pub velocity: Option<Velocity3>,
+    /// Position on the WGS84 ellipsoid. With a configured radar site, a
+    /// target may be given by this instead of its range, azimuth, elevation,
+    /// altitude and radial velocity, which are then derived from it.
+    #[serde(default)]
+    pub geodetic: Option<GeodeticPosition>,
+    /// Mean radar cross section in square meters
+    pub rcs: f64,
+    /// RCS fluctuation model drawn from on every dwell
+    #[serde(default)]
+    pub swerling: SwerlingModel,
+    /// Rotor hub generating blade micro-Doppler (none for rigid targets)
+    #[serde(default)]
+    pub rotor: Option<RotorModel>,
+    /// IMM mode probabilities of the track this report comes from (empty when untracked)
+    #[serde(default)]
+    pub mode_probabilities: Vec<ModeProbability>,
+}
+
+impl TargetPosition {
+    /// Position in the radar's local frame: `[x east, y north, z up]`
+    pub fn position_m(&self) -> [f64; 3] {
+        let (azimuth, elevation) = (
+            self.azimuth_deg.to_radians(),
+            self.elevation_deg.to_radians(),
+        );
+        let ground = self.range_m * elevation.cos();
+        [
+            ground * azimuth.sin(),
+            ground * azimuth.cos(),
+            self.range_m * elevation.sin(),
+        ]
+    }
+
+    /// Height above the radar: the given altitude, or `range * sin(elevation)`
+    pub fn altitude_m(&self) -> f64 {
+        self.altitude_m.unwrap_or_else(|| self.position_m()[2])
+    }
+
+    /// Speed from the velocity vector, or the radial speed without one
+    pub fn speed_m_s(&self) -> f64 {
+        self.velocity
+            .map_or(self.vel_m_s.abs(), |velocity| velocity.speed_m_s())
+    }
+}
+
+/// Velocity in the radar's local frame: `x` east, `y` north, `z` up
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, ToSchema)]
+pub struct Velocity3 {
+    pub vx_m_s: f64,
+    pub vy_m_s: f64,
+    pub vz_m_s: f64,
+}
+
+impl Velocity3 {
+    /// Below this horizontal speed a target is treated as hovering and has no heading
+    pub const MIN_HEADING_SPEED_M_S: f64 = 0.5;
+
+    pub fn speed_m_s(&self) -> f64 {
+        self.vx_m_s.hypot(self.vy_m_s).hypot(self.vz_m_s)
+    }
+
+    /// Direction of horizontal motion in degrees clockwise from north
+    pub fn heading_deg(&self) -> Option<f64> {
+        (self.vx_m_s.hypot(self.vy_m_s) >= Self::MIN_HEADING_SPEED_M_S).then(|| {
+            self.vx_m_s
+                .atan2(self.vy_m_s)
+                .to_degrees()
+                .rem_euclid(360.0)
+        })
+    }
+
+    /// Component along the line of sight to `position_m` (positive = moving away)
+    pub fn radial_m_s(&self, position_m: [f64; 3]) -> f64 {
+        let [x, y, z] = position_m;
+        let range = x.hypot(y).hypot(z);
+        if range <= 0.0 {
+            return 0.0;
+        }
+        (x * self.vx_m_s + y * self.vy_m_s + z * self.vz_m_s) / range
+    }
+}
+
+/// Target to analyze, placed by its range and azimuth or, with a configured
+/// radar site, by its geodetic position
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AnalyzeRequest {
+    /// Target identifier
+    pub id: usize,
+    /// Slant range in meters; required unless `geodetic` is given
+    #[serde(default)]
+    pub range_m: Option<f64>,
+    /// Azimuth angle in degrees (0-360); required like `range_m`
+    #[serde(default)]
+    pub azimuth_deg: Option<f64>,
+    /// Elevation angle above the radar's horizontal plane in degrees
+    #[serde(default)]
+    pub elevation_deg: f64,
+    /// Height above the radar in meters; derived from the slant range and
+    /// elevation when absent
+    #[serde(default)]
+    pub altitude_m: Option<f64>,
+    /// Radial velocity in meters per second (positive = moving away)
+    #[serde(default)]
     pub vel_m_s: f64,
-    /// Radar cross section
+    /// Velocity vector, when known; east-north-up when the target is given by
+    /// its `geodetic` position
+    #[serde(default)]
+    pub velocity: Option<Velocity3>,
+    /// Position on the WGS84 ellipsoid, from which the range, azimuth,
+    /// elevation, altitude and radial velocity are derived
+    #[serde(default)]
+    pub geodetic: Option<GeodeticPosition>,
+    /// Mean radar cross section in square meters
     pub rcs: f64,
+    /// RCS fluctuation model
+    #[serde(default)]
+    pub swerling: SwerlingModel,
+    /// Rotor hub generating blade micro-Doppler (none for rigid targets)
+    #[serde(default)]
+    pub rotor: Option<RotorModel>,
+    /// IMM mode probabilities of the track this report comes from (empty when untracked)
+    #[serde(default)]
+    pub mode_probabilities: Vec<ModeProbability>,
+}
+
+impl AnalyzeRequest {
+    /// The target to analyze, or `None` when it is placed neither by range
+    /// and azimuth nor geodetically. A geodetic target's range and azimuth
+    /// are zero until it is resolved against the radar site.
+    pub fn into_target(self) -> Option<TargetPosition> {
+        let (range_m, azimuth_deg) = self
+            .range_m
+            .zip(self.azimuth_deg)
+            .or(self.geodetic.map(|_| (0.0, 0.0)))?;
+        Some(TargetPosition {
+            id: self.id,
+            range_m,
+            azimuth_deg,
+            elevation_deg: self.elevation_deg,
+            altitude_m: self.altitude_m,
+            vel_m_s: self.vel_m_s,
+            velocity: self.velocity,
+            geodetic: self.geodetic,
+            rcs: self.rcs,
+            swerling: self.swerling,
+            rotor: self.rotor,
+            mode_probabilities: self.mode_probabilities,
+        })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -20,9 +193,29 @@ pub struct TargetPosition {
 #[schema(as = utoipa::openapi::Object)]
 pub enum WebSocketMessage {
     #[serde(rename = "start_tracking")]
-    StartTracking,
-    #[serde(rename = "targets")]
-    Targets { targets: Vec<TargetPosition> },
+    StartTracking {
+        /// Scene to simulate detections in; targets only when absent
+        #[serde(default)]
+        scenario: Option<Box<ScenarioConfig>>,
+        /// Track with a multiple hypothesis tracker instead of the default
+        /// IMM tracker with global-nearest-neighbour association
+        #[serde(default)]
+        mht: Option<Box<MhtConfig>>,
+        /// Filter, association and lifecycle settings of the default tracker,
+        /// e.g. a particle filter for bearing-only measurements; ignored with `mht`
+        #[serde(default)]
+        tracker: Option<Box<TrackerConfig>>,
+        /// Radar site of this session, replacing the configured one
+        #[serde(default)]
+        site: Option<RadarSite>,
+        /// Attach each track's geodetic position; needs a radar site
+        #[serde(default)]
+        geodetic: bool,
+    },
+    #[serde(rename = "tracks")]
+    Tracks { tracks: Vec<TrackEstimate> },
+    #[serde(rename = "detections")]
+    Detections { detections: Vec<Detection> },
     #[serde(rename = "error")]
     Error { message: String },
     #[serde(rename = "status")]
@@ -35,26 +228,37 @@ pub struct DroneAnalysis {
     pub drone_id: usize,
     /// Threat level: "low", "medium", or "high"
     pub threat_level: String,
-    /// Estimated drone type
+    /// Most likely target class
     pub estimated_type: String,
-    /// Confidence score (0.0 to 1.0)
+    /// Probability of the most likely class (0.0 to 1.0)
     pub confidence: f64,
+    /// Probability of every class
+    pub class_probabilities: Vec<ClassProbability>,
+    /// Features the classification was based on
+    pub features: FeatureVector,
     /// Trajectory analysis results
     pub trajectory_analysis: TrajectoryAnalysis,
     /// Risk assessment results
     pub risk_assessment: RiskAssessment,
     /// List of recommendations
     pub recommendations: Vec<String>,
+    /// Position of the drone on the WGS84 ellipsoid, when a radar site is configured
+    #[serde(default)]
+    pub geodetic: Option<GeodeticPosition>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct TrajectoryAnalysis {
-    /// Heading in degrees
-    pub heading_deg: f64,
+    /// Heading in degrees clockwise from north, from the velocity vector;
+    /// none without one or while hovering. North is the radar's zero azimuth
+    /// unless a radar site is configured.
+    pub heading_deg: Option<f64>,
     /// Speed in meters per second
     pub speed_m_s: f64,
-    /// Estimated altitude in meters
+    /// Height above the radar in meters, from the target's altitude
     pub altitude_estimate_m: f64,
+    /// Current behavior from the track's IMM mode probabilities, if tracked
+    pub behavior: Option<FlightBehavior>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -66,3 +270,253 @@ pub struct RiskAssessment {
     /// Overall risk score (0-100)
     pub overall_risk: f64,
 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+pub struct RadarConfig {
+    /// Peak transmit power in watts
+    pub transmit_power_w: f64,
+    /// Transmit antenna gain in dBi
+    pub tx_gain_db: f64,
+    /// Receive antenna gain in dBi
+    pub rx_gain_db: f64,
+    /// Carrier wavelength in meters
+    pub wavelength_m: f64,
+    /// Receiver noise figure in dB
+    pub noise_figure_db: f64,
+    /// Total system losses in dB
+    pub system_losses_db: f64,
+    /// Receiver noise bandwidth in Hz
+    pub bandwidth_hz: f64,
+    /// Coherent/non-coherent integration gain in dB
+    #[serde(default)]
+    pub processing_gain_db: f64,
+}
+
+impl RadarConfig {
+    /// Reject a radar the range equation cannot be evaluated for
+    pub fn validate(&self) -> AppResult<()> {
+        if self.transmit_power_w <= 0.0 || self.wavelength_m <= 0.0 || self.bandwidth_hz <= 0.0 {
+            return Err(AppError::InvalidInput(format!(
+                "radar needs positive power, wavelength and bandwidth: {}W, {}m, {}Hz",
+                self.transmit_power_w, self.wavelength_m, self.bandwidth_hz
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for RadarConfig {
+    /// X-band drone surveillance radar
+    fn default() -> Self {
+        Self {
+            transmit_power_w: 1_000.0,
+            tx_gain_db: 35.0,
+            rx_gain_db: 35.0,
+            wavelength_m: 0.0316,
+            noise_figure_db: 3.0,
+            system_losses_db: 5.0,
+            bandwidth_hz: 1e6,
+            processing_gain_db: 20.0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LinkBudgetRequest {
+    /// Radar parameters
+    pub radar: RadarConfig,
+    /// Targets to evaluate
+    pub targets: Vec<TargetPosition>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TargetLinkBudget {
+    /// Target identifier
+    pub target_id: usize,
+    /// Range in meters
+    pub range_m: f64,
+    /// Radar cross section in square meters
+    pub rcs: f64,
+    /// Received echo power in dBm
+    pub received_power_dbm: f64,
+    /// Signal-to-noise ratio after processing gain in dB
+    pub snr_db: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LinkBudgetResponse {
+    /// Receiver noise power in dBm
+    pub noise_power_dbm: f64,
+    /// Per-target link budget
+    pub targets: Vec<TargetLinkBudget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RangeSweep {
+    /// First range in meters
+    pub start_m: f64,
+    /// Last range in meters
+    pub stop_m: f64,
+    /// Number of evenly spaced ranges
+    pub num_points: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RocRequest {
+    /// Radar parameters
+    pub radar: RadarConfig,
+    /// Mean target radar cross section in square meters
+    pub rcs: f64,
+    /// Target RCS fluctuation model
+    #[serde(default)]
+    pub swerling: SwerlingModel,
+    /// Ranges at which Pd is evaluated
+    pub range_sweep: RangeSweep,
+    /// False-alarm probability used for the Pd vs range curve
+    pub pfa: f64,
+    /// Ranges in meters at which ROC curves (Pd vs Pfa) are produced
+    #[serde(default)]
+    pub roc_ranges_m: Vec<f64>,
+    /// Pulses non-coherently integrated per dwell
+    #[serde(default = "default_num_pulses")]
+    pub num_pulses: usize,
+    /// Monte Carlo trials per point (at least one)
+    #[serde(default = "default_monte_carlo_trials")]
+    pub monte_carlo_trials: usize,
+    /// Random seed for reproducible Monte Carlo runs
+    #[serde(default)]
+    pub seed: u64,
+}
+
+fn default_num_pulses() -> usize {
+    1
+}
+
+fn default_monte_carlo_trials() -> usize {
+    2_000
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RangePerformance {
+    /// Range in meters
+    pub range_m: f64,
+    /// Single-pulse SNR in dB
+    pub snr_db: f64,
+    /// Analytic probability of detection
+    pub pd_analytic: f64,
+    /// Monte Carlo probability of detection
+    pub pd_monte_carlo: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RocPoint {
+    /// Probability of false alarm
+    pub pfa: f64,
+    /// Analytic probability of detection
+    pub pd_analytic: f64,
+    /// Monte Carlo probability of detection
+    pub pd_monte_carlo: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RocCurve {
+    /// Range in meters
+    pub range_m: f64,
+    /// Single-pulse SNR in dB at this range
+    pub snr_db: f64,
+    /// Pd vs Pfa points, ordered by increasing Pfa
+    pub points: Vec<RocPoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RocResponse {
+    /// Pd vs range at the requested Pfa
+    pub pd_vs_range: Vec<RangePerformance>,
+    /// ROC curves at the requested ranges
+    pub roc_curves: Vec<RocCurve>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AmbiguityRequest {
+    /// Waveform to evaluate
+    pub waveform: Waveform,
+    /// Complex sample rate used to synthesize the waveform, in Hz
+    pub sample_rate_hz: f64,
+    /// Largest delay on the grid in seconds (defaults to the pulse length)
+    #[serde(default)]
+    pub max_delay_s: Option<f64>,
+    /// Largest Doppler shift on the grid in Hz (defaults to 4 / pulse length)
+    #[serde(default)]
+    pub max_doppler_hz: Option<f64>,
+    /// Delay grid points, symmetric about zero
+    #[serde(default = "default_num_delay_points")]
+    pub num_delay_points: usize,
+    /// Doppler grid points, symmetric about zero
+    #[serde(default = "default_num_doppler_points")]
+    pub num_doppler_points: usize,
+}
+
+fn default_num_delay_points() -> usize {
+    201
+}
+
+fn default_num_doppler_points() -> usize {
+    101
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AmbiguityResponse {
+    /// Delay of each grid column in seconds
+    pub delays_s: Vec<f64>,
+    /// Doppler shift of each grid row in Hz
+    pub dopplers_hz: Vec<f64>,
+    /// Normalized ambiguity magnitude indexed as [Doppler row][delay column]; 1 at the origin
+    pub magnitude: Vec<Vec<f64>>,
+    /// Pulse length in seconds
+    pub duration_s: f64,
+    /// Nominal bandwidth in Hz
+    pub bandwidth_hz: f64,
+    /// Time-bandwidth product (pulse-compression gain)
+    pub time_bandwidth_product: f64,
+    /// -3 dB width of the zero-Doppler cut, in meters of range
+    pub range_resolution_m: f64,
+    /// -3 dB width of the zero-delay cut, in Hz
+    pub doppler_resolution_hz: f64,
+    /// Peak sidelobe level of the zero-Doppler cut in dB
+    pub peak_sidelobe_db: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EvaluationRequest {
+    /// OSPA/GOSPA cut-off and order
+    #[serde(default)]
+    pub config: EvaluationConfig,
+    /// Recorded scans of ground truth and published tracks, oldest first
+    pub scans: Vec<EvaluationScan>,
+}
+
+fn default_k_factor() -> f64 {
+    STANDARD_K_FACTOR
+}
+
+fn default_horizon_points() -> usize {
+    101
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HorizonQuery {
+    /// Antenna height above the ground in meters
+    pub antenna_height_m: f64,
+    /// Target altitude above the ground in meters
+    pub target_altitude_m: f64,
+    /// Effective earth radius factor (default 4/3)
+    #[serde(default = "default_k_factor")]
+    pub k_factor: f64,
+    /// Farthest ground range of the profile (default 1.5 times the radar horizon)
+    #[serde(default)]
+    pub max_range_m: Option<f64>,
+    /// Number of ground ranges in the profile (default 101)
+    #[serde(default = "default_horizon_points")]
+    pub points: usize,
+}
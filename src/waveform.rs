@@ -0,0 +1,684 @@
+use crate::constants::C;
+use crate::fft::{fft_padded, ifft_in_place};
+use crate::types::{AmbiguityRequest, AmbiguityResponse};
+use num_complex::Complex;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// Transmit waveform, synthesized as unit-amplitude complex baseband
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Waveform {
+    /// Linear-FM chirp sweeping `bandwidth_hz` about zero
+    Lfm {
+        bandwidth_hz: f64,
+        pulse_width_s: f64,
+    },
+    /// Binary Barker code of length 2, 3, 4, 5, 7, 11 or 13
+    Barker { length: usize, chip_width_s: f64 },
+    /// Frank polyphase code of `order²` chips
+    Frank { order: usize, chip_width_s: f64 },
+    /// P4 polyphase code (sampled LFM phase) of `length` chips
+    P4 { length: usize, chip_width_s: f64 },
+    /// Contiguous sub-pulses stepped in frequency by `step_hz`, centred on zero
+    SteppedFrequency {
+        num_steps: usize,
+        step_hz: f64,
+        subpulse_width_s: f64,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum WaveformError {
+    #[error("no Barker code of length {0}")]
+    UnsupportedBarkerLength(usize),
+
+    #[error("invalid waveform parameter: {0}")]
+    InvalidParameter(&'static str),
+}
+
+/// Known Barker sequences by length
+fn barker_code(length: usize) -> Option<&'static [i8]> {
+    Some(match length {
+        2 => &[1, -1],
+        3 => &[1, 1, -1],
+        4 => &[1, 1, -1, 1],
+        5 => &[1, 1, 1, -1, 1],
+        7 => &[1, 1, 1, -1, -1, 1, -1],
+        11 => &[1, 1, 1, -1, -1, -1, 1, -1, -1, 1, -1],
+        13 => &[1, 1, 1, 1, 1, -1, -1, 1, 1, -1, 1, -1, 1],
+        _ => return None,
+    })
+}
+
+impl Waveform {
+    /// Total pulse length in seconds
+    pub fn duration_s(&self) -> f64 {
+        match self {
+            Waveform::Lfm { pulse_width_s, .. } => *pulse_width_s,
+            Waveform::Barker {
+                length,
+                chip_width_s,
+            }
+            | Waveform::P4 {
+                length,
+                chip_width_s,
+            } => *length as f64 * chip_width_s,
+            Waveform::Frank {
+                order,
+                chip_width_s,
+            } => (*order as f64).powi(2) * chip_width_s,
+            Waveform::SteppedFrequency {
+                num_steps,
+                subpulse_width_s,
+                ..
+            } => *num_steps as f64 * subpulse_width_s,
+        }
+    }
+
+    /// Nominal bandwidth in Hz (sweep, chip rate or total frequency span)
+    pub fn bandwidth_hz(&self) -> f64 {
+        match self {
+            Waveform::Lfm { bandwidth_hz, .. } => *bandwidth_hz,
+            Waveform::Barker { chip_width_s, .. }
+            | Waveform::Frank { chip_width_s, .. }
+            | Waveform::P4 { chip_width_s, .. } => 1.0 / chip_width_s,
+            Waveform::SteppedFrequency {
+                num_steps, step_hz, ..
+            } => *num_steps as f64 * step_hz,
+        }
+    }
+
+    /// Time-bandwidth product, the pulse-compression gain
+    pub fn time_bandwidth_product(&self) -> f64 {
+        self.duration_s() * self.bandwidth_hz()
+    }
+
+    /// Number of samples [`samples`](Self::samples) produces at
+    /// `sample_rate_hz`: at least one per chip or sub-pulse, so a waveform of
+    /// many short chips needs more samples than its duration suggests. `None`
+    /// when the count overflows.
+    pub fn num_samples(&self, sample_rate_hz: f64) -> Option<usize> {
+        // Float-to-integer casts saturate, so huge durations overflow below
+        let count = |duration: f64| ((duration * sample_rate_hz).round() as usize).max(1);
+        match self {
+            Waveform::Lfm { pulse_width_s, .. } => Some(count(*pulse_width_s)),
+            Waveform::Barker {
+                length,
+                chip_width_s,
+            }
+            | Waveform::P4 {
+                length,
+                chip_width_s,
+            } => length.checked_mul(count(*chip_width_s)),
+            Waveform::Frank {
+                order,
+                chip_width_s,
+            } => order.checked_mul(*order)?.checked_mul(count(*chip_width_s)),
+            Waveform::SteppedFrequency {
+                num_steps,
+                subpulse_width_s,
+                ..
+            } => num_steps.checked_mul(count(*subpulse_width_s)),
+        }
+    }
+
+    /// Per-chip phases in radians for phase-coded waveforms
+    fn chip_phases(&self) -> Result<Vec<f64>, WaveformError> {
+        match self {
+            Waveform::Barker { length, .. } => barker_code(*length)
+                .map(|code| code.iter().map(|&c| if c > 0 { 0.0 } else { PI }).collect())
+                .ok_or(WaveformError::UnsupportedBarkerLength(*length)),
+            Waveform::Frank { order, .. } => {
+                let m = *order;
+                Ok((0..m * m)
+                    .map(|n| 2.0 * PI * ((n / m) * (n % m)) as f64 / m as f64)
+                    .collect())
+            }
+            Waveform::P4 { length, .. } => {
+                let n = *length as f64;
+                Ok((0..*length)
+                    .map(|i| {
+                        let i = i as f64;
+                        PI * i * i / n - PI * i
+                    })
+                    .collect())
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Complex baseband samples at `sample_rate_hz`, unit amplitude
+    pub fn samples(&self, sample_rate_hz: f64) -> Result<Vec<Complex<f64>>, WaveformError> {
+        if sample_rate_hz <= 0.0 {
+            return Err(WaveformError::InvalidParameter(
+                "sample rate must be positive",
+            ));
+        }
+        if self.num_samples(sample_rate_hz).is_none() {
+            return Err(WaveformError::InvalidParameter(
+                "too many samples to synthesize",
+            ));
+        }
+        let dt = 1.0 / sample_rate_hz;
+        let count = |duration: f64| (duration * sample_rate_hz).round() as usize;
+
+        match self {
+            Waveform::Lfm {
+                bandwidth_hz,
+                pulse_width_s,
+            } => {
+                if *pulse_width_s <= 0.0 || *bandwidth_hz < 0.0 {
+                    return Err(WaveformError::InvalidParameter(
+                        "LFM needs a positive pulse width and non-negative bandwidth",
+                    ));
+                }
+                let slope = bandwidth_hz / pulse_width_s;
+                Ok((0..count(*pulse_width_s).max(1))
+                    .map(|n| {
+                        let t = n as f64 * dt - 0.5 * pulse_width_s;
+                        Complex::from_polar(1.0, PI * slope * t * t)
+                    })
+                    .collect())
+            }
+            Waveform::Barker { chip_width_s, .. }
+            | Waveform::Frank { chip_width_s, .. }
+            | Waveform::P4 { chip_width_s, .. } => {
+                let phases = self.chip_phases()?;
+                if *chip_width_s <= 0.0 || phases.is_empty() {
+                    return Err(WaveformError::InvalidParameter(
+                        "phase codes need a positive chip width and at least one chip",
+                    ));
+                }
+                let per_chip = count(*chip_width_s).max(1);
+                Ok(phases
+                    .iter()
+                    .flat_map(|&phase| {
+                        std::iter::repeat_n(Complex::from_polar(1.0, phase), per_chip)
+                    })
+                    .collect())
+            }
+            Waveform::SteppedFrequency {
+                num_steps,
+                step_hz,
+                subpulse_width_s,
+            } => {
+                if *num_steps == 0 || *subpulse_width_s <= 0.0 {
+                    return Err(WaveformError::InvalidParameter(
+                        "stepped frequency needs steps and a positive sub-pulse width",
+                    ));
+                }
+                let per_step = count(*subpulse_width_s).max(1);
+                let centre = (*num_steps as f64 - 1.0) / 2.0;
+                Ok((0..num_steps * per_step)
+                    .map(|n| {
+                        let frequency = ((n / per_step) as f64 - centre) * step_hz;
+                        Complex::from_polar(1.0, 2.0 * PI * frequency * n as f64 * dt)
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Correlate `received` with a replica: `out[n] = sum_k received[n + k] conj(replica[k])`.
+///
+/// An echo starting at sample `n` peaks at output `n` with height equal to
+/// the replica energy; samples past the end of `received` are taken as zero.
+pub fn matched_filter(received: &[Complex<f64>], replica: &[Complex<f64>]) -> Vec<Complex<f64>> {
+    if received.is_empty() || replica.is_empty() {
+        return Vec::new();
+    }
+    let size = (received.len() + replica.len() - 1).next_power_of_two();
+    let x = fft_padded(received, size);
+    let h = fft_padded(replica, size);
+    let mut product: Vec<Complex<f64>> = x.iter().zip(&h).map(|(a, b)| a * b.conj()).collect();
+    ifft_in_place(&mut product);
+    product.truncate(received.len());
+    product
+}
+
+/// Magnitude of the narrowband ambiguity function at a delay of `lag` samples
+/// and a Doppler shift, normalised so the origin is 1:
+/// `|sum_n s[n] conj(s[n - lag]) exp(j 2 pi f_d n / fs)| / sum_n |s[n]|²`
+pub fn ambiguity(
+    samples: &[Complex<f64>],
+    sample_rate_hz: f64,
+    lag: isize,
+    doppler_hz: f64,
+) -> f64 {
+    let energy: f64 = samples.iter().map(|s| s.norm_sqr()).sum();
+    if energy == 0.0 {
+        return 0.0;
+    }
+    let len = samples.len() as isize;
+    let omega = 2.0 * PI * doppler_hz / sample_rate_hz;
+    let value: Complex<f64> = (lag.max(0)..len.min(len + lag))
+        .map(|n| {
+            samples[n as usize]
+                * samples[(n - lag) as usize].conj()
+                * Complex::from_polar(1.0, omega * n as f64)
+        })
+        .sum();
+    value.norm() / energy
+}
+
+/// Width of the mainlobe around `peak` where `values` stay above `level`,
+/// linearly interpolated between samples
+fn mainlobe_width(values: &[f64], peak: usize, level: f64) -> f64 {
+    let crossing = |step: isize| {
+        let mut i = peak as isize;
+        loop {
+            let next = i + step;
+            if next < 0 || next >= values.len() as isize {
+                return (i - peak as isize).abs() as f64;
+            }
+            let (a, b) = (values[i as usize], values[next as usize]);
+            if b < level {
+                return (i - peak as isize).abs() as f64 + (a - level) / (a - b);
+            }
+            i = next;
+        }
+    };
+    crossing(-1) + crossing(1)
+}
+
+/// Peak sidelobe level in dB of a magnitude response: the largest value
+/// outside the mainlobe (bounded by the first minimum on each side) relative
+/// to the peak
+pub fn peak_sidelobe_db(response: &[f64]) -> f64 {
+    let Some((peak, &top)) = response
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+    else {
+        return f64::NEG_INFINITY;
+    };
+    let mut left = peak;
+    while left > 0 && response[left - 1] < response[left] {
+        left -= 1;
+    }
+    let mut right = peak;
+    while right + 1 < response.len() && response[right + 1] < response[right] {
+        right += 1;
+    }
+    let sidelobe = response[..left]
+        .iter()
+        .chain(&response[right + 1..])
+        .cloned()
+        .fold(0.0, f64::max);
+    20.0 * (sidelobe / top).log10()
+}
+
+/// Largest ambiguity workload, in [`ambiguity_work`] units, a single request
+/// may ask for
+pub const MAX_AMBIGUITY_WORK: usize = 50_000_000;
+
+/// Doppler points of the zero-delay cut
+const ZERO_DELAY_POINTS: usize = 129;
+
+/// Ambiguity workload of a request: complex multiply-adds over every sample
+/// for each point of the grid and of the zero-Doppler and zero-delay cuts;
+/// `None` when the waveform has too many samples to count
+pub fn ambiguity_work(request: &AmbiguityRequest) -> Option<usize> {
+    let num_samples = request.waveform.num_samples(request.sample_rate_hz)?;
+    let points = request
+        .num_delay_points
+        .max(1)
+        .checked_mul(request.num_doppler_points.max(1))?
+        .checked_add(num_samples.checked_mul(2)?)?
+        .checked_add(ZERO_DELAY_POINTS)?;
+    num_samples.checked_mul(points)
+}
+
+/// Ambiguity surface on the requested delay/Doppler grid plus resolution and
+/// sidelobe figures taken from the zero-Doppler and zero-delay cuts at full
+/// sample resolution
+pub fn evaluate_ambiguity(request: &AmbiguityRequest) -> Result<AmbiguityResponse, WaveformError> {
+    let fs = request.sample_rate_hz;
+    let samples = request.waveform.samples(fs)?;
+    let len = samples.len() as isize;
+    let duration_s = samples.len() as f64 / fs;
+
+    let max_lag = request
+        .max_delay_s
+        .map(|d| (d * fs).round() as isize)
+        .unwrap_or(len - 1)
+        .clamp(0, len - 1);
+    let num_delays = request.num_delay_points.max(1);
+    let lags: Vec<isize> = (0..num_delays)
+        .map(|i| {
+            if num_delays == 1 {
+                0
+            } else {
+                (-max_lag as f64 + 2.0 * max_lag as f64 * i as f64 / (num_delays - 1) as f64)
+                    .round() as isize
+            }
+        })
+        .collect();
+    let max_doppler_hz = request.max_doppler_hz.unwrap_or(4.0 / duration_s);
+    let num_dopplers = request.num_doppler_points.max(1);
+    let dopplers_hz: Vec<f64> = (0..num_dopplers)
+        .map(|i| {
+            if num_dopplers == 1 {
+                0.0
+            } else {
+                -max_doppler_hz + 2.0 * max_doppler_hz * i as f64 / (num_dopplers - 1) as f64
+            }
+        })
+        .collect();
+
+    let magnitude: Vec<Vec<f64>> = dopplers_hz
+        .iter()
+        .map(|&f| {
+            lags.iter()
+                .map(|&k| ambiguity(&samples, fs, k, f))
+                .collect()
+        })
+        .collect();
+
+    // Zero-Doppler cut: the matched-filter response at every lag
+    let zero_doppler: Vec<f64> = (-(len - 1)..len)
+        .map(|k| ambiguity(&samples, fs, k, 0.0))
+        .collect();
+    let delay_width_s = mainlobe_width(&zero_doppler, (len - 1) as usize, 0.5f64.sqrt()) / fs;
+
+    // Zero-delay cut at an eighth of the Rayleigh Doppler resolution
+    let doppler_step = 1.0 / (8.0 * duration_s);
+    let half = (ZERO_DELAY_POINTS / 2) as isize;
+    let zero_delay: Vec<f64> = (-half..=half)
+        .map(|i| ambiguity(&samples, fs, 0, i as f64 * doppler_step))
+        .collect();
+    let doppler_width_hz = mainlobe_width(&zero_delay, half as usize, 0.5f64.sqrt()) * doppler_step;
+
+    Ok(AmbiguityResponse {
+        delays_s: lags.iter().map(|&k| k as f64 / fs).collect(),
+        dopplers_hz,
+        magnitude,
+        duration_s,
+        bandwidth_hz: request.waveform.bandwidth_hz(),
+        time_bandwidth_product: request.waveform.time_bandwidth_product(),
+        range_resolution_m: delay_width_s * C / 2.0,
+        doppler_resolution_hz: doppler_width_hz,
+        peak_sidelobe_db: peak_sidelobe_db(&zero_doppler),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: f64 = 20e6;
+
+    fn request(waveform: Waveform) -> AmbiguityRequest {
+        AmbiguityRequest {
+            waveform,
+            sample_rate_hz: FS,
+            max_delay_s: None,
+            max_doppler_hz: None,
+            num_delay_points: 41,
+            num_doppler_points: 21,
+        }
+    }
+
+    fn autocorrelation(waveform: &Waveform, fs: f64) -> Vec<f64> {
+        let s = waveform.samples(fs).unwrap();
+        let len = s.len() as isize;
+        (-(len - 1)..len)
+            .map(|k| ambiguity(&s, fs, k, 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_codes_are_unit_modulus_with_expected_length() {
+        let waveforms = [
+            (
+                Waveform::Barker {
+                    length: 13,
+                    chip_width_s: 1e-6,
+                },
+                13,
+            ),
+            (
+                Waveform::Frank {
+                    order: 4,
+                    chip_width_s: 1e-6,
+                },
+                16,
+            ),
+            (
+                Waveform::P4 {
+                    length: 25,
+                    chip_width_s: 1e-6,
+                },
+                25,
+            ),
+        ];
+        for (waveform, chips) in waveforms {
+            let s = waveform.samples(1e6).unwrap();
+            assert_eq!(s.len(), chips);
+            assert_eq!(waveform.num_samples(1e6), Some(chips));
+            assert!(s.iter().all(|c| (c.norm() - 1.0).abs() < 1e-12));
+            assert!((waveform.time_bandwidth_product() - chips as f64).abs() < 1e-9);
+        }
+        assert!(matches!(
+            Waveform::Barker {
+                length: 6,
+                chip_width_s: 1e-6
+            }
+            .samples(1e6),
+            Err(WaveformError::UnsupportedBarkerLength(6))
+        ));
+    }
+
+    #[test]
+    fn test_num_samples_counts_every_chip() {
+        // A billion 10 fs chips last 10 µs, but each still takes a sample
+        let p4 = Waveform::P4 {
+            length: 1_000_000_000,
+            chip_width_s: 1e-14,
+        };
+        assert_eq!(p4.num_samples(1e6), Some(1_000_000_000));
+        let stepped = Waveform::SteppedFrequency {
+            num_steps: 8,
+            step_hz: 1e6,
+            subpulse_width_s: 2e-6,
+        };
+        assert_eq!(stepped.num_samples(4e6), Some(64));
+        assert_eq!(
+            stepped.samples(4e6).unwrap().len(),
+            stepped.num_samples(4e6).unwrap()
+        );
+
+        // order² overflows instead of wrapping or panicking
+        let frank = Waveform::Frank {
+            order: usize::MAX / 2,
+            chip_width_s: 1e-6,
+        };
+        assert_eq!(frank.num_samples(1e6), None);
+        assert!(frank.duration_s().is_finite());
+        assert!(matches!(
+            frank.samples(1e6),
+            Err(WaveformError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_ambiguity_work_counts_grid_and_cuts() {
+        let mut request = request(Waveform::SteppedFrequency {
+            num_steps: 8,
+            step_hz: 1e6,
+            subpulse_width_s: 2e-6,
+        });
+        request.sample_rate_hz = 4e6;
+        // 64 samples over 41 x 21 grid points, two per sample for the lag cut
+        // and 129 Dopplers
+        assert_eq!(
+            ambiguity_work(&request),
+            Some(64 * (41 * 21 + 2 * 64 + 129))
+        );
+
+        // The longest waveform the handler accepts, on its largest grid
+        request.waveform = Waveform::P4 {
+            length: 4096,
+            chip_width_s: 1e-6,
+        };
+        request.sample_rate_hz = 1e6;
+        request.num_delay_points = 512;
+        request.num_doppler_points = 512;
+        assert!(ambiguity_work(&request).unwrap() > MAX_AMBIGUITY_WORK);
+    }
+
+    #[test]
+    fn test_barker_sidelobes_are_one_over_length() {
+        // One sample per chip: every off-peak lag of a Barker code has |r| <= 1
+        for length in [5, 7, 11, 13] {
+            let waveform = Waveform::Barker {
+                length,
+                chip_width_s: 1e-6,
+            };
+            let r = autocorrelation(&waveform, 1e6);
+            let expected = -20.0 * (length as f64).log10();
+            assert!(
+                (peak_sidelobe_db(&r) - expected).abs() < 1e-9,
+                "Barker {length}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_polyphase_codes_beat_lfm_sidelobes() {
+        let lfm = Waveform::Lfm {
+            bandwidth_hz: 1e6,
+            pulse_width_s: 64e-6,
+        };
+        let frank = Waveform::Frank {
+            order: 8,
+            chip_width_s: 1e-6,
+        };
+        let p4 = Waveform::P4 {
+            length: 64,
+            chip_width_s: 1e-6,
+        };
+        // Oversampled so the LFM is not itself reduced to a P4-like chip sequence
+        let psl = |w: &Waveform| peak_sidelobe_db(&autocorrelation(w, 4e6));
+        assert!(psl(&lfm) > -16.0, "LFM {}", psl(&lfm));
+        assert!(psl(&frank) < -20.0, "Frank {}", psl(&frank));
+        assert!(psl(&p4) < -20.0, "P4 {}", psl(&p4));
+    }
+
+    #[test]
+    fn test_matched_filter_peaks_at_echo_delay() {
+        let replica = Waveform::P4 {
+            length: 32,
+            chip_width_s: 1e-7,
+        }
+        .samples(FS)
+        .unwrap();
+        let mut received = vec![Complex::new(0.0, 0.0); 400];
+        for (i, s) in replica.iter().enumerate() {
+            received[123 + i] += s * 0.5;
+        }
+        let out = matched_filter(&received, &replica);
+        let (peak, value) = out
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.norm().total_cmp(&b.1.norm()))
+            .unwrap();
+        assert_eq!(peak, 123);
+        assert!((value.norm() - 0.5 * replica.len() as f64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lfm_resolution_and_range_doppler_coupling() {
+        let bandwidth = 5e6;
+        let width = 20e-6;
+        let lfm = Waveform::Lfm {
+            bandwidth_hz: bandwidth,
+            pulse_width_s: width,
+        };
+        let response = evaluate_ambiguity(&request(lfm.clone())).unwrap();
+        // -3 dB widths: 0.886 / B in delay and 0.886 / T in Doppler
+        let expected_range = 0.886 / bandwidth * C / 2.0;
+        assert!((response.range_resolution_m - expected_range).abs() / expected_range < 0.1);
+        let expected_doppler = 0.886 / width;
+        assert!((response.doppler_resolution_hz - expected_doppler).abs() / expected_doppler < 0.1);
+
+        // A Doppler shift moves the ridge peak along delay by f_d / slope
+        let samples = lfm.samples(FS).unwrap();
+        let slope = bandwidth / width;
+        let doppler = 200e3;
+        let best_lag = (-200..200)
+            .max_by(|&a, &b| {
+                ambiguity(&samples, FS, a, doppler).total_cmp(&ambiguity(&samples, FS, b, doppler))
+            })
+            .unwrap();
+        let expected_lag = doppler / slope * FS;
+        assert!(
+            (best_lag.abs() as f64 - expected_lag).abs() <= 2.0,
+            "lag {best_lag}"
+        );
+        assert!(ambiguity(&samples, FS, best_lag, doppler) > 0.9);
+    }
+
+    #[test]
+    fn test_barker_is_doppler_intolerant() {
+        // Phase codes lose their match within a fraction of 1/T; the LFM ridge does not
+        let barker = Waveform::Barker {
+            length: 13,
+            chip_width_s: 1e-6,
+        };
+        let lfm = Waveform::Lfm {
+            bandwidth_hz: 1e6,
+            pulse_width_s: 13e-6,
+        };
+        let doppler = 1.0 / 13e-6;
+        let best = |w: &Waveform| {
+            let s = w.samples(FS).unwrap();
+            (-400..400)
+                .map(|k| ambiguity(&s, FS, k, doppler))
+                .fold(0.0, f64::max)
+        };
+        assert!(best(&barker) < 0.5);
+        assert!(best(&lfm) > 0.85);
+    }
+
+    #[test]
+    fn test_stepped_frequency_resolution_follows_total_span() {
+        let stepped = Waveform::SteppedFrequency {
+            num_steps: 10,
+            step_hz: 1e6,
+            subpulse_width_s: 2e-6,
+        };
+        let single = Waveform::SteppedFrequency {
+            num_steps: 1,
+            step_hz: 1e6,
+            subpulse_width_s: 20e-6,
+        };
+        let fine = evaluate_ambiguity(&request(stepped)).unwrap();
+        let coarse = evaluate_ambiguity(&request(single)).unwrap();
+        assert!(fine.range_resolution_m * 5.0 < coarse.range_resolution_m);
+        assert!((fine.bandwidth_hz - 10e6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_surface_grid_shape_and_peak() {
+        let response = evaluate_ambiguity(&request(Waveform::Frank {
+            order: 4,
+            chip_width_s: 0.5e-6,
+        }))
+        .unwrap();
+        assert_eq!(response.delays_s.len(), 41);
+        assert_eq!(response.dopplers_hz.len(), 21);
+        assert_eq!(response.magnitude.len(), 21);
+        assert!(response.magnitude.iter().all(|row| row.len() == 41));
+        assert!((response.magnitude[10][20] - 1.0).abs() < 1e-9);
+        assert!(response
+            .magnitude
+            .iter()
+            .flatten()
+            .all(|&m| m <= 1.0 + 1e-9));
+    }
+}